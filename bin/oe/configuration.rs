@@ -29,6 +29,7 @@ use ethcore::{
     client::VMType,
     miner::{stratum, MinerOptions},
     snapshot::SnapshotConfiguration,
+    spec::HbbftUnavailabilityAction,
     verification::queue::VerifierSettings,
 };
 use ethereum_types::{Address, H256, U256};
@@ -459,6 +460,7 @@ impl Configuration {
             local_accounts: HashSet::from_iter(
                 to_addresses(&self.args.arg_tx_queue_locals)?.into_iter(),
             ),
+            hbbft_unavailability_action: self.hbbft_unavailability_action()?,
         };
 
         Ok(extras)
@@ -472,6 +474,19 @@ impl Configuration {
         to_address(self.args.arg_engine_signer.clone())
     }
 
+    fn hbbft_unavailability_action(&self) -> Result<Option<HbbftUnavailabilityAction>, String> {
+        match self.args.arg_hbbft_unavailability_action.as_ref().map(String::as_str) {
+            None => Ok(None),
+            Some("shutdown") => Ok(Some(HbbftUnavailabilityAction::Shutdown)),
+            Some("warn") => Ok(Some(HbbftUnavailabilityAction::Warn)),
+            Some("reannounce") => Ok(Some(HbbftUnavailabilityAction::Reannounce)),
+            Some(other) => Err(format!(
+                "Invalid hbbft unavailability action: {}. Expected one of: shutdown, warn, reannounce.",
+                other
+            )),
+        }
+    }
+
     fn format(&self) -> Result<Option<DataFormat>, String> {
         match self
             .args
@@ -1605,6 +1620,27 @@ mod tests {
         assert!(conf.miner_options().is_err());
     }
 
+    #[test]
+    fn should_parse_hbbft_unavailability_action() {
+        let conf0 = parse(&["openethereum"]);
+        let conf1 = parse(&[
+            "openethereum",
+            "--hbbft-unavailability-action",
+            "reannounce",
+        ]);
+        let conf2 = parse(&["openethereum", "--hbbft-unavailability-action", "restart"]);
+
+        assert_eq!(
+            conf0.miner_extras().unwrap().hbbft_unavailability_action,
+            None
+        );
+        assert_eq!(
+            conf1.miner_extras().unwrap().hbbft_unavailability_action,
+            Some(HbbftUnavailabilityAction::Reannounce)
+        );
+        assert!(conf2.miner_extras().is_err());
+    }
+
     #[test]
     fn should_parse_network_settings() {
         // given
@@ -56,6 +56,8 @@ struct ExitStatus {
     panicking: bool,
     /// Whether the program should exit.
     should_exit: bool,
+    /// Exit code requested by a graceful shutdown of the client.
+    exit_code: Option<i32>,
 }
 
 fn main() -> Result<(), i32> {
@@ -96,6 +98,7 @@ fn main() -> Result<(), i32> {
         Mutex::new(ExitStatus {
             panicking: false,
             should_exit: false,
+            exit_code: None,
         }),
         Condvar::new(),
     ));
@@ -125,6 +128,7 @@ fn main() -> Result<(), i32> {
                             *e.0.lock() = ExitStatus {
                                 panicking: true,
                                 should_exit: true,
+                                exit_code: None,
                             };
                             e.1.notify_all();
                         }
@@ -139,6 +143,23 @@ fn main() -> Result<(), i32> {
                             *e.0.lock() = ExitStatus {
                                 panicking: false,
                                 should_exit: true,
+                                exit_code: None,
+                            };
+                            e.1.notify_all();
+                        }
+                    }
+                });
+
+                // the engine may request a graceful shutdown, e.g. if this validator has been flagged as unavailable.
+                client.set_shutdown_handler({
+                    let e = exit.clone();
+                    let exiting = exiting.clone();
+                    move |reason| {
+                        if !exiting.swap(true, Ordering::SeqCst) {
+                            *e.0.lock() = ExitStatus {
+                                panicking: false,
+                                should_exit: true,
+                                exit_code: Some(reason.exit_code()),
                             };
                             e.1.notify_all();
                         }
@@ -169,6 +190,14 @@ fn main() -> Result<(), i32> {
                 if lock.panicking {
                     return Err(1);
                 }
+
+                if let Some(exit_code) = lock.exit_code {
+                    warn!(
+                        "Client shut down on request, exiting with code {}",
+                        exit_code
+                    );
+                    process::exit(exit_code);
+                }
             }
         },
         Err(err) => {
@@ -2,6 +2,8 @@ use std::{sync::Arc, time::Instant};
 
 use crate::{futures::Future, rpc, rpc_apis};
 
+use ethcore::client::EngineInfo;
+
 use parking_lot::Mutex;
 
 use hyper::{service::service_fn_ok, Body, Method, Request, Response, Server, StatusCode};
@@ -52,6 +54,7 @@ fn handle_request(
             let state = state.lock();
             state.rpc_apis.client.prometheus_metrics(&mut reg);
             state.rpc_apis.sync.prometheus_metrics(&mut reg);
+            state.rpc_apis.client.engine().prometheus_metrics(&mut reg);
             let elapsed = start.elapsed();
             reg.register_gauge(
                 "metrics_time",
@@ -26,7 +26,7 @@ use crate::{
 use ethcore::{
     client::Mode,
     ethereum,
-    spec::{Spec, SpecParams},
+    spec::{HbbftUnavailabilityAction, Spec, SpecParams},
 };
 use ethereum_types::{Address, U256};
 use fetch::Client as FetchClient;
@@ -295,6 +295,7 @@ pub struct MinerExtras {
     pub gas_range_target: (U256, U256),
     pub work_notify: Vec<String>,
     pub local_accounts: HashSet<Address>,
+    pub hbbft_unavailability_action: Option<HbbftUnavailabilityAction>,
 }
 
 impl Default for MinerExtras {
@@ -306,6 +307,7 @@ impl Default for MinerExtras {
             gas_range_target: (8_000_000.into(), 10_000_000.into()),
             work_notify: Default::default(),
             local_accounts: Default::default(),
+            hbbft_unavailability_action: None,
         }
     }
 }
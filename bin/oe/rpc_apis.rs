@@ -71,6 +71,8 @@ pub enum Api {
     /// Geth-compatible (best-effort) debug API (Potentially UNSAFE)
     /// NOTE We don't aim to support all methods, only the ones that are useful.
     Debug,
+    /// HoneyBadgerBFT validator and consensus status (Semi-safe: reveals the validator identity of the node)
+    Hbbft,
 }
 
 impl FromStr for Api {
@@ -82,6 +84,7 @@ impl FromStr for Api {
         match s {
             "debug" => Ok(Debug),
             "eth" => Ok(Eth),
+            "hbbft" => Ok(Hbbft),
             "net" => Ok(Net),
             "parity" => Ok(Parity),
             "parity_accounts" => Ok(ParityAccounts),
@@ -163,6 +166,7 @@ fn to_modules(apis: &HashSet<Api>) -> BTreeMap<String, String> {
             Api::Debug => ("debug", "1.0"),
             Api::Eth => ("eth", "1.0"),
             Api::EthPubSub => ("pubsub", "1.0"),
+            Api::Hbbft => ("hbbft", "1.0"),
             Api::Net => ("net", "1.0"),
             Api::Parity => ("parity", "1.0"),
             Api::ParityAccounts => ("parity_accounts", "1.0"),
@@ -270,6 +274,9 @@ impl FullDependencies {
                 Api::Net => {
                     handler.extend_with(NetClient::new(&self.sync).to_delegate());
                 }
+                Api::Hbbft => {
+                    handler.extend_with(HbbftClient::new(self.client.clone()).to_delegate());
+                }
                 Api::Eth => {
                     let client = EthClient::new(
                         &self.client,
@@ -470,10 +477,12 @@ impl ApiSet {
                 public_list.insert(Api::Traces);
                 public_list.insert(Api::ParityPubSub);
                 public_list.insert(Api::ParityAccounts);
+                public_list.insert(Api::Hbbft);
                 public_list
             }
             ApiSet::All => {
                 public_list.insert(Api::Debug);
+                public_list.insert(Api::Hbbft);
                 public_list.insert(Api::Traces);
                 public_list.insert(Api::ParityPubSub);
                 public_list.insert(Api::ParityAccounts);
@@ -508,6 +517,7 @@ mod test {
         assert_eq!(Api::Net, "net".parse().unwrap());
         assert_eq!(Api::Eth, "eth".parse().unwrap());
         assert_eq!(Api::EthPubSub, "pubsub".parse().unwrap());
+        assert_eq!(Api::Hbbft, "hbbft".parse().unwrap());
         assert_eq!(Api::Personal, "personal".parse().unwrap());
         assert_eq!(Api::Signer, "signer".parse().unwrap());
         assert_eq!(Api::Parity, "parity".parse().unwrap());
@@ -564,6 +574,7 @@ mod test {
             Api::Rpc,
             // semi-safe
             Api::ParityAccounts,
+            Api::Hbbft,
         ]
         .into_iter()
         .collect();
@@ -590,6 +601,7 @@ mod test {
                     Api::Signer,
                     Api::Personal,
                     Api::Debug,
+                    Api::Hbbft,
                 ]
                 .into_iter()
                 .collect()
@@ -616,6 +628,7 @@ mod test {
                     Api::ParitySet,
                     Api::Signer,
                     Api::Debug,
+                    Api::Hbbft,
                 ]
                 .into_iter()
                 .collect()
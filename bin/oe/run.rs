@@ -36,17 +36,19 @@ use crate::{
         GasPricerConfig, MinerExtras, Pruning, SpecType, Switch,
     },
     rpc, rpc_apis, secretstore, signer,
-    sync::{self, SyncConfig, SyncProvider},
+    sync::{self, ManageNetwork, SyncConfig, SyncProvider},
     user_defaults::UserDefaults,
 };
 use ansi_term::Colour;
 use dir::{DatabaseDirectories, Directories};
 use ethcore::{
     client::{
-        BlockChainClient, BlockInfo, ChainSyncing, Client, DatabaseCompactionProfile, Mode, VMType,
+        BlockChainClient, BlockInfo, ChainSyncing, Client, DatabaseCompactionProfile, Mode,
+        ShutdownReason, VMType,
     },
     miner::{self, stratum, Miner, MinerOptions, MinerService},
     snapshot::{self, SnapshotConfiguration},
+    spec::SpecParams,
     verification::queue::VerifierSettings,
 };
 use ethcore_logger::{Config as LogConfig, RotatingLogger};
@@ -168,7 +170,9 @@ impl ChainSyncing for SyncProviderWrapper {
 /// On error, returns what to print on stderr.
 pub fn execute(cmd: RunCmd, logger: Arc<RotatingLogger>) -> Result<RunningClient, String> {
     // load spec
-    let spec = cmd.spec.spec(&cmd.dirs.cache)?;
+    let mut spec_params = SpecParams::from_path(&cmd.dirs.cache);
+    spec_params.hbbft_unavailability_action = cmd.miner_extras.hbbft_unavailability_action;
+    let spec = cmd.spec.spec(spec_params)?;
 
     // load genesis hash
     let genesis_hash = spec.genesis_header().hash();
@@ -641,6 +645,7 @@ pub fn execute(cmd: RunCmd, logger: Arc<RotatingLogger>) -> Result<RunningClient
             informant,
             client,
             client_service: Arc::new(service),
+            manage_network,
             keep_alive: Box::new((
                 watcher,
                 ws_server,
@@ -681,6 +686,7 @@ enum RunningClientInner {
         informant: Arc<Informant<FullNodeInformantData>>,
         client: Arc<Client>,
         client_service: Arc<ClientService>,
+        manage_network: Arc<dyn ManageNetwork>,
         keep_alive: Box<dyn Any>,
     },
 }
@@ -703,6 +709,16 @@ impl RunningClient {
         }
     }
 
+    /// Sets the closure to call when the engine requests the node to shut down.
+    pub fn set_shutdown_handler<F>(&self, f: F)
+    where
+        F: 'static + Fn(ShutdownReason) + Send,
+    {
+        match self.inner {
+            RunningClientInner::Full { ref client, .. } => client.set_shutdown_handler(f),
+        }
+    }
+
     /// Shuts down the client.
     pub fn shutdown(self) {
         match self.inner {
@@ -711,9 +727,13 @@ impl RunningClient {
                 informant,
                 client,
                 client_service,
+                manage_network,
                 keep_alive,
             } => {
                 info!("Finishing work, please wait...");
+                // Stop networking first, so no more blocks and consensus messages are received.
+                manage_network.stop_network();
+                trace!(target: "shutdown", "Network stopped");
                 // Create a weak reference to the client so that we can wait on shutdown
                 // until it is dropped
                 let weak_client = Arc::downgrade(&client);
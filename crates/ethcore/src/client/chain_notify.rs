@@ -199,4 +199,14 @@ pub trait ChainNotify: Send + Sync {
     fn transactions_received(&self, _txs: &[UnverifiedTransaction], _peer_id: usize) {
         // does nothing by default
     }
+
+    /// fires when the engine requests a node to be kept connected as a reserved peer
+    fn add_reserved_peer(&self, _enode: &str) {
+        // does nothing by default
+    }
+
+    /// fires when the engine requests a node to no longer be kept connected as a reserved peer
+    fn remove_reserved_peer(&self, _enode: &str) {
+        // does nothing by default
+    }
 }
@@ -52,12 +52,12 @@ use types::{
     log_entry::LocalizedLogEntry,
     receipt::{LocalizedReceipt, TypedReceipt},
     transaction::{
-        self, Action, LocalizedTransaction, SignedTransaction, TypedTransaction,
-        UnverifiedTransaction,
+        self, AccessList, AccessListTx, Action, LocalizedTransaction, SignedTransaction,
+        TypedTransaction, UnverifiedTransaction,
     },
     BlockNumber,
 };
-use vm::{EnvInfo, LastHashes};
+use vm::{EnvInfo, Error as VmError, LastHashes};
 
 use ansi_term::Colour;
 use block::{enact_verified, ClosedBlock, Drain, LockedBlock, OpenBlock, SealedBlock};
@@ -65,7 +65,7 @@ use call_contract::RegistryInfo;
 use client::{
     ancient_import::AncientVerifier,
     bad_blocks,
-    traits::{ChainSyncing, ForceUpdateSealing, TransactionRequest},
+    traits::{ChainSyncing, ForceUpdateSealing, ShutdownReason, TransactionRequest},
     AccountData, BadBlocks, Balance, BlockChain as BlockChainTrait, BlockChainClient,
     BlockChainReset, BlockId, BlockInfo, BlockProducer, BroadcastProposalBlock, Call,
     CallAnalytics, ChainInfo, ChainMessageType, ChainNotify, ChainRoute, ClientConfig,
@@ -92,7 +92,8 @@ use state::{self, State};
 use state_db::StateDB;
 use stats::{PrometheusMetrics, PrometheusRegistry};
 use trace::{
-    self, Database as TraceDatabase, ImportRequest as TraceImportRequest, LocalizedTrace, TraceDB,
+    self, AccessListTracer, Database as TraceDatabase, ImportRequest as TraceImportRequest,
+    LocalizedTrace, NoopVMTracer, TraceDB,
 };
 use transaction_ext::Transaction;
 use verification::{
@@ -114,6 +115,8 @@ const ANCIENT_BLOCKS_QUEUE_SIZE: usize = 4096;
 const ANCIENT_BLOCKS_BATCH_SIZE: usize = 4;
 const MAX_QUEUE_SIZE_TO_SLEEP_ON: usize = 2;
 const MIN_HISTORY_SIZE: u64 = 8;
+// Max number of calls made to let an access list converge.
+const MAX_ACCESS_LIST_ITERATIONS: usize = 16;
 
 /// Report on the status of a client.
 #[derive(Default, Clone, Debug, Eq, PartialEq)]
@@ -254,6 +257,9 @@ pub struct Client {
     /// A closure to call when we want to restart the client
     exit_handler: Mutex<Option<Box<dyn Fn(String) + 'static + Send>>>,
 
+    /// A closure to call when the engine requests the node to shut down
+    shutdown_handler: Mutex<Option<Box<dyn Fn(ShutdownReason) + 'static + Send>>>,
+
     /// Accessor to query chain syncing state.
     sync_provider: Mutex<Option<Box<dyn ChainSyncing>>>,
 
@@ -1047,6 +1053,7 @@ impl Client {
             on_user_defaults_change: Mutex::new(None),
             registrar_address,
             exit_handler: Mutex::new(None),
+            shutdown_handler: Mutex::new(None),
             sync_provider: Mutex::new(None),
             importer,
             config,
@@ -1144,6 +1151,10 @@ impl Client {
             abe.as_mut().unwrap().end()
         }
         *abe = None;
+
+        if let Err(e) = self.db.read().key_value().flush() {
+            warn!(target: "shutdown", "Failed to flush the database on shutdown: {}", e);
+        }
     }
 
     /// Wakes up client if it's a sleep.
@@ -1192,6 +1203,14 @@ impl Client {
         *self.on_user_defaults_change.lock() = Some(Box::new(f));
     }
 
+    /// Sets the closure to call when the engine requests the node to shut down.
+    pub fn set_shutdown_handler<F>(&self, f: F)
+    where
+        F: 'static + Fn(ShutdownReason) + Send,
+    {
+        *self.shutdown_handler.lock() = Some(Box::new(f));
+    }
+
     /// Flush the block import queue.
     pub fn flush_queue(&self) {
         self.importer.block_queue.flush();
@@ -2161,6 +2180,85 @@ impl Call for Client {
         trace!(target: "estimate_gas", "estimate_gas chopping {} .. {}", lower, upper);
         binary_chop(lower, upper, cond)
     }
+
+    fn create_access_list(
+        &self,
+        t: &SignedTransaction,
+        state: &Self::State,
+        header: &Header,
+    ) -> Result<(AccessList, U256, Option<VmError>), CallError> {
+        let env_info = EnvInfo {
+            number: header.number(),
+            author: header.author().clone(),
+            timestamp: header.timestamp(),
+            difficulty: header.difficulty().clone(),
+            last_hashes: self.build_last_hashes(header.parent_hash()),
+            gas_used: U256::default(),
+            gas_limit: U256::max_value(),
+            //if gas pricing is not defined, force base_fee to zero
+            base_fee: if t.effective_gas_price(header.base_fee()).is_zero() {
+                Some(0.into())
+            } else {
+                header.base_fee()
+            },
+        };
+        let machine = self.engine.machine();
+        let schedule = machine.schedule(env_info.number);
+
+        // The sender, the recipient, the precompiles and since EIP-3651 the coinbase are warm anyway.
+        let sender = t.sender();
+        let mut excluded: HashSet<Address> = machine
+            .builtins()
+            .iter()
+            .filter(|(_, builtin)| builtin.is_active(env_info.number))
+            .map(|(address, _)| *address)
+            .collect();
+        excluded.insert(sender);
+        if let Action::Call(to) = t.tx().action {
+            excluded.insert(to);
+        }
+        if schedule.eip3651 {
+            excluded.insert(env_info.author);
+        }
+
+        // The list changes the gas costs and thereby possibly the accesses, so repeat the call
+        // with the last list until it does not change anymore. A failing call still accessed
+        // the accounts up to its failure, so its list converges the same way.
+        let mut access_list = t.access_list().cloned().unwrap_or_default();
+        for _ in 0..MAX_ACCESS_LIST_ITERATIONS {
+            let tx = match t.as_unsigned().clone() {
+                TypedTransaction::Legacy(tx) => {
+                    TypedTransaction::AccessList(AccessListTx::new(tx, access_list.clone()))
+                }
+                TypedTransaction::AccessList(mut tx) => {
+                    tx.access_list = access_list.clone();
+                    TypedTransaction::AccessList(tx)
+                }
+                TypedTransaction::EIP1559Transaction(mut tx) => {
+                    tx.transaction.access_list = access_list.clone();
+                    TypedTransaction::EIP1559Transaction(tx)
+                }
+            }
+            .fake_sign(sender);
+
+            let mut clone = state.clone();
+            let options =
+                TransactOptions::new(AccessListTracer::new(excluded.clone()), NoopVMTracer)
+                    .dont_check_nonce();
+            let executed = Executive::new(&mut clone, &env_info, &machine, &schedule)
+                .transact_virtual(&tx, options)?;
+            if executed.trace == access_list {
+                return Ok((access_list, executed.gas_used, executed.exception));
+            }
+            access_list = executed.trace;
+        }
+
+        let err = ExecutionError::Internal(format!(
+            "Access list did not converge within {} calls",
+            MAX_ACCESS_LIST_ITERATIONS
+        ));
+        Err(err.into())
+    }
 }
 
 impl EngineInfo for Client {
@@ -2966,6 +3064,14 @@ impl IoClient for Client {
             }
         }
     }
+
+    fn peer_connected(&self, node_id: H512) {
+        self.engine.on_peer_connected(node_id);
+    }
+
+    fn peer_disconnected(&self, node_id: H512) {
+        self.engine.on_peer_disconnected(node_id);
+    }
 }
 
 impl ReopenBlock for Client {
@@ -3215,6 +3321,56 @@ impl super::traits::EngineClient for Client {
             .miner
             .create_pending_block_at(self, txns, timestamp, block_number)
     }
+
+    fn request_shutdown(&self, reason: ShutdownReason) {
+        if let Some(ref h) = *self.shutdown_handler.lock() {
+            info!(target: "shutdown", "Shutdown requested: {:?}", reason);
+            (*h)(reason);
+        } else {
+            warn!(target: "shutdown", "Shutdown requested ({:?}), but no shutdown handler is set.", reason);
+        }
+    }
+
+    fn engine_data(&self, key: &[u8]) -> Option<Bytes> {
+        match self
+            .db
+            .read()
+            .key_value()
+            .get(::db::COL_EXTRA, &engine_data_key(key))
+        {
+            Ok(value) => value.map(|value| value.to_vec()),
+            Err(e) => {
+                warn!(target: "client", "Failed to read engine data: {}", e);
+                None
+            }
+        }
+    }
+
+    fn set_engine_data(&self, key: &[u8], value: Bytes) {
+        let mut batch = DBTransaction::new();
+        batch.put(::db::COL_EXTRA, &engine_data_key(key), &value);
+        if let Err(e) = self.db.read().key_value().write(batch) {
+            warn!(target: "client", "Failed to store engine data: {}", e);
+        }
+    }
+
+    fn add_reserved_peer(&self, enode: String) {
+        self.notify(|notify| notify.add_reserved_peer(&enode));
+    }
+
+    fn remove_reserved_peer(&self, enode: String) {
+        self.notify(|notify| notify.remove_reserved_peer(&enode));
+    }
+}
+
+/// Prefix of the keys of engine data in the extras column,
+/// which keeps them apart from the keys of the blockchain extras.
+const ENGINE_DATA_KEY_PREFIX: &[u8] = b"engine-data:";
+
+fn engine_data_key(key: &[u8]) -> Vec<u8> {
+    let mut db_key = ENGINE_DATA_KEY_PREFIX.to_vec();
+    db_key.extend_from_slice(key);
+    db_key
 }
 
 impl ProvingBlockChainClient for Client {
@@ -3655,6 +3811,26 @@ mod tests {
     use spec::Spec;
     use test_helpers::generate_dummy_client_with_spec_and_data;
 
+    #[test]
+    fn should_call_shutdown_handler_on_request() {
+        use client::{EngineClient, ShutdownReason};
+        use parking_lot::Mutex;
+        use std::sync::Arc;
+        use test_helpers::generate_dummy_client;
+
+        let client = generate_dummy_client(0);
+        // Without a handler the request is ignored.
+        client.request_shutdown(ShutdownReason::ValidatorUnavailable);
+
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let handler_requests = requests.clone();
+        client.set_shutdown_handler(move |reason| handler_requests.lock().push(reason));
+        client.request_shutdown(ShutdownReason::ValidatorUnavailable);
+
+        assert_eq!(*requests.lock(), vec![ShutdownReason::ValidatorUnavailable]);
+        assert_ne!(ShutdownReason::ValidatorUnavailable.exit_code(), 1);
+    }
+
     #[test]
     fn should_not_cache_details_before_commit() {
         use client::{BlockChainClient, ChainInfo};
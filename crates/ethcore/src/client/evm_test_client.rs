@@ -109,7 +109,14 @@ impl<'a> EvmTestClient<'a> {
             ForkSpec::Berlin => Some(ethereum::new_berlin_test()),
             ForkSpec::London => Some(ethereum::new_london_test()),
             ForkSpec::BerlinToLondonAt5 => Some(ethereum::new_berlin_to_london_test()),
-            ForkSpec::FrontierToHomesteadAt5
+            ForkSpec::Shanghai => Some(ethereum::new_shanghai_test()),
+            ForkSpec::ShanghaiEIP1153EIP5656EIP6780 => {
+                Some(ethereum::new_shanghai_eip1153_eip5656_eip6780_test())
+            }
+            // Blob transactions (EIP-4844), the beacon block root (EIP-4788) and BLOBBASEFEE (EIP-7516)
+            // are not implemented.
+            ForkSpec::Cancun
+            | ForkSpec::FrontierToHomesteadAt5
             | ForkSpec::HomesteadToDaoAt5
             | ForkSpec::HomesteadToEIP150At5
             | ForkSpec::ByzantiumToConstantinopleAt5 => None,
@@ -41,7 +41,7 @@ pub use self::{
         BlockProducer, BroadcastProposalBlock, Call, ChainInfo, ChainSyncing, EngineClient,
         EngineInfo, ImportBlock, ImportExportBlocks, ImportSealedBlock, IoClient, Nonce,
         PrepareOpenBlock, ProvingBlockChainClient, ReopenBlock, ScheduleInfo, SealedBlockImporter,
-        StateClient, StateOrBlock, TransactionInfo,
+        ShutdownReason, StateClient, StateOrBlock, TransactionInfo,
     },
 };
 pub use state::StateInfo;
@@ -17,7 +17,7 @@
 //! Test client.
 
 use std::{
-    collections::{BTreeMap, HashMap},
+    collections::{BTreeMap, BTreeSet, HashMap},
     str::FromStr,
     sync::{
         atomic::{AtomicBool, AtomicUsize, Ordering as AtomicOrder},
@@ -47,19 +47,19 @@ use types::{
     pruning_info::PruningInfo,
     receipt::{LegacyReceipt, LocalizedReceipt, TransactionOutcome, TypedReceipt},
     transaction::{
-        self, Action, LocalizedTransaction, SignedTransaction, Transaction, TypedTransaction,
-        TypedTxId,
+        self, AccessList, Action, LocalizedTransaction, SignedTransaction, Transaction,
+        TypedTransaction, TypedTxId,
     },
     view,
     views::BlockView,
     BlockNumber,
 };
-use vm::Schedule;
+use vm::{Error as VmError, Schedule};
 
 use block::{ClosedBlock, OpenBlock, SealedBlock};
 use call_contract::{CallContract, RegistryInfo};
 use client::{
-    traits::{ForceUpdateSealing, TransactionRequest},
+    traits::{ForceUpdateSealing, ShutdownReason, TransactionRequest},
     AccountData, BadBlocks, Balance, BlockChain, BlockChainClient, BlockChainInfo, BlockId,
     BlockInfo, BlockProducer, BlockStatus, BroadcastProposalBlock, Call, CallAnalytics, ChainInfo,
     EngineInfo, ImportBlock, ImportSealedBlock, IoClient, LastHashes, Mode, Nonce,
@@ -73,7 +73,7 @@ use executive::Executed;
 use journaldb;
 use miner::{self, Miner, MinerService};
 use spec::Spec;
-use state::StateInfo;
+use state::{AccountOverride, Overridable, StateInfo};
 use state_db::StateDB;
 use stats::{PrometheusMetrics, PrometheusRegistry};
 use trace::LocalizedTrace;
@@ -129,6 +129,12 @@ pub struct TestBlockChainClient {
     pub disabled: AtomicBool,
     /// Transaction hashes producer
     pub new_transaction_hashes: RwLock<Option<crossbeam_channel::Sender<H256>>>,
+    /// Shutdown requests issued by the engine.
+    pub shutdown_requests: RwLock<Vec<ShutdownReason>>,
+    /// Data stored by the engine.
+    pub engine_data: RwLock<HashMap<Vec<u8>, Bytes>>,
+    /// Enode URLs of the reserved peers requested by the engine.
+    pub reserved_peers: RwLock<BTreeSet<String>>,
 }
 
 /// Used for generating test client blocks.
@@ -199,6 +205,9 @@ impl TestBlockChainClient {
             disabled: AtomicBool::new(false),
             error_on_logs: RwLock::new(None),
             new_transaction_hashes: RwLock::new(None),
+            shutdown_requests: RwLock::new(Vec::new()),
+            engine_data: RwLock::new(HashMap::new()),
+            reserved_peers: RwLock::new(BTreeSet::new()),
         };
 
         // insert genesis hash.
@@ -703,6 +712,15 @@ impl Call for TestBlockChainClient {
     ) -> Result<U256, CallError> {
         Ok(21000.into())
     }
+
+    fn create_access_list(
+        &self,
+        _t: &SignedTransaction,
+        _state: &Self::State,
+        _header: &Header,
+    ) -> Result<(AccessList, U256, Option<VmError>), CallError> {
+        Ok((Vec::new(), 21000.into(), None))
+    }
 }
 
 /// NewType wrapper around `()` to impersonate `State` in trait impls. State will not be used by
@@ -723,6 +741,16 @@ impl StateInfo for TestState {
     }
 }
 
+impl Overridable for TestState {
+    fn apply_override(
+        &mut self,
+        _address: &Address,
+        _account_override: &AccountOverride,
+    ) -> ethtrie::Result<()> {
+        Ok(())
+    }
+}
+
 impl StateClient for TestBlockChainClient {
     // State will not be used by test client anyway, since all methods that accept state are mocked
     type State = TestState;
@@ -1160,6 +1188,14 @@ impl IoClient for TestBlockChainClient {
     fn queue_consensus_message(&self, message: Bytes, node_id: Option<H512>) {
         self.spec.engine.handle_message(&message, node_id).unwrap();
     }
+
+    fn peer_connected(&self, node_id: H512) {
+        self.spec.engine.on_peer_connected(node_id);
+    }
+
+    fn peer_disconnected(&self, node_id: H512) {
+        self.spec.engine.on_peer_disconnected(node_id);
+    }
 }
 
 impl ProvingBlockChainClient for TestBlockChainClient {
@@ -1230,6 +1266,26 @@ impl super::traits::EngineClient for TestBlockChainClient {
         self.miner
             .create_pending_block_at(self, txns, timestamp, block_number)
     }
+
+    fn request_shutdown(&self, reason: ShutdownReason) {
+        self.shutdown_requests.write().push(reason);
+    }
+
+    fn engine_data(&self, key: &[u8]) -> Option<Bytes> {
+        self.engine_data.read().get(key).cloned()
+    }
+
+    fn set_engine_data(&self, key: &[u8], value: Bytes) {
+        self.engine_data.write().insert(key.to_vec(), value);
+    }
+
+    fn add_reserved_peer(&self, enode: String) {
+        self.reserved_peers.write().insert(enode);
+    }
+
+    fn remove_reserved_peer(&self, enode: String) {
+        self.reserved_peers.write().remove(&enode);
+    }
 }
 
 impl PrometheusMetrics for TestBlockChainClient {
@@ -40,10 +40,10 @@ use types::{
     pruning_info::PruningInfo,
     receipt::LocalizedReceipt,
     trace_filter::Filter as TraceFilter,
-    transaction::{self, Action, LocalizedTransaction, SignedTransaction, TypedTxId},
+    transaction::{self, AccessList, Action, LocalizedTransaction, SignedTransaction, TypedTxId},
     BlockNumber,
 };
-use vm::LastHashes;
+use vm::{Error as VmError, LastHashes};
 
 use block::{ClosedBlock, OpenBlock, SealedBlock};
 use client::Mode;
@@ -200,6 +200,15 @@ pub trait Call {
         state: &Self::State,
         header: &Header,
     ) -> Result<U256, CallError>;
+
+    /// Creates the EIP-2930 access list of a call, repeating the call until the list converges.
+    /// Returns the list, the gas used by the call with it and the error the call failed with.
+    fn create_access_list(
+        &self,
+        t: &SignedTransaction,
+        state: &Self::State,
+        header: &Header,
+    ) -> Result<(AccessList, U256, Option<VmError>), CallError>;
 }
 
 /// Provides `engine` method
@@ -231,6 +240,12 @@ pub trait IoClient: Sync + Send {
 
     /// Queue conensus engine message.
     fn queue_consensus_message(&self, message: Bytes, node_id: Option<H512>);
+
+    /// Notify the consensus engine that a peer connected.
+    fn peer_connected(&self, node_id: H512);
+
+    /// Notify the consensus engine that a peer disconnected.
+    fn peer_disconnected(&self, node_id: H512);
 }
 
 /// Provides recently seen bad blocks.
@@ -610,6 +625,22 @@ pub enum ForceUpdateSealing {
     No,
 }
 
+/// Reasons for the engine to request the node to shut down.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ShutdownReason {
+    /// The node is a staked validator that has been flagged as unavailable.
+    ValidatorUnavailable,
+}
+
+impl ShutdownReason {
+    /// Process exit code, distinct from the exit codes of crashes and configuration errors.
+    pub fn exit_code(&self) -> i32 {
+        match *self {
+            ShutdownReason::ValidatorUnavailable => 3,
+        }
+    }
+}
+
 /// Client facilities used by internally sealing Engines.
 pub trait EngineClient: Sync + Send + ChainInfo {
     /// Make a new block and seal it.
@@ -650,6 +681,22 @@ pub trait EngineClient: Sync + Send + ChainInfo {
         timestamp: u64,
         block_number: u64,
     ) -> Option<Header>;
+
+    /// Request a graceful shutdown of the node.
+    fn request_shutdown(&self, reason: ShutdownReason);
+
+    /// Get data the engine stored in the client database.
+    fn engine_data(&self, key: &[u8]) -> Option<Bytes>;
+
+    /// Store data of the engine in the client database.
+    /// The data is kept across restarts and is not part of the consensus.
+    fn set_engine_data(&self, key: &[u8], value: Bytes);
+
+    /// Keep the node with the given enode URL connected as a reserved peer.
+    fn add_reserved_peer(&self, enode: String);
+
+    /// Stop keeping the node with the given enode URL connected as a reserved peer.
+    fn remove_reserved_peer(&self, enode: String);
 }
 
 /// Extended client interface for providing proofs of the state.
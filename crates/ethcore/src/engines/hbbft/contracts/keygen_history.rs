@@ -2,7 +2,10 @@ use client::traits::EngineClient;
 use crypto::{self, publickey::Public};
 use engines::{
     hbbft::{
-        contracts::validator_set::{get_validator_pubkeys, ValidatorType},
+        contracts::{
+            validator_set::{get_validator_pubkeys, ValidatorType},
+            ContractAddresses, SystemContract,
+        },
         utils::bound_contract::{BoundContract, CallError},
         NodeId,
     },
@@ -19,7 +22,8 @@ use hbbft::{
 };
 use itertools::Itertools;
 use parking_lot::RwLock;
-use std::{collections::BTreeMap, str::FromStr, sync::Arc};
+use rand_065::{self, Rng};
+use std::{collections::BTreeMap, sync::Arc};
 use types::ids::BlockId;
 
 use_contract!(
@@ -27,9 +31,14 @@ use_contract!(
     "res/contracts/key_history_contract.json"
 );
 
-lazy_static! {
-    pub static ref KEYGEN_HISTORY_ADDRESS: Address =
-        Address::from_str("7000000000000000000000000000000000000001").unwrap();
+/// Binds the key history contract used for the given block.
+fn keygen_history_contract<'a>(
+    client: &'a dyn EngineClient,
+    contracts: &ContractAddresses,
+    block_id: BlockId,
+) -> Result<BoundContract<'a>, CallError> {
+    let address = contracts.address_for_call(client, SystemContract::KeygenHistory, block_id)?;
+    Ok(BoundContract::bind(client, block_id, address))
 }
 
 macro_rules! call_const_key_history {
@@ -38,9 +47,10 @@ macro_rules! call_const_key_history {
 	};
 }
 
-pub fn engine_signer_to_synckeygen<'a>(
+pub fn engine_signer_to_synckeygen<'a, R: Rng>(
     signer: &Arc<RwLock<Option<Box<dyn EngineSigner>>>>,
     pub_keys: PubKeyMap<Public, PublicWrapper>,
+    rng: &mut R,
 ) -> Result<(SyncKeyGen<Public, PublicWrapper>, Option<Part>), Error> {
     let wrapper = KeyPairWrapper {
         inner: signer.clone(),
@@ -51,9 +61,8 @@ pub fn engine_signer_to_synckeygen<'a>(
             .expect("Signer's public key must be available!"),
         None => Public::from(H512::from_low_u64_be(0)),
     };
-    let mut rng = rand_065::thread_rng();
     let num_nodes = pub_keys.len();
-    SyncKeyGen::new(public, wrapper, pub_keys, max_faulty(num_nodes), &mut rng)
+    SyncKeyGen::new(public, wrapper, pub_keys, max_faulty(num_nodes), rng)
 }
 
 pub fn synckeygen_to_network_info(
@@ -86,23 +95,24 @@ pub fn synckeygen_to_network_info(
     ))
 }
 
-pub fn part_of_address(
+pub fn part_of_address<R: Rng>(
     client: &dyn EngineClient,
+    contracts: &ContractAddresses,
     address: Address,
     vmap: &BTreeMap<Address, Public>,
     skg: &mut SyncKeyGen<Public, PublicWrapper>,
     block_id: BlockId,
+    rng: &mut R,
 ) -> Result<Option<Ack>, CallError> {
-    let c = BoundContract::bind(client, block_id, *KEYGEN_HISTORY_ADDRESS);
+    let c = keygen_history_contract(client, contracts, block_id)?;
     let serialized_part = call_const_key_history!(c, parts, address)?;
     //println!("Part for address {}: {:?}", address, serialized_part);
     if serialized_part.is_empty() {
         return Ok(None);
     }
     let deserialized_part: Part = bincode::deserialize(&serialized_part).unwrap();
-    let mut rng = rand_065::thread_rng();
     let outcome = skg
-        .handle_part(vmap.get(&address).unwrap(), deserialized_part, &mut rng)
+        .handle_part(vmap.get(&address).unwrap(), deserialized_part, rng)
         .unwrap();
 
     match outcome {
@@ -111,29 +121,34 @@ pub fn part_of_address(
     }
 }
 
-pub fn get_current_key_gen_round(client: &dyn EngineClient) -> Result<U256, CallError> {
-    let c = BoundContract::bind(client, BlockId::Latest, *KEYGEN_HISTORY_ADDRESS);
+pub fn get_current_key_gen_round(
+    client: &dyn EngineClient,
+    contracts: &ContractAddresses,
+) -> Result<U256, CallError> {
+    let c = keygen_history_contract(client, contracts, BlockId::Latest)?;
     let serialized_result = call_const_key_history!(c, get_current_key_gen_round)?;
     Ok(U256::from(serialized_result))
 }
 
 pub fn has_acks_of_address_data(
     client: &dyn EngineClient,
+    contracts: &ContractAddresses,
     address: Address,
 ) -> Result<bool, CallError> {
-    let c = BoundContract::bind(client, BlockId::Latest, *KEYGEN_HISTORY_ADDRESS);
+    let c = keygen_history_contract(client, contracts, BlockId::Latest)?;
     let serialized_length = call_const_key_history!(c, get_acks_length, address)?;
     Ok(serialized_length.low_u64() != 0)
 }
 
 pub fn acks_of_address(
     client: &dyn EngineClient,
+    contracts: &ContractAddresses,
     address: Address,
     vmap: &BTreeMap<Address, Public>,
     skg: &mut SyncKeyGen<Public, PublicWrapper>,
     block_id: BlockId,
 ) -> Result<(), CallError> {
-    let c = BoundContract::bind(client, block_id, *KEYGEN_HISTORY_ADDRESS);
+    let c = keygen_history_contract(client, contracts, block_id)?;
     let serialized_length = call_const_key_history!(c, get_acks_length, address)?;
 
     // println!(
@@ -192,8 +207,19 @@ impl<'a> SecretKey for KeyPairWrapper {
     }
 }
 
+/// Returns the number of Parts and Acks written to the key history contract.
+pub fn get_number_of_key_fragments_written(
+    client: &dyn EngineClient,
+    contracts: &ContractAddresses,
+    block_id: BlockId,
+) -> Result<(U256, U256), CallError> {
+    let c = keygen_history_contract(client, contracts, block_id)?;
+    call_const_key_history!(c, get_number_of_key_fragments_written)
+}
+
 pub fn all_parts_acks_available(
     client: &dyn EngineClient,
+    contracts: &ContractAddresses,
     block_timestamp: u64,
     num_validators: usize,
 ) -> Result<bool, CallError> {
@@ -212,8 +238,8 @@ pub fn all_parts_acks_available(
         return Ok(true);
     }
 
-    let c = BoundContract::bind(client, BlockId::Latest, *KEYGEN_HISTORY_ADDRESS);
-    let (num_parts, num_acks) = call_const_key_history!(c, get_number_of_key_fragments_written)?;
+    let (num_parts, num_acks) =
+        get_number_of_key_fragments_written(client, contracts, BlockId::Latest)?;
     Ok(num_parts.low_u64() == (num_validators as u64)
         && num_acks.low_u64() == (num_validators) as u64)
 }
@@ -221,26 +247,39 @@ pub fn all_parts_acks_available(
 /// Read available keygen data from the blockchain and initialize a SyncKeyGen instance with it.
 pub fn initialize_synckeygen(
     client: &dyn EngineClient,
+    contracts: &ContractAddresses,
     signer: &Arc<RwLock<Option<Box<dyn EngineSigner>>>>,
     block_id: BlockId,
     validator_type: ValidatorType,
 ) -> Result<SyncKeyGen<Public, PublicWrapper>, CallError> {
-    let vmap = get_validator_pubkeys(&*client, block_id, validator_type)?;
+    let vmap = get_validator_pubkeys(&*client, contracts, block_id, validator_type)?;
     let pub_keys: BTreeMap<_, _> = vmap
         .values()
         .map(|p| (*p, PublicWrapper { inner: p.clone() }))
         .collect();
 
+    // Our own Part and the Acks created here are discarded, the key only depends on the
+    // Parts and Acks on the chain. So they do not need to be reproducible.
+    let mut rng = rand_065::thread_rng();
+
     // if synckeygen creation fails then either signer or validator pub keys are problematic.
     // Todo: We should expect up to f clients to write invalid pub keys. Report and re-start pending validator set selection.
-    let (mut synckeygen, _) = engine_signer_to_synckeygen(signer, Arc::new(pub_keys))
+    let (mut synckeygen, _) = engine_signer_to_synckeygen(signer, Arc::new(pub_keys), &mut rng)
         .map_err(|_| CallError::ReturnValueInvalid)?;
 
     for v in vmap.keys().sorted() {
-        part_of_address(&*client, *v, &vmap, &mut synckeygen, block_id)?;
+        part_of_address(
+            &*client,
+            contracts,
+            *v,
+            &vmap,
+            &mut synckeygen,
+            block_id,
+            &mut rng,
+        )?;
     }
     for v in vmap.keys().sorted() {
-        acks_of_address(&*client, *v, &vmap, &mut synckeygen, block_id)?;
+        acks_of_address(&*client, contracts, *v, &vmap, &mut synckeygen, block_id)?;
     }
 
     Ok(synckeygen)
@@ -251,7 +290,7 @@ mod tests {
     use super::*;
     use crypto::publickey::{KeyPair, Secret};
     use engines::signer::{from_keypair, EngineSigner};
-    use std::{collections::BTreeMap, sync::Arc};
+    use std::{collections::BTreeMap, str::FromStr, sync::Arc};
 
     #[test]
     fn test_synckeygen_initialization() {
@@ -273,6 +312,11 @@ mod tests {
         let mut pub_keys: BTreeMap<Public, PublicWrapper> = BTreeMap::new();
         pub_keys.insert(public, wrapper);
 
-        assert!(engine_signer_to_synckeygen(&signer, Arc::new(pub_keys)).is_ok());
+        assert!(engine_signer_to_synckeygen(
+            &signer,
+            Arc::new(pub_keys),
+            &mut rand_065::thread_rng()
+        )
+        .is_ok());
     }
 }
@@ -1,3 +1,165 @@
+use client::traits::EngineClient;
+use engines::hbbft::utils::bound_contract::CallError;
+use ethereum_types::Address;
+use ethjson::spec::HbbftParams;
+use std::{collections::BTreeMap, str::FromStr};
+use types::{ids::BlockId, BlockNumber};
+
 pub mod keygen_history;
 pub mod staking;
 pub mod validator_set;
+
+/// The hbbft system contracts.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SystemContract {
+    ValidatorSet,
+    Staking,
+    KeygenHistory,
+}
+
+/// Addresses of the hbbft system contracts, mapped by the block from which on they are used.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ContractAddresses {
+    validator_set: BTreeMap<BlockNumber, Address>,
+    staking: BTreeMap<BlockNumber, Address>,
+    keygen_history: BTreeMap<BlockNumber, Address>,
+}
+
+impl Default for ContractAddresses {
+    /// The addresses of the system contracts in the genesis block of the DMD networks.
+    fn default() -> Self {
+        let genesis = |address: &str| {
+            let mut transitions = BTreeMap::new();
+            transitions.insert(0, Address::from_str(address).unwrap());
+            transitions
+        };
+        ContractAddresses {
+            validator_set: genesis("1000000000000000000000000000000000000001"),
+            staking: genesis("1100000000000000000000000000000000000001"),
+            keygen_history: genesis("7000000000000000000000000000000000000001"),
+        }
+    }
+}
+
+impl ContractAddresses {
+    /// Returns the default addresses, overridden by the transitions configured in the engine parameters.
+    pub fn from_params(params: &HbbftParams) -> Self {
+        let mut addresses = ContractAddresses::default();
+        let configured = vec![
+            (
+                &mut addresses.validator_set,
+                &params.validator_set_contract_address,
+            ),
+            (&mut addresses.staking, &params.staking_contract_address),
+            (
+                &mut addresses.keygen_history,
+                &params.keygen_history_contract_address,
+            ),
+        ];
+        for (transitions, configured) in configured {
+            if let Some(configured) = configured {
+                transitions.extend(
+                    configured
+                        .iter()
+                        .map(|(block, address)| (block.0.as_u64(), *address)),
+                );
+            }
+        }
+        addresses
+    }
+
+    /// Returns the address of the contract used for the given block.
+    pub fn address_at(&self, contract: SystemContract, block_number: BlockNumber) -> Address {
+        let transitions = match contract {
+            SystemContract::ValidatorSet => &self.validator_set,
+            SystemContract::Staking => &self.staking,
+            SystemContract::KeygenHistory => &self.keygen_history,
+        };
+        transitions
+            .range(..=block_number)
+            .last()
+            .map(|(_, address)| *address)
+            .expect("all contracts have an address from the genesis block on; qed")
+    }
+
+    /// Returns the address of the contract for calls on the state of the given block.
+    /// Fails if the block is not known to the client.
+    pub fn address_for_call(
+        &self,
+        client: &dyn EngineClient,
+        contract: SystemContract,
+        block_id: BlockId,
+    ) -> Result<Address, CallError> {
+        let block_number = client.block_number(block_id).ok_or_else(|| {
+            CallError::CallFailed(format!(
+                "Unknown block {:?} for calling {:?}",
+                block_id, contract
+            ))
+        })?;
+        Ok(self.address_at(contract, block_number))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_contract_address_transitions() {
+        let params: HbbftParams = serde_json::from_str(
+            r#"{
+				"minimumBlockTime": 0,
+				"maximumBlockTime": 600,
+				"transactionQueueSizeTrigger": 1,
+				"stakingContractAddress": {
+					"100": "0x1100000000000000000000000000000000000002"
+				},
+				"keygenHistoryContractAddress": {
+					"0": "0x7000000000000000000000000000000000000002"
+				}
+			}"#,
+        )
+        .unwrap();
+        let addresses = ContractAddresses::from_params(&params);
+        let defaults = ContractAddresses::default();
+
+        assert_eq!(
+            addresses.address_at(SystemContract::ValidatorSet, 1000),
+            defaults.address_at(SystemContract::ValidatorSet, 0)
+        );
+        assert_eq!(
+            addresses.address_at(SystemContract::Staking, 99),
+            defaults.address_at(SystemContract::Staking, 0)
+        );
+        assert_eq!(
+            addresses.address_at(SystemContract::Staking, 100),
+            Address::from_str("1100000000000000000000000000000000000002").unwrap()
+        );
+        assert_eq!(
+            addresses.address_at(SystemContract::KeygenHistory, 0),
+            Address::from_str("7000000000000000000000000000000000000002").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_address_for_call_of_unknown_block() {
+        use client::TestBlockChainClient;
+        use ethereum_types::H256;
+
+        let client = TestBlockChainClient::new();
+        let addresses = ContractAddresses::default();
+        assert_eq!(
+            addresses
+                .address_for_call(&client, SystemContract::Staking, BlockId::Latest)
+                .unwrap(),
+            addresses.address_at(SystemContract::Staking, 0)
+        );
+        assert!(addresses
+            .address_for_call(
+                &client,
+                SystemContract::Staking,
+                BlockId::Hash(H256::from_low_u64_be(1))
+            )
+            .is_err());
+    }
+}
@@ -1,14 +1,22 @@
 use client::EngineClient;
-use engines::hbbft::utils::bound_contract::{BoundContract, CallError};
+use engines::hbbft::{
+    contracts::{ContractAddresses, SystemContract},
+    utils::bound_contract::{BoundContract, CallError},
+};
 use ethereum_types::{Address, U256};
-use std::str::FromStr;
+use std::net::{IpAddr, Ipv6Addr};
 use types::ids::BlockId;
 
 use_contract!(staking_contract, "res/contracts/staking_contract.json");
 
-lazy_static! {
-    static ref STAKING_CONTRACT_ADDRESS: Address =
-        Address::from_str("1100000000000000000000000000000000000001").unwrap();
+/// Binds the staking contract used for the given block.
+fn staking_contract<'a>(
+    client: &'a dyn EngineClient,
+    contracts: &ContractAddresses,
+    block_id: BlockId,
+) -> Result<BoundContract<'a>, CallError> {
+    let address = contracts.address_for_call(client, SystemContract::Staking, block_id)?;
+    Ok(BoundContract::bind(client, block_id, address))
 }
 
 macro_rules! call_const_staking {
@@ -17,35 +25,47 @@ macro_rules! call_const_staking {
 		};
 	}
 
-pub fn get_posdao_epoch(client: &dyn EngineClient, block_id: BlockId) -> Result<U256, CallError> {
-    let c = BoundContract::bind(client, block_id, *STAKING_CONTRACT_ADDRESS);
+pub fn get_posdao_epoch(
+    client: &dyn EngineClient,
+    contracts: &ContractAddresses,
+    block_id: BlockId,
+) -> Result<U256, CallError> {
+    let c = staking_contract(client, contracts, block_id)?;
     call_const_staking!(c, staking_epoch)
 }
 
 pub fn get_posdao_epoch_start(
     client: &dyn EngineClient,
+    contracts: &ContractAddresses,
     block_id: BlockId,
 ) -> Result<U256, CallError> {
-    let c = BoundContract::bind(client, block_id, *STAKING_CONTRACT_ADDRESS);
+    let c = staking_contract(client, contracts, block_id)?;
     call_const_staking!(c, staking_epoch_start_block)
 }
 
-pub fn start_time_of_next_phase_transition(client: &dyn EngineClient) -> Result<U256, CallError> {
-    let c = BoundContract::bind(client, BlockId::Latest, *STAKING_CONTRACT_ADDRESS);
+pub fn start_time_of_next_phase_transition(
+    client: &dyn EngineClient,
+    contracts: &ContractAddresses,
+) -> Result<U256, CallError> {
+    let c = staking_contract(client, contracts, BlockId::Latest)?;
     call_const_staking!(c, start_time_of_next_phase_transition)
 }
 
-pub fn candidate_min_stake(client: &dyn EngineClient) -> Result<U256, CallError> {
-    let c = BoundContract::bind(client, BlockId::Latest, *STAKING_CONTRACT_ADDRESS);
+pub fn candidate_min_stake(
+    client: &dyn EngineClient,
+    contracts: &ContractAddresses,
+) -> Result<U256, CallError> {
+    let c = staking_contract(client, contracts, BlockId::Latest)?;
     call_const_staking!(c, candidate_min_stake)
 }
 
 pub fn stake_amount(
     client: &dyn EngineClient,
+    contracts: &ContractAddresses,
     staking_address: &Address,
     owner_address: &Address,
 ) -> Result<U256, CallError> {
-    let c = BoundContract::bind(client, BlockId::Latest, *STAKING_CONTRACT_ADDRESS);
+    let c = staking_contract(client, contracts, BlockId::Latest)?;
     call_const_staking!(
         c,
         stake_amount,
@@ -54,22 +74,51 @@ pub fn stake_amount(
     )
 }
 
+/// Returns the internet address of the pool's node, or `None` if the pool did not register one.
+/// IPv4 addresses are stored as IPv4-mapped IPv6 addresses.
+pub fn get_pool_internet_address(
+    client: &dyn EngineClient,
+    contracts: &ContractAddresses,
+    staking_address: &Address,
+) -> Result<Option<IpAddr>, CallError> {
+    let c = staking_contract(client, contracts, BlockId::Latest)?;
+    let address = call_const_staking!(c, get_pool_internet_address, staking_address.clone())?;
+    let address: &[u8] = address.as_ref();
+    if address.len() != 16 {
+        return Err(CallError::ReturnValueInvalid);
+    }
+    let mut octets = [0u8; 16];
+    octets.copy_from_slice(address);
+    let ip = Ipv6Addr::from(octets);
+    if ip.is_unspecified() {
+        return Ok(None);
+    }
+    Ok(Some(match ip.to_ipv4() {
+        Some(ipv4) => IpAddr::V4(ipv4),
+        None => IpAddr::V6(ip),
+    }))
+}
+
 #[cfg(test)]
 pub mod tests {
     use super::*;
     use crypto::publickey::{Generator, KeyPair, Public, Random};
     use engines::hbbft::test::hbbft_test_client::HbbftTestClient;
 
-    pub fn min_staking(client: &dyn EngineClient) -> Result<U256, CallError> {
-        let c = BoundContract::bind(client, BlockId::Latest, *STAKING_CONTRACT_ADDRESS);
+    pub fn min_staking(
+        client: &dyn EngineClient,
+        contracts: &ContractAddresses,
+    ) -> Result<U256, CallError> {
+        let c = staking_contract(client, contracts, BlockId::Latest)?;
         call_const_staking!(c, candidate_min_stake)
     }
 
     pub fn is_pool_active(
         client: &dyn EngineClient,
+        contracts: &ContractAddresses,
         staking_address: Address,
     ) -> Result<bool, CallError> {
-        let c = BoundContract::bind(client, BlockId::Latest, *STAKING_CONTRACT_ADDRESS);
+        let c = staking_contract(client, contracts, BlockId::Latest)?;
         call_const_staking!(c, is_pool_active, staking_address)
     }
 
@@ -95,11 +144,23 @@ pub mod tests {
         miner: &HbbftTestClient,
         extra_funds: U256,
     ) -> KeyPair {
-        let min_staking_amount =
-            min_staking(moc.client.as_ref()).expect("Query for minimum staking must succeed.");
+        let staker: KeyPair = Random.generate();
+        register_staker(moc, funder, miner, &staker, extra_funds);
+        staker
+    }
+
+    /// Registers the given staking address as a pool with the staking contract, see `create_staker`.
+    pub fn register_staker(
+        moc: &mut HbbftTestClient,
+        funder: &KeyPair,
+        miner: &HbbftTestClient,
+        staker: &KeyPair,
+        extra_funds: U256,
+    ) {
+        let min_staking_amount = min_staking(moc.client.as_ref(), &moc.contracts())
+            .expect("Query for minimum staking must succeed.");
         let amount_to_transfer = min_staking_amount + extra_funds;
 
-        let staker: KeyPair = Random.generate();
         moc.transfer(funder, &staker.address(), &amount_to_transfer);
 
         // Generate call data.
@@ -107,12 +168,10 @@ pub mod tests {
 
         // Register the staker
         moc.call_as(
-            &staker,
-            &STAKING_CONTRACT_ADDRESS,
+            staker,
+            &moc.contracts().address_at(SystemContract::Staking, 0),
             abi_bytes,
             &min_staking_amount,
         );
-
-        staker
     }
 }
@@ -3,9 +3,12 @@ use client::{
     BlockChainClient,
 };
 use crypto::publickey::Public;
-use engines::hbbft::utils::bound_contract::{BoundContract, CallError};
+use engines::hbbft::{
+    contracts::{ContractAddresses, SystemContract},
+    utils::bound_contract::{BoundContract, CallError},
+};
 use ethereum_types::{Address, U256};
-use std::{collections::BTreeMap, str::FromStr};
+use std::collections::BTreeMap;
 use types::{ids::BlockId, transaction::Error};
 
 use_contract!(
@@ -13,9 +16,14 @@ use_contract!(
     "res/contracts/validator_set_hbbft.json"
 );
 
-lazy_static! {
-    static ref VALIDATOR_SET_ADDRESS: Address =
-        Address::from_str("1000000000000000000000000000000000000001").unwrap();
+/// Binds the validator set contract used for the given block.
+fn validator_set_contract<'a>(
+    client: &'a dyn EngineClient,
+    contracts: &ContractAddresses,
+    block_id: BlockId,
+) -> Result<BoundContract<'a>, CallError> {
+    let address = contracts.address_for_call(client, SystemContract::ValidatorSet, block_id)?;
+    Ok(BoundContract::bind(client, block_id, address))
 }
 
 macro_rules! call_const_validator {
@@ -31,10 +39,11 @@ pub enum ValidatorType {
 
 pub fn get_validator_pubkeys(
     client: &dyn EngineClient,
+    contracts: &ContractAddresses,
     block_id: BlockId,
     validator_type: ValidatorType,
 ) -> Result<BTreeMap<Address, Public>, CallError> {
-    let c = BoundContract::bind(client, block_id, *VALIDATOR_SET_ADDRESS);
+    let c = validator_set_contract(client, contracts, block_id)?;
     let validators = match validator_type {
         ValidatorType::Current => call_const_validator!(c, get_validators)?,
         ValidatorType::Pending => call_const_validator!(c, get_pending_validators)?,
@@ -57,25 +66,28 @@ pub fn get_validator_pubkeys(
 #[cfg(test)]
 pub fn mining_by_staking_address(
     client: &dyn EngineClient,
+    contracts: &ContractAddresses,
     staking_address: &Address,
 ) -> Result<Address, CallError> {
-    let c = BoundContract::bind(client, BlockId::Latest, *VALIDATOR_SET_ADDRESS);
+    let c = validator_set_contract(client, contracts, BlockId::Latest)?;
     call_const_validator!(c, mining_by_staking_address, staking_address.clone())
 }
 
 pub fn staking_by_mining_address(
     client: &dyn EngineClient,
+    contracts: &ContractAddresses,
     mining_address: &Address,
 ) -> Result<Address, CallError> {
-    let c = BoundContract::bind(client, BlockId::Latest, *VALIDATOR_SET_ADDRESS);
+    let c = validator_set_contract(client, contracts, BlockId::Latest)?;
     call_const_validator!(c, staking_by_mining_address, mining_address.clone())
 }
 
 pub fn is_pending_validator(
     client: &dyn EngineClient,
+    contracts: &ContractAddresses,
     staking_address: &Address,
 ) -> Result<bool, CallError> {
-    let c = BoundContract::bind(client, BlockId::Latest, *VALIDATOR_SET_ADDRESS);
+    let c = validator_set_contract(client, contracts, BlockId::Latest)?;
     call_const_validator!(c, is_pending_validator, staking_address.clone())
 }
 
@@ -88,9 +100,10 @@ pub enum KeyGenMode {
 
 pub fn get_pending_validator_key_generation_mode(
     client: &dyn EngineClient,
+    contracts: &ContractAddresses,
     mining_address: &Address,
 ) -> Result<KeyGenMode, CallError> {
-    let c = BoundContract::bind(client, BlockId::Latest, *VALIDATOR_SET_ADDRESS);
+    let c = validator_set_contract(client, contracts, BlockId::Latest)?;
     let key_gen_mode = call_const_validator!(
         c,
         get_pending_validator_key_generation_mode,
@@ -105,21 +118,27 @@ pub fn get_pending_validator_key_generation_mode(
 
 pub fn get_validator_available_since(
     client: &dyn EngineClient,
+    contracts: &ContractAddresses,
     address: &Address,
 ) -> Result<U256, CallError> {
-    let c = BoundContract::bind(client, BlockId::Latest, *VALIDATOR_SET_ADDRESS);
+    let c = validator_set_contract(client, contracts, BlockId::Latest)?;
     call_const_validator!(c, validator_available_since, address.clone())
 }
 
-pub fn get_pending_validators(client: &dyn EngineClient) -> Result<Vec<Address>, CallError> {
-    let c = BoundContract::bind(client, BlockId::Latest, *VALIDATOR_SET_ADDRESS);
+pub fn get_pending_validators(
+    client: &dyn EngineClient,
+    contracts: &ContractAddresses,
+) -> Result<Vec<Address>, CallError> {
+    let c = validator_set_contract(client, contracts, BlockId::Latest)?;
     call_const_validator!(c, get_pending_validators)
 }
 
+/// Queues the transaction announcing the availability of the validator, returns its nonce.
 pub fn send_tx_announce_availability(
     full_client: &dyn BlockChainClient,
+    contracts: &ContractAddresses,
     address: &Address,
-) -> Result<(), Error> {
+) -> Result<U256, Error> {
     // chain.latest_nonce(address)
     // we need to get the real latest nonce.
     //let nonce_from_full_client =  full_client.nonce(address,BlockId::Latest);
@@ -146,13 +165,14 @@ pub fn send_tx_announce_availability(
                     block_number,
                     block_hash,
                 );
-                let transaction = TransactionRequest::call(*VALIDATOR_SET_ADDRESS, send_data.0)
+                let contract = contracts.address_at(SystemContract::ValidatorSet, block_number + 1);
+                let transaction = TransactionRequest::call(contract, send_data.0)
                     .gas(U256::from(1_000_000))
                     .nonce(nonce);
 
                 info!(target:"consensus", "sending announce availability with nonce: {}", nonce);
                 full_client.transact_silently(transaction)?;
-                return Ok(());
+                return Ok(nonce);
             }
         },
         None => {
@@ -162,3 +182,51 @@ pub fn send_tx_announce_availability(
 
     return Err(Error::TransactionTypeNotEnabled);
 }
+
+/// Returns true if the reporting validator may report the malicious validator for the given block.
+pub fn report_malicious_callable(
+    client: &dyn EngineClient,
+    contracts: &ContractAddresses,
+    reporting_address: &Address,
+    malicious_address: &Address,
+    block_number: u64,
+) -> Result<bool, CallError> {
+    let c = validator_set_contract(client, contracts, BlockId::Latest)?;
+    let (callable, _remove_reporting_validator) = call_const_validator!(
+        c,
+        report_malicious_callable,
+        reporting_address.clone(),
+        malicious_address.clone(),
+        block_number
+    )?;
+    Ok(callable)
+}
+
+pub fn send_tx_report_malicious(
+    full_client: &dyn BlockChainClient,
+    contracts: &ContractAddresses,
+    reporting_address: &Address,
+    malicious_address: &Address,
+    block_number: u64,
+    proof: Vec<u8>,
+) -> Result<(), Error> {
+    // The nonce has to account for reports that are still queued.
+    let nonce = full_client.next_nonce(reporting_address);
+
+    let send_data = validator_set_hbbft::functions::report_malicious::call(
+        malicious_address.clone(),
+        block_number,
+        proof,
+    );
+    let contract = contracts.address_at(
+        SystemContract::ValidatorSet,
+        full_client.chain_info().best_block_number + 1,
+    );
+    let transaction = TransactionRequest::call(contract, send_data.0)
+        .gas(U256::from(1_000_000))
+        .nonce(nonce);
+
+    info!(target:"consensus", "sending report of malicious validator {} for block {} with nonce: {}", malicious_address, block_number, nonce);
+    full_client.transact_silently(transaction)?;
+    Ok(())
+}
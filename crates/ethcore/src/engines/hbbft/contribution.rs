@@ -1,6 +1,8 @@
-use rand_065::{self, distributions::Standard, Rng};
+use ethereum_types::{Address, U256};
+use hash::keccak;
+use rand_065::{distributions::Standard, Rng};
 use rlp::RlpStream;
-use std::time::UNIX_EPOCH;
+use std::{collections::HashSet, time::UNIX_EPOCH};
 use types::transaction::SignedTransaction;
 
 #[derive(Clone, Eq, PartialEq, Debug, Hash, Serialize, Deserialize)]
@@ -9,15 +11,29 @@ pub(crate) struct Contribution {
     pub timestamp: u64,
     /// Random data for on-chain randomness.
     ///
-    /// The invariant of `random_data.len()` == RANDOM_BYTES_PER_EPOCH **must** hold true.
+    /// The invariant of `random_data.len()` >= MIN_RANDOM_BYTES_PER_EPOCH **must** hold true.
     pub random_data: Vec<u8>,
 }
 
-/// Number of random bytes to generate per epoch.
+/// Default number of random bytes to generate per epoch.
 ///
-/// Currently, we want twenty u32s worth of random data to generated on each epoch.
-// TODO: Make this configurable somewhere.
-const RANDOM_BYTES_PER_EPOCH: usize = 4 * 20;
+/// Twenty u32s worth of random data are generated on each epoch, unless configured otherwise.
+pub(crate) const DEFAULT_RANDOM_BYTES_PER_EPOCH: usize = 4 * 20;
+
+/// Minimum number of random bytes per epoch, the size of the seed fed into the randomness contract.
+pub(crate) const MIN_RANDOM_BYTES_PER_EPOCH: usize = 32;
+
+/// Default maximum size of the encoded transactions of a single contribution, in bytes.
+pub(crate) const DEFAULT_MAX_CONTRIBUTION_BYTES: usize = 1024 * 1024;
+
+/// Bounds of the transactions a validator proposes in a single contribution.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct ContributionLimits {
+    /// Maximum total gas of the transactions.
+    pub max_gas: U256,
+    /// Maximum total size of the encoded transactions.
+    pub max_bytes: usize,
+}
 
 /// Returns the current UNIX Epoch time, in seconds.
 pub fn unix_now_secs() -> u64 {
@@ -32,42 +48,120 @@ pub fn unix_now_millis() -> u128 {
         .as_millis()
 }
 
+fn encode_transaction(txn: &SignedTransaction) -> Vec<u8> {
+    let mut s = RlpStream::new();
+    txn.rlp_append(&mut s);
+    s.drain()
+}
+
+/// Returns the index of the validator responsible for proposing the transactions of the sender.
+///
+/// The assignment changes with every epoch, so a faulty validator can only delay
+/// the transactions of a sender, but not censor them.
+fn responsible_validator(sender: &Address, epoch: u64, num_validators: usize) -> usize {
+    let mut data = sender.as_bytes().to_vec();
+    data.extend_from_slice(&epoch.to_be_bytes());
+    (keccak(&data).to_low_u64_be() % num_validators as u64) as usize
+}
+
+/// Selects the transactions the validator with the given index proposes in the given epoch,
+/// following the B/N selection of the HoneyBadgerBFT paper.
+///
+/// Instead of every validator proposing the whole transaction queue, the senders are partitioned
+/// among the validators, so the contributions of an epoch do not overlap. Transactions are taken
+/// in queue order until the limits are reached. Later transactions of a sender whose transaction
+/// exceeded the limits are skipped as well, since they could not be executed without it.
+/// The first selected transaction is exempt from the limits, so every transaction can be proposed.
+pub(crate) fn select_transactions(
+    txns: Vec<SignedTransaction>,
+    epoch: u64,
+    our_index: usize,
+    num_validators: usize,
+    limits: &ContributionLimits,
+) -> Vec<SignedTransaction> {
+    let mut selected = Vec::new();
+    let mut total_gas = U256::zero();
+    let mut total_bytes = 0;
+    let mut skipped_senders = HashSet::new();
+    for txn in txns {
+        let sender = txn.sender();
+        if skipped_senders.contains(&sender)
+            || responsible_validator(&sender, epoch, num_validators) != our_index
+        {
+            continue;
+        }
+        let gas = txn.tx().gas;
+        let bytes = encode_transaction(&txn).len();
+        if !selected.is_empty()
+            && (total_gas + gas > limits.max_gas || total_bytes + bytes > limits.max_bytes)
+        {
+            skipped_senders.insert(sender);
+            continue;
+        }
+        total_gas = total_gas + gas;
+        total_bytes += bytes;
+        selected.push(txn);
+    }
+    selected
+}
+
 impl Contribution {
-    pub fn new(txns: &Vec<SignedTransaction>) -> Self {
-        let ser_txns: Vec<_> = txns
-            .iter()
-            .map(|txn| {
-                let mut s = RlpStream::new();
-                txn.rlp_append(&mut s);
-                s.drain()
-            })
-            .collect();
-        let mut rng = rand_065::thread_rng();
+    pub fn new<R: Rng>(
+        txns: &Vec<SignedTransaction>,
+        random_bytes_per_epoch: usize,
+        timestamp: u64,
+        rng: &mut R,
+    ) -> Self {
+        let ser_txns: Vec<_> = txns.iter().map(encode_transaction).collect();
 
         Contribution {
             transactions: ser_txns,
-            timestamp: unix_now_secs(),
+            timestamp,
             random_data: rng
                 .sample_iter(&Standard)
-                .take(RANDOM_BYTES_PER_EPOCH)
+                .take(random_bytes_per_epoch)
                 .collect(),
         }
     }
+
+    /// Folds the random data into a 256 bit seed by XORing all of its 32 byte chunks.
+    ///
+    /// Returns `None` if the contribution carries less than `MIN_RANDOM_BYTES_PER_EPOCH` bytes.
+    pub fn random_seed(&self) -> Option<U256> {
+        if self.random_data.len() < MIN_RANDOM_BYTES_PER_EPOCH {
+            return None;
+        }
+        let mut seed = [0u8; MIN_RANDOM_BYTES_PER_EPOCH];
+        for (i, byte) in self.random_data.iter().enumerate() {
+            seed[i % MIN_RANDOM_BYTES_PER_EPOCH] ^= byte;
+        }
+        Some(U256::from(&seed[..]))
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use super::{
+        responsible_validator, select_transactions, unix_now_secs, ContributionLimits,
+        DEFAULT_RANDOM_BYTES_PER_EPOCH, MIN_RANDOM_BYTES_PER_EPOCH,
+    };
     use crypto::publickey::{Generator, Random};
     use engines::hbbft::test::create_transactions::create_transaction;
     use ethereum_types::U256;
-    use types::transaction::{SignedTransaction, TypedTransaction};
+    use rand_065;
+    use types::transaction::{SignedTransaction, Transaction, TypedTransaction};
 
     #[test]
     fn test_contribution_serialization() {
         let mut pending: Vec<SignedTransaction> = Vec::new();
         let keypair = Random.generate();
         pending.push(create_transaction(&keypair, &U256::from(1)));
-        let contribution = super::Contribution::new(&pending);
+        let contribution = super::Contribution::new(
+            &pending,
+            DEFAULT_RANDOM_BYTES_PER_EPOCH,
+            unix_now_secs(),
+            &mut rand_065::thread_rng(),
+        );
 
         let deser_txns: Vec<_> = contribution
             .transactions
@@ -82,4 +176,119 @@ mod tests {
             deser_txns.iter().nth(0).unwrap()
         );
     }
+
+    #[test]
+    fn test_contribution_random_seed() {
+        let mut contribution = super::Contribution::new(
+            &Vec::new(),
+            DEFAULT_RANDOM_BYTES_PER_EPOCH,
+            unix_now_secs(),
+            &mut rand_065::thread_rng(),
+        );
+        assert_eq!(
+            contribution.random_data.len(),
+            DEFAULT_RANDOM_BYTES_PER_EPOCH
+        );
+
+        contribution.random_data = vec![0u8; MIN_RANDOM_BYTES_PER_EPOCH - 1];
+        assert_eq!(contribution.random_seed(), None);
+
+        // The chunks 0x01.. and 0x03.. fold into 0x02.., the remaining byte is XORed into the first.
+        let mut random_data = vec![1u8; MIN_RANDOM_BYTES_PER_EPOCH];
+        random_data.extend(vec![3u8; MIN_RANDOM_BYTES_PER_EPOCH]);
+        random_data.push(0xff);
+        contribution.random_data = random_data;
+        let mut expected = [2u8; MIN_RANDOM_BYTES_PER_EPOCH];
+        expected[0] ^= 0xff;
+        assert_eq!(contribution.random_seed(), Some(U256::from(&expected[..])));
+    }
+
+    #[test]
+    fn test_select_transactions_partitioned_by_sender() {
+        let num_validators = 4;
+        let epoch = 7;
+        let mut txns = Vec::new();
+        for _ in 0..20 {
+            let keypair = Random.generate();
+            for nonce in 0..3 {
+                txns.push(create_transaction(&keypair, &U256::from(nonce)));
+            }
+        }
+        let unlimited = ContributionLimits {
+            max_gas: U256::max_value(),
+            max_bytes: usize::max_value(),
+        };
+
+        let mut proposed = Vec::new();
+        for index in 0..num_validators {
+            let selected =
+                select_transactions(txns.clone(), epoch, index, num_validators, &unlimited);
+            for txn in &selected {
+                assert_eq!(
+                    responsible_validator(&txn.sender(), epoch, num_validators),
+                    index
+                );
+            }
+            proposed.extend(selected);
+        }
+
+        // Every transaction is proposed by exactly one validator, in queue order per sender.
+        assert_eq!(proposed.len(), txns.len());
+        for txn in &txns {
+            assert_eq!(proposed.iter().filter(|p| *p == txn).count(), 1);
+        }
+    }
+
+    #[test]
+    fn test_select_transactions_limits() {
+        let sender_1 = Random.generate();
+        let sender_2 = Random.generate();
+        let txns = vec![
+            create_transaction(&sender_1, &U256::from(0)),
+            create_transaction(&sender_1, &U256::from(1)),
+            create_transaction(&sender_2, &U256::from(0)),
+        ];
+        let gas = txns[0].tx().gas;
+
+        // The first transaction is always selected.
+        let limits = ContributionLimits {
+            max_gas: U256::zero(),
+            max_bytes: 0,
+        };
+        assert_eq!(
+            select_transactions(txns.clone(), 0, 0, 1, &limits),
+            vec![txns[0].clone()]
+        );
+
+        // The gas limit allows two transactions, the second one of sender_1 is taken in queue order.
+        let limits = ContributionLimits {
+            max_gas: gas * 2,
+            max_bytes: usize::max_value(),
+        };
+        assert_eq!(
+            select_transactions(txns.clone(), 0, 0, 1, &limits),
+            vec![txns[0].clone(), txns[1].clone()]
+        );
+
+        // If a transaction of a sender exceeds the limit, the later transactions of the sender are skipped.
+        let expensive = TypedTransaction::Legacy(Transaction {
+            gas: gas * 3,
+            ..txns[2].tx().clone()
+        })
+        .sign(sender_2.secret(), None);
+        let txns = vec![
+            create_transaction(&sender_1, &U256::from(0)),
+            expensive,
+            create_transaction(&sender_2, &U256::from(1)),
+            create_transaction(&sender_1, &U256::from(1)),
+        ];
+        let limits = ContributionLimits {
+            max_gas: gas * 3,
+            max_bytes: usize::max_value(),
+        };
+        assert_eq!(
+            select_transactions(txns.clone(), 0, 0, 1, &limits),
+            vec![txns[0].clone(), txns[3].clone()]
+        );
+    }
 }
@@ -0,0 +1,80 @@
+use clap::{Arg, ArgMatches};
+use ethabi::{Contract, Token};
+use ethereum_types::Address;
+use std::str::FromStr;
+
+const STAKING_ABI: &[u8] = include_bytes!("../../../../../res/contracts/staking_contract.json");
+const VALIDATOR_SET_ABI: &[u8] =
+    include_bytes!("../../../../../res/contracts/validator_set_hbbft.json");
+
+/// Default addresses of the system contracts in the genesis block of the DMD networks.
+const DEFAULT_STAKING_ADDRESS: &str = "1100000000000000000000000000000000000001";
+const DEFAULT_VALIDATOR_SET_ADDRESS: &str = "1000000000000000000000000000000000000001";
+
+/// A system contract, with the ABI and the address to call it.
+pub struct SystemContract {
+    pub address: Address,
+    abi: Contract,
+}
+
+impl SystemContract {
+    fn new(abi: &[u8], address: Address) -> Self {
+        SystemContract {
+            address,
+            abi: Contract::load(abi).expect("the bundled contract ABIs are valid; qed"),
+        }
+    }
+
+    pub fn staking(matches: &ArgMatches) -> Result<Self, String> {
+        let address = parse_address(
+            matches
+                .value_of("staking-contract")
+                .unwrap_or(DEFAULT_STAKING_ADDRESS),
+        )?;
+        Ok(SystemContract::new(STAKING_ABI, address))
+    }
+
+    pub fn validator_set(matches: &ArgMatches) -> Result<Self, String> {
+        let address = parse_address(
+            matches
+                .value_of("validator-set-contract")
+                .unwrap_or(DEFAULT_VALIDATOR_SET_ADDRESS),
+        )?;
+        Ok(SystemContract::new(VALIDATOR_SET_ABI, address))
+    }
+
+    /// Returns the call data for the given function.
+    pub fn encode(&self, function: &str, params: &[Token]) -> Result<Vec<u8>, String> {
+        self.abi
+            .function(function)
+            .and_then(|f| f.encode_input(params))
+            .map_err(|e| format!("Could not encode the {} call: {}", function, e))
+    }
+
+    /// Decodes the output of a call to the given function.
+    pub fn decode(&self, function: &str, output: &[u8]) -> Result<Vec<Token>, String> {
+        self.abi
+            .function(function)
+            .and_then(|f| f.decode_output(output))
+            .map_err(|e| format!("Could not decode the {} result: {}", function, e))
+    }
+}
+
+/// Arguments to override the addresses of the system contracts.
+pub fn contract_args<'a, 'b>() -> Vec<Arg<'a, 'b>> {
+    vec![
+        Arg::with_name("staking-contract")
+            .long("staking-contract")
+            .takes_value(true)
+            .help("Address of the staking contract, if it differs from the DMD default"),
+        Arg::with_name("validator-set-contract")
+            .long("validator-set-contract")
+            .takes_value(true)
+            .help("Address of the validator set contract, if it differs from the DMD default"),
+    ]
+}
+
+pub fn parse_address(address: &str) -> Result<Address, String> {
+    Address::from_str(address.trim_start_matches("0x"))
+        .map_err(|_| format!("Invalid address: {}", address))
+}
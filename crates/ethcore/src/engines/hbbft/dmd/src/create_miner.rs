@@ -1,25 +1,40 @@
-use ethstore::{KeyFile, SafeAccount};
+use crate::keys::{create_private_file, new_password, write_private_file};
+use ethstore::{ethkey::Password, KeyFile, SafeAccount};
 use parity_crypto::publickey::{Generator, KeyPair, Random, Secret};
 use std::{fs, num::NonZeroU32, path::Path};
 
-fn write_json_for_secret(secret: Secret, filename: &str) {
+/// The file a prompted password is saved to, if requested.
+const PASSWORD_FILE: &str = "password.txt";
+
+fn write_json_for_secret(secret: Secret, password: &Password, path: &Path) -> Result<(), String> {
     let json_key: KeyFile = SafeAccount::create(
         &KeyPair::from_secret(secret).unwrap(),
         [0u8; 16],
-        &"test".into(),
+        password,
         NonZeroU32::new(10240).expect("We know 10240 is not zero."),
-        "Test".to_owned(),
+        "Miner".to_owned(),
         "{}".to_owned(),
     )
-    .expect("json key object creation should succeed")
+    .map_err(|e| format!("Could not create the json key: {}", e))?
     .into();
 
     let serialized_json_key =
         serde_json::to_string(&json_key).expect("json key object serialization should succeed");
-    fs::write(filename, serialized_json_key).expect("Unable to write json key file");
+    write_private_file(path, serialized_json_key.as_bytes())
 }
 
-pub fn create_miner() {
+/// Creates the keys of a new miner. The password of the miner's key is read from the given file,
+/// or prompted for. A prompted password is only written to password.txt, for the node to unlock
+/// the key, if `save_password` is set. An existing password.txt is never overwritten.
+pub fn create_miner(password_file: Option<&str>, save_password: bool) -> Result<(), String> {
+    let save_password = password_file.is_none() && save_password;
+    if save_password && Path::new(PASSWORD_FILE).exists() {
+        return Err(format!(
+            "{} exists already, refusing to overwrite it",
+            PASSWORD_FILE
+        ));
+    }
+    let password = new_password(password_file)?;
     println!("Creating dmd v4 miner...");
     let acc = Random.generate();
 
@@ -27,8 +42,10 @@ pub fn create_miner() {
     let network_key_dir = Path::new("./data/network");
     fs::create_dir_all(network_key_dir).expect("Could not create network key directory");
     // Write the private key for the hbbft node
-    fs::write(network_key_dir.join("key"), acc.secret().to_hex())
-        .expect("Unable to write the network key file");
+    write_private_file(
+        &network_key_dir.join("key"),
+        acc.secret().to_hex().as_bytes(),
+    )?;
 
     // Create "keys" and "DPoSChain" subfolders.
     let accounts_dir = Path::new("./data/keys/DPoSChain");
@@ -37,15 +54,20 @@ pub fn create_miner() {
     // Write JSON account.
     write_json_for_secret(
         acc.secret().clone(),
-        accounts_dir
-            .join("dmd_miner_key.json")
-            .to_str()
-            .expect("Could not convert the JSON account path to a string"),
-    );
-    fs::write("password.txt", "test").expect("Unable to write password.txt file");
+        &password,
+        &accounts_dir.join("dmd_miner_key.json"),
+    )?;
+    if save_password {
+        eprintln!(
+            "WARNING: Writing the password in plain text to {}, anyone who can read this file can use the miner key.",
+            PASSWORD_FILE
+        );
+        create_private_file(Path::new(PASSWORD_FILE), password.as_str().as_bytes())?;
+    }
     fs::write("public_key.txt", format!("{:?}", acc.public()))
-        .expect("Unable to write password.txt file");
+        .expect("Unable to write public_key.txt file");
 
     println!("Miner address: {:?}", acc.address());
     println!("Miner public key: {:?}", acc.public());
+    Ok(())
 }
@@ -0,0 +1,80 @@
+use ethstore::{
+    accounts_dir::{DiskKeyFileManager, KeyFileManager},
+    ethkey::Password,
+};
+use parity_crypto::publickey::KeyPair;
+use std::{fs, io::Write, path::Path};
+
+/// Reads a password from the first line of the given file, or prompts for it.
+pub fn read_password(password_file: Option<&str>, prompt: &str) -> Result<Password, String> {
+    match password_file {
+        Some(path) => fs::read_to_string(path)
+            .map(|content| content.lines().next().unwrap_or("").into())
+            .map_err(|e| format!("Could not read the password file {}: {}", path, e)),
+        None => rpassword::prompt_password_stdout(prompt)
+            .map(Into::into)
+            .map_err(|e| format!("Could not read the password: {}", e)),
+    }
+}
+
+/// Reads the password for a new key from the given file, or prompts for it twice.
+pub fn new_password(password_file: Option<&str>) -> Result<Password, String> {
+    if password_file.is_some() {
+        return read_password(password_file, "");
+    }
+    let password = read_password(None, "New password: ")?;
+    let confirmation = read_password(None, "Repeat the password: ")?;
+    if password != confirmation {
+        return Err("The passwords do not match".into());
+    }
+    if password.as_str().is_empty() {
+        return Err("The password must not be empty".into());
+    }
+    Ok(password)
+}
+
+/// Decrypts the key of a JSON key file.
+pub fn load_key(path: &str, password: &Password) -> Result<KeyPair, String> {
+    let file =
+        fs::File::open(path).map_err(|e| format!("Could not open the key file {}: {}", path, e))?;
+    let account = DiskKeyFileManager::default()
+        .read(None, file)
+        .map_err(|e| format!("Could not read the key file {}: {}", path, e))?;
+    let secret = account
+        .crypto
+        .secret(password)
+        .map_err(|_| format!("Invalid password for the key file {}", path))?;
+    KeyPair::from_secret(secret).map_err(|e| format!("Invalid key in the key file {}: {}", path, e))
+}
+
+/// Writes a file that only the owner can read, since it contains a secret.
+pub fn write_private_file(path: &Path, content: &[u8]) -> Result<(), String> {
+    let mut options = private_file_options();
+    options.create(true).truncate(true);
+    write_file(options, path, content)
+}
+
+/// Creates a file that only the owner can read, failing if the file exists already.
+pub fn create_private_file(path: &Path, content: &[u8]) -> Result<(), String> {
+    let mut options = private_file_options();
+    options.create_new(true);
+    write_file(options, path, content)
+}
+
+fn private_file_options() -> fs::OpenOptions {
+    let mut options = fs::OpenOptions::new();
+    options.write(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options
+}
+
+fn write_file(options: fs::OpenOptions, path: &Path, content: &[u8]) -> Result<(), String> {
+    options
+        .open(path)
+        .and_then(|mut file| file.write_all(content))
+        .map_err(|e| format!("Could not write {}: {}", path.display(), e))
+}
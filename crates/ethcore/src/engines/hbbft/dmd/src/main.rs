@@ -1,21 +1,49 @@
+mod contracts;
 mod create_miner;
+mod keys;
+mod rpc;
+mod transaction;
+mod validator;
 
-use clap::{App, AppSettings, SubCommand};
+use clap::{App, AppSettings, Arg, SubCommand};
 use create_miner::create_miner;
+use std::process;
 
 fn main() {
     let matches = App::new("dmd v4 swiss army knife")
         .version("1.0")
         .author("David Forstenlechner <dforsten@gmail.com>")
-        .about("Utilities for setting up a dmd v4 node")
+        .about("Utilities for setting up and operating a dmd v4 node")
         .setting(AppSettings::ArgRequiredElseHelp)
         .subcommand(
             SubCommand::with_name("create_miner")
-                .about("Creates the keys and config for a new dmd v4 miner"),
+                .about("Creates the keys and config for a new dmd v4 miner")
+                .arg(
+                    Arg::with_name("password-file")
+                        .long("password-file")
+                        .takes_value(true)
+                        .help("File containing the password for the miner key, prompted for if not given"),
+                )
+                .arg(
+                    Arg::with_name("save-password")
+                        .long("save-password")
+                        .conflicts_with("password-file")
+                        .help("Write the prompted password in plain text to password.txt, for the node to unlock the miner key"),
+                ),
         )
+        .subcommands(validator::subcommands())
         .get_matches();
 
-    if let Some(_) = matches.subcommand_matches("create_miner") {
-        create_miner();
+    let result = match matches.subcommand() {
+        ("create_miner", Some(matches)) => create_miner(
+            matches.value_of("password-file"),
+            matches.is_present("save-password"),
+        ),
+        (name, Some(matches)) => validator::run(name, matches).unwrap_or(Ok(())),
+        _ => Ok(()),
+    };
+    if let Err(error) = result {
+        eprintln!("{}", error);
+        process::exit(1);
     }
 }
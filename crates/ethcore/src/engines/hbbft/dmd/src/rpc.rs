@@ -0,0 +1,120 @@
+use ethereum_types::{Address, H256, U256};
+use fetch::{Abort, BodyReader, Client, Fetch, Request, Url};
+use futures::Future;
+use http::header::{HeaderValue, CONTENT_TYPE};
+use rustc_hex::ToHex;
+use serde_json::{json, Value};
+use std::str::FromStr;
+
+/// A JSON-RPC client for the HTTP endpoint of a node.
+pub struct RpcClient {
+    url: Url,
+    fetch: Client,
+}
+
+impl RpcClient {
+    pub fn new(url: &str) -> Result<Self, String> {
+        let url = Url::parse(url).map_err(|e| format!("Invalid RPC URL {}: {}", url, e))?;
+        let fetch =
+            Client::new(1).map_err(|e| format!("Could not create the HTTP client: {}", e))?;
+        Ok(RpcClient { url, fetch })
+    }
+
+    /// Calls the given method and returns its result.
+    pub fn request(&self, method: &str, params: Value) -> Result<Value, String> {
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": method,
+            "params": params,
+        })
+        .to_string();
+        let request = Request::post(self.url.clone())
+            .with_header(CONTENT_TYPE, HeaderValue::from_static("application/json"))
+            .with_body(body);
+        let response = self
+            .fetch
+            .fetch(request, Abort::default())
+            .wait()
+            .map_err(|e| format!("The {} request to {} failed: {}", method, self.url, e))?;
+        if !response.is_success() {
+            return Err(format!(
+                "The {} request to {} failed with HTTP status {}",
+                method,
+                self.url,
+                response.status()
+            ));
+        }
+
+        let response: Value = serde_json::from_reader(BodyReader::new(response))
+            .map_err(|e| format!("Invalid {} response: {}", method, e))?;
+        if let Some(error) = response.get("error") {
+            return Err(format!("{} failed: {}", method, error));
+        }
+        Ok(response["result"].clone())
+    }
+
+    fn request_u256(&self, method: &str, params: Value) -> Result<U256, String> {
+        let result = self.request(method, params)?;
+        result
+            .as_str()
+            .and_then(|s| U256::from_str(s.trim_start_matches("0x")).ok())
+            .ok_or_else(|| format!("Invalid {} result: {}", method, result))
+    }
+
+    pub fn chain_id(&self) -> Result<u64, String> {
+        self.request_u256("eth_chainId", json!([]))
+            .map(|chain_id| chain_id.low_u64())
+    }
+
+    pub fn gas_price(&self) -> Result<U256, String> {
+        self.request_u256("eth_gasPrice", json!([]))
+    }
+
+    /// Returns the nonce of the address, including its pending transactions.
+    pub fn nonce(&self, address: &Address) -> Result<U256, String> {
+        self.request_u256(
+            "eth_getTransactionCount",
+            json!([format!("{:?}", address), "pending"]),
+        )
+    }
+
+    /// Returns the number and the hash of the latest block.
+    pub fn latest_block(&self) -> Result<(U256, H256), String> {
+        let block = self.request("eth_getBlockByNumber", json!(["latest", false]))?;
+        let field = |name: &str| block[name].as_str().map(|s| s.trim_start_matches("0x"));
+        match (
+            field("number").and_then(|n| U256::from_str(n).ok()),
+            field("hash").and_then(|h| H256::from_str(h).ok()),
+        ) {
+            (Some(number), Some(hash)) => Ok((number, hash)),
+            _ => Err(format!("Invalid latest block: {}", block)),
+        }
+    }
+
+    /// Executes a call on the state of the latest block.
+    pub fn call(&self, to: &Address, data: &[u8]) -> Result<Vec<u8>, String> {
+        let result = self.request(
+            "eth_call",
+            json!([
+                {
+                    "to": format!("{:?}", to),
+                    "data": format!("0x{}", data.to_hex::<String>()),
+                },
+                "latest"
+            ]),
+        )?;
+        result
+            .as_str()
+            .and_then(|s| rustc_hex::FromHex::from_hex(s.trim_start_matches("0x")).ok())
+            .ok_or_else(|| format!("Invalid eth_call result: {}", result))
+    }
+
+    /// Submits a signed transaction and returns its hash.
+    pub fn send_raw_transaction(&self, transaction: &[u8]) -> Result<Value, String> {
+        self.request(
+            "eth_sendRawTransaction",
+            json!([format!("0x{}", transaction.to_hex::<String>())]),
+        )
+    }
+}
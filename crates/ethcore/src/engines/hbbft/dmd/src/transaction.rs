@@ -0,0 +1,151 @@
+use crate::rpc::RpcClient;
+use clap::{Arg, ArgMatches};
+use common_types::transaction::{Action, Transaction, TypedTransaction};
+use ethereum_types::{Address, U256};
+use parity_crypto::publickey::KeyPair;
+use rustc_hex::ToHex;
+
+pub const DEFAULT_RPC_URL: &str = "http://127.0.0.1:8545";
+const DEFAULT_GAS: &str = "1000000";
+
+/// Arguments to sign and submit transactions.
+pub fn transaction_args<'a, 'b>() -> Vec<Arg<'a, 'b>> {
+    vec![
+        Arg::with_name("rpc")
+            .long("rpc")
+            .takes_value(true)
+            .default_value(DEFAULT_RPC_URL)
+            .help("HTTP JSON-RPC endpoint of the node"),
+        Arg::with_name("offline")
+            .long("offline")
+            .requires_all(&["nonce", "chain-id", "gas-price"])
+            .help("Print the signed transaction instead of sending it through the RPC endpoint"),
+        Arg::with_name("nonce")
+            .long("nonce")
+            .takes_value(true)
+            .help("Nonce of the transaction, read from the node if not given"),
+        Arg::with_name("chain-id")
+            .long("chain-id")
+            .takes_value(true)
+            .help("Chain id of the network, read from the node if not given"),
+        Arg::with_name("gas-price")
+            .long("gas-price")
+            .takes_value(true)
+            .help("Gas price in wei, read from the node if not given"),
+        Arg::with_name("gas")
+            .long("gas")
+            .takes_value(true)
+            .default_value(DEFAULT_GAS)
+            .help("Gas limit of the transaction"),
+    ]
+}
+
+/// Returns the RPC client, unless the transaction is created offline.
+pub fn rpc_client(matches: &ArgMatches) -> Result<Option<RpcClient>, String> {
+    if matches.is_present("offline") {
+        return Ok(None);
+    }
+    let url = matches.value_of("rpc").unwrap_or(DEFAULT_RPC_URL);
+    RpcClient::new(url).map(Some)
+}
+
+fn parse_u256(matches: &ArgMatches, name: &str) -> Result<Option<U256>, String> {
+    matches
+        .value_of(name)
+        .map(|value| {
+            U256::from_dec_str(value)
+                .map_err(|_| format!("Invalid value for --{}: {}", name, value))
+        })
+        .transpose()
+}
+
+/// Signs a call to the given contract, then prints it or sends it through the RPC endpoint.
+pub fn submit(
+    matches: &ArgMatches,
+    key: &KeyPair,
+    to: Address,
+    value: U256,
+    data: Vec<u8>,
+) -> Result<(), String> {
+    let client = rpc_client(matches)?;
+    let from_node = |name: &str| match client {
+        Some(ref client) => Ok(client),
+        None => Err(format!("--{} is required with --offline", name)),
+    };
+    let nonce = match parse_u256(matches, "nonce")? {
+        Some(nonce) => nonce,
+        None => from_node("nonce")?.nonce(&key.address())?,
+    };
+    let chain_id = match parse_u256(matches, "chain-id")? {
+        Some(chain_id) => chain_id.low_u64(),
+        None => from_node("chain-id")?.chain_id()?,
+    };
+    let gas_price = match parse_u256(matches, "gas-price")? {
+        Some(gas_price) => gas_price,
+        None => from_node("gas-price")?.gas_price()?,
+    };
+    let gas = parse_u256(matches, "gas")?.unwrap_or_else(|| U256::from(1_000_000));
+
+    let transaction = TypedTransaction::Legacy(Transaction {
+        nonce,
+        gas_price,
+        gas,
+        action: Action::Call(to),
+        value,
+        data,
+    })
+    .sign(key.secret(), Some(chain_id));
+
+    match client {
+        Some(client) => {
+            let hash = client.send_raw_transaction(&transaction.encode())?;
+            println!("Transaction sent: {}", hash.as_str().unwrap_or(""));
+        }
+        None => {
+            println!("Transaction hash: {:?}", transaction.hash());
+            println!(
+                "Signed transaction: 0x{}",
+                transaction.encode().to_hex::<String>()
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Parses an amount of coins with up to 18 decimals into wei.
+pub fn parse_coins(amount: &str) -> Result<U256, String> {
+    let invalid = || format!("Invalid amount: {}", amount);
+    let (integer, fraction) = match amount.find('.') {
+        Some(index) => (&amount[..index], &amount[index + 1..]),
+        None => (amount, ""),
+    };
+    if fraction.len() > 18
+        || integer.is_empty() && fraction.is_empty()
+        || !integer
+            .chars()
+            .chain(fraction.chars())
+            .all(|c| c.is_ascii_digit())
+    {
+        return Err(invalid());
+    }
+    let digits = format!("{}{:0<18}", integer, fraction);
+    U256::from_dec_str(&digits).map_err(|_| invalid())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_coins() {
+        let coin = U256::from(10).pow(18.into());
+        assert_eq!(parse_coins("1").unwrap(), coin);
+        assert_eq!(parse_coins("10000").unwrap(), coin * 10000);
+        assert_eq!(parse_coins("0.5").unwrap(), coin / 2);
+        assert_eq!(parse_coins(".000000000000000001").unwrap(), U256::from(1));
+        assert!(parse_coins("0.0000000000000000001").is_err());
+        assert!(parse_coins("1,5").is_err());
+        assert!(parse_coins("-1").is_err());
+        assert!(parse_coins(".").is_err());
+    }
+}
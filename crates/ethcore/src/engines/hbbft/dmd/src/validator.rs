@@ -0,0 +1,446 @@
+use crate::{
+    contracts::{contract_args, parse_address, SystemContract},
+    keys::{load_key, read_password},
+    rpc::RpcClient,
+    transaction::{parse_coins, rpc_client, submit, transaction_args, DEFAULT_RPC_URL},
+};
+use clap::{App, Arg, ArgMatches, SubCommand};
+use ethabi::Token;
+use ethereum_types::{Address, H256, U256};
+use parity_crypto::publickey::{public_to_address, KeyPair, Public};
+use std::{net::IpAddr, str::FromStr};
+
+fn key_args<'a, 'b>(help: &'b str) -> Vec<Arg<'a, 'b>> {
+    vec![
+        Arg::with_name("key")
+            .long("key")
+            .takes_value(true)
+            .required(true)
+            .help(help),
+        Arg::with_name("password-file")
+            .long("password-file")
+            .takes_value(true)
+            .help("File containing the password of the key, prompted for if not given"),
+    ]
+}
+
+fn transaction_command<'a, 'b>(name: &str, about: &'b str, key_help: &'b str) -> App<'a, 'b> {
+    SubCommand::with_name(name)
+        .about(about)
+        .args(&key_args(key_help))
+        .args(&transaction_args())
+        .args(&contract_args())
+}
+
+/// The subcommands to manage a validator.
+pub fn subcommands<'a, 'b>() -> Vec<App<'a, 'b>> {
+    vec![
+        transaction_command(
+            "create_pool",
+            "Registers a staking pool for a miner, with the candidate minimum stake",
+            "JSON key file of the staking address that owns the pool",
+        )
+        .arg(
+            Arg::with_name("mining-public-key")
+                .long("mining-public-key")
+                .takes_value(true)
+                .required(true)
+                .help("Public key of the miner, as written to public_key.txt by create_miner"),
+        )
+        .arg(
+            Arg::with_name("stake")
+                .long("stake")
+                .takes_value(true)
+                .required(true)
+                .help("Initial stake in coins"),
+        )
+        .arg(
+            Arg::with_name("ip").long("ip").takes_value(true).help(
+                "Public IP address of the miner's node, for the other validators to connect to",
+            ),
+        ),
+        transaction_command(
+            "stake",
+            "Stakes coins on a pool",
+            "JSON key file of the staker",
+        )
+        .arg(pool_arg())
+        .arg(amount_arg("Amount to stake in coins")),
+        transaction_command(
+            "unstake",
+            "Withdraws a stake from a pool, or orders its withdrawal if the pool is a validator",
+            "JSON key file of the staker",
+        )
+        .arg(pool_arg())
+        .arg(amount_arg("Amount to withdraw in coins"))
+        .arg(
+            Arg::with_name("ordered")
+                .long("ordered")
+                .help("Order the withdrawal, to claim it after the staking epoch ended"),
+        )
+        .arg(
+            Arg::with_name("cancel")
+                .long("cancel")
+                .requires("ordered")
+                .help("Reduce an ordered withdrawal by the amount instead"),
+        ),
+        SubCommand::with_name("status")
+            .about("Shows the staking and validator status of a pool")
+            .arg(
+                Arg::with_name("address")
+                    .takes_value(true)
+                    .required(true)
+                    .help("Staking or mining address of the pool"),
+            )
+            .arg(
+                Arg::with_name("rpc")
+                    .long("rpc")
+                    .takes_value(true)
+                    .default_value(DEFAULT_RPC_URL)
+                    .help("HTTP JSON-RPC endpoint of the node"),
+            )
+            .args(&contract_args()),
+        transaction_command(
+            "announce_availability",
+            "Announces that the miner's node is available to become a validator",
+            "JSON key file of the miner",
+        )
+        .arg(
+            Arg::with_name("block-number")
+                .long("block-number")
+                .takes_value(true)
+                .requires("block-hash")
+                .help("Number of a recent block, the latest block if not given"),
+        )
+        .arg(
+            Arg::with_name("block-hash")
+                .long("block-hash")
+                .takes_value(true)
+                .requires("block-number")
+                .help("Hash of the block given by --block-number"),
+        ),
+        transaction_command(
+            "rotate_mining_key",
+            "Replaces the mining key of a pool, the new key takes effect with the next key generation round",
+            "JSON key file of the staking address that owns the pool",
+        )
+        .arg(
+            Arg::with_name("new-mining-public-key")
+                .long("new-mining-public-key")
+                .takes_value(true)
+                .required(true)
+                .help("Public key of the new miner, as written to public_key.txt by create_miner"),
+        ),
+    ]
+}
+
+fn pool_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("pool")
+        .long("pool")
+        .takes_value(true)
+        .required(true)
+        .help("Staking address of the pool")
+}
+
+fn amount_arg<'a, 'b>(help: &'b str) -> Arg<'a, 'b> {
+    Arg::with_name("amount")
+        .long("amount")
+        .takes_value(true)
+        .required(true)
+        .help(help)
+}
+
+/// Runs the subcommand, returns `None` if it is not a validator subcommand.
+pub fn run(name: &str, matches: &ArgMatches) -> Option<Result<(), String>> {
+    Some(match name {
+        "create_pool" => create_pool(matches),
+        "stake" => stake(matches),
+        "unstake" => unstake(matches),
+        "status" => status(matches),
+        "announce_availability" => announce_availability(matches),
+        "rotate_mining_key" => rotate_mining_key(matches),
+        _ => return None,
+    })
+}
+
+fn signer(matches: &ArgMatches) -> Result<KeyPair, String> {
+    let path = matches.value_of("key").expect("--key is required; qed");
+    let password = read_password(
+        matches.value_of("password-file"),
+        &format!("Password for {}: ", path),
+    )?;
+    load_key(path, &password)
+}
+
+fn parse_public(public: &str) -> Result<Public, String> {
+    Public::from_str(public.trim().trim_start_matches("0x"))
+        .map_err(|_| format!("Invalid public key: {}", public))
+}
+
+fn parse_ip(ip: Option<&str>) -> Result<[u8; 16], String> {
+    match ip.map(IpAddr::from_str) {
+        None => Ok([0; 16]),
+        Some(Ok(IpAddr::V4(ip))) => Ok(ip.to_ipv6_mapped().octets()),
+        Some(Ok(IpAddr::V6(ip))) => Ok(ip.octets()),
+        Some(Err(_)) => Err(format!("Invalid IP address: {}", ip.unwrap_or(""))),
+    }
+}
+
+fn amount(matches: &ArgMatches, name: &str) -> Result<U256, String> {
+    parse_coins(matches.value_of(name).expect("amounts are required; qed"))
+}
+
+fn create_pool(matches: &ArgMatches) -> Result<(), String> {
+    let staking = SystemContract::staking(matches)?;
+    let mining_public = parse_public(matches.value_of("mining-public-key").unwrap_or(""))?;
+    let stake = amount(matches, "stake")?;
+    let ip = parse_ip(matches.value_of("ip"))?;
+    let data = staking.encode(
+        "addPool",
+        &[
+            Token::Address(public_to_address(&mining_public)),
+            Token::Bytes(mining_public.as_bytes().to_vec()),
+            Token::FixedBytes(ip.to_vec()),
+        ],
+    )?;
+    submit(matches, &signer(matches)?, staking.address, stake, data)
+}
+
+fn stake(matches: &ArgMatches) -> Result<(), String> {
+    let staking = SystemContract::staking(matches)?;
+    let pool = parse_address(matches.value_of("pool").unwrap_or(""))?;
+    let data = staking.encode("stake", &[Token::Address(pool)])?;
+    submit(
+        matches,
+        &signer(matches)?,
+        staking.address,
+        amount(matches, "amount")?,
+        data,
+    )
+}
+
+fn unstake(matches: &ArgMatches) -> Result<(), String> {
+    let staking = SystemContract::staking(matches)?;
+    let pool = parse_address(matches.value_of("pool").unwrap_or(""))?;
+    let amount = amount(matches, "amount")?;
+    let data = if matches.is_present("ordered") {
+        // A negative amount reduces the ordered withdrawal.
+        let amount = if matches.is_present("cancel") {
+            (!amount).overflowing_add(U256::one()).0
+        } else {
+            amount
+        };
+        staking.encode("orderWithdraw", &[Token::Address(pool), Token::Int(amount)])?
+    } else {
+        staking.encode("withdraw", &[Token::Address(pool), Token::Uint(amount)])?
+    };
+    submit(
+        matches,
+        &signer(matches)?,
+        staking.address,
+        U256::zero(),
+        data,
+    )
+}
+
+/// Calls a view function and returns its single return value.
+fn call(
+    client: &RpcClient,
+    contract: &SystemContract,
+    function: &str,
+    params: &[Token],
+) -> Result<Token, String> {
+    let output = client.call(&contract.address, &contract.encode(function, params)?)?;
+    contract
+        .decode(function, &output)?
+        .into_iter()
+        .next()
+        .ok_or_else(|| format!("{} returned no value", function))
+}
+
+fn call_address(
+    client: &RpcClient,
+    contract: &SystemContract,
+    function: &str,
+    address: Address,
+) -> Result<Address, String> {
+    match call(client, contract, function, &[Token::Address(address)])? {
+        Token::Address(address) => Ok(address),
+        token => Err(format!("{} returned {:?}", function, token)),
+    }
+}
+
+fn call_uint(
+    client: &RpcClient,
+    contract: &SystemContract,
+    function: &str,
+    address: Address,
+) -> Result<U256, String> {
+    match call(client, contract, function, &[Token::Address(address)])? {
+        Token::Uint(value) => Ok(value),
+        token => Err(format!("{} returned {:?}", function, token)),
+    }
+}
+
+fn call_bool(
+    client: &RpcClient,
+    contract: &SystemContract,
+    function: &str,
+    address: Address,
+) -> Result<bool, String> {
+    match call(client, contract, function, &[Token::Address(address)])? {
+        Token::Bool(value) => Ok(value),
+        token => Err(format!("{} returned {:?}", function, token)),
+    }
+}
+
+fn status(matches: &ArgMatches) -> Result<(), String> {
+    let client = RpcClient::new(matches.value_of("rpc").unwrap_or_default())?;
+    let staking = SystemContract::staking(matches)?;
+    let validator_set = SystemContract::validator_set(matches)?;
+    let address = parse_address(matches.value_of("address").unwrap_or(""))?;
+
+    // The address is either the staking or the mining address of the pool.
+    let (staking_address, mining_address) =
+        match call_address(&client, &validator_set, "miningByStakingAddress", address)? {
+            mining_address if !mining_address.is_zero() => (address, mining_address),
+            _ => (
+                call_address(&client, &validator_set, "stakingByMiningAddress", address)?,
+                address,
+            ),
+        };
+    if staking_address.is_zero() {
+        return Err(format!("No pool is registered for {:?}", address));
+    }
+
+    let available_since = call_uint(
+        &client,
+        &validator_set,
+        "validatorAvailableSince",
+        mining_address,
+    )?;
+    println!("Staking address: {:?}", staking_address);
+    println!("Mining address: {:?}", mining_address);
+    println!(
+        "Pool active: {}",
+        call_bool(&client, &staking, "isPoolActive", staking_address)?
+    );
+    println!(
+        "Total stake: {} wei",
+        call_uint(&client, &staking, "stakeAmountTotal", staking_address)?
+    );
+    if available_since.is_zero() {
+        println!("Available: no");
+    } else {
+        println!("Available: since {}", available_since);
+    }
+    println!(
+        "Current validator: {}",
+        call_bool(&client, &validator_set, "isValidator", mining_address)?
+    );
+    println!(
+        "Pending validator: {}",
+        call_bool(
+            &client,
+            &validator_set,
+            "isPendingValidator",
+            mining_address
+        )?
+    );
+    Ok(())
+}
+
+fn announce_availability(matches: &ArgMatches) -> Result<(), String> {
+    let validator_set = SystemContract::validator_set(matches)?;
+    let (block_number, block_hash) = match (
+        matches.value_of("block-number"),
+        matches.value_of("block-hash"),
+    ) {
+        (Some(number), Some(hash)) => (
+            U256::from_dec_str(number).map_err(|_| format!("Invalid block number: {}", number))?,
+            H256::from_str(hash.trim_start_matches("0x"))
+                .map_err(|_| format!("Invalid block hash: {}", hash))?,
+        ),
+        _ => match rpc_client(matches)? {
+            Some(client) => client.latest_block()?,
+            None => {
+                return Err("--block-number and --block-hash are required with --offline".into())
+            }
+        },
+    };
+    let data = validator_set.encode(
+        "announceAvailability",
+        &[
+            Token::Uint(block_number),
+            Token::FixedBytes(block_hash.as_bytes().to_vec()),
+        ],
+    )?;
+    submit(
+        matches,
+        &signer(matches)?,
+        validator_set.address,
+        U256::zero(),
+        data,
+    )
+}
+
+fn rotate_mining_key(matches: &ArgMatches) -> Result<(), String> {
+    let validator_set = SystemContract::validator_set(matches)?;
+    let new_public = parse_public(matches.value_of("new-mining-public-key").unwrap_or(""))?;
+    let data = validator_set.encode(
+        "changeMiningAddress",
+        &[
+            Token::Address(public_to_address(&new_public)),
+            Token::Bytes(new_public.as_bytes().to_vec()),
+        ],
+    )?;
+    submit(
+        matches,
+        &signer(matches)?,
+        validator_set.address,
+        U256::zero(),
+        data,
+    )?;
+    println!(
+        "Once the transaction is included, switch the node's engine signer to {:?} with parity_setEngineSigner. \
+         The new key takes part in the next key generation round and takes over at the next POSDAO epoch.",
+        public_to_address(&new_public)
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_ip() {
+        assert_eq!(parse_ip(None).unwrap(), [0; 16]);
+        assert_eq!(
+            parse_ip(Some("192.168.0.1")).unwrap(),
+            [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0xff, 0xff, 192, 168, 0, 1]
+        );
+        assert_eq!(parse_ip(Some("::1")).unwrap()[15], 1);
+        assert!(parse_ip(Some("localhost")).is_err());
+    }
+
+    #[test]
+    fn test_encode_change_mining_address() {
+        let matches = App::new("dmd")
+            .args(&contract_args())
+            .get_matches_from(vec!["dmd"]);
+        let validator_set = SystemContract::validator_set(&matches).unwrap();
+        let public = Public::from_low_u64_be(1);
+        let data = validator_set
+            .encode(
+                "changeMiningAddress",
+                &[
+                    Token::Address(public_to_address(&public)),
+                    Token::Bytes(public.as_bytes().to_vec()),
+                ],
+            )
+            .unwrap();
+        // Selector, address, offset and length of the public key and the 64 bytes of the key.
+        assert_eq!(data.len(), 4 + 32 * 3 + 64);
+    }
+}
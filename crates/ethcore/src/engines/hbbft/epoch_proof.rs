@@ -0,0 +1,153 @@
+use super::sealing::RlpSig;
+use engines::EpochVerifier;
+use error::{BlockError, Error};
+use ethereum_types::H256;
+use hbbft::crypto::{PublicKey, Signature, PK_SIZE};
+use machine::EthereumMachine;
+use rlp::{self, DecoderError, Rlp, RlpStream};
+use types::{header::Header, BlockNumber};
+
+/// Epoch transition proof of the hbbft engine.
+///
+/// Stored with every POSDAO epoch transition, it carries the threshold public master key
+/// that is used to verify the seals of all children of the transition block.
+///
+/// RLP format: [posdao_epoch, public_master_key, (signed transition header)]
+///
+/// The signed transition header is missing for the first recorded transition,
+/// since the genesis key can not be proven by an earlier epoch.
+#[derive(Debug, PartialEq)]
+pub struct EpochProof {
+    /// The POSDAO epoch the children of the transition block belong to.
+    pub posdao_epoch: u64,
+    /// The threshold public master key of the POSDAO epoch.
+    pub public_master_key: PublicKey,
+}
+
+const PUBLIC_KEY_ERR: &str = "RLP bytes don't encode a valid threshold public key";
+
+impl EpochProof {
+    /// Encodes the proof, optionally followed by the sealed transition header
+    /// which is used to prove finality with the key of the previous epoch.
+    pub fn encode(&self, transition_header: Option<&Header>) -> Vec<u8> {
+        let mut stream = RlpStream::new_list(if transition_header.is_some() { 3 } else { 2 });
+        stream
+            .append(&self.posdao_epoch)
+            .append(&self.public_master_key.to_bytes().to_vec());
+        if let Some(header) = transition_header {
+            stream.append_raw(&header.encoded().into_inner(), 1);
+        }
+        stream.out()
+    }
+
+    /// Decodes the proof and returns the raw sealed transition header, if present.
+    pub fn decode(proof: &[u8]) -> Result<(Self, Option<&[u8]>), DecoderError> {
+        let rlp = Rlp::new(proof);
+        let posdao_epoch: u64 = rlp.val_at(0)?;
+        let key_bytes: Vec<u8> = rlp.val_at(1)?;
+        if key_bytes.len() != PK_SIZE {
+            return Err(DecoderError::Custom(PUBLIC_KEY_ERR));
+        }
+        let mut key_array = [0u8; PK_SIZE];
+        key_array.copy_from_slice(&key_bytes);
+        let public_master_key =
+            PublicKey::from_bytes(key_array).map_err(|_| DecoderError::Custom(PUBLIC_KEY_ERR))?;
+
+        let transition_header = match rlp.item_count()? {
+            2 => None,
+            3 => Some(rlp.at(2)?.as_raw()),
+            _ => return Err(DecoderError::RlpIncorrectListLen),
+        };
+
+        Ok((
+            EpochProof {
+                posdao_epoch,
+                public_master_key,
+            },
+            transition_header,
+        ))
+    }
+}
+
+/// Verifies block seals of a single POSDAO epoch against its threshold public master key.
+pub struct HbbftEpochVerifier {
+    public_master_key: PublicKey,
+    eip1559_transition: BlockNumber,
+}
+
+impl HbbftEpochVerifier {
+    pub fn new(public_master_key: PublicKey, eip1559_transition: BlockNumber) -> Self {
+        HbbftEpochVerifier {
+            public_master_key,
+            eip1559_transition,
+        }
+    }
+}
+
+impl EpochVerifier<EthereumMachine> for HbbftEpochVerifier {
+    fn verify_light(&self, header: &Header) -> Result<(), Error> {
+        if header.seal().len() != 1 {
+            return Err(BlockError::InvalidSeal.into());
+        }
+        let RlpSig(sig): RlpSig<Signature> = rlp::decode(&header.seal()[0])?;
+        if self.public_master_key.verify(&sig, header.bare_hash()) {
+            Ok(())
+        } else {
+            Err(BlockError::InvalidSeal.into())
+        }
+    }
+
+    /// The finality proof of an hbbft epoch transition is the sealed transition header,
+    /// signed by the validators of the previous epoch.
+    fn check_finality_proof(&self, proof: &[u8]) -> Option<Vec<H256>> {
+        let header = Header::decode_rlp(&Rlp::new(proof), self.eip1559_transition).ok()?;
+        match self.verify_light(&header) {
+            Ok(()) => Some(vec![header.hash()]),
+            Err(_) => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hbbft::crypto::SecretKey;
+    use rand_065;
+
+    #[test]
+    fn test_epoch_proof_rlp() {
+        let secret_key: SecretKey = rand_065::random();
+        let proof = EpochProof {
+            posdao_epoch: 7,
+            public_master_key: secret_key.public_key(),
+        };
+        let encoded = proof.encode(None);
+        let (decoded, header) = EpochProof::decode(&encoded).expect("decode EpochProof");
+        assert_eq!(decoded, proof);
+        assert!(header.is_none());
+    }
+
+    #[test]
+    fn test_epoch_verifier_seal() {
+        let secret_key: SecretKey = rand_065::random();
+        let verifier = HbbftEpochVerifier::new(secret_key.public_key(), BlockNumber::max_value());
+
+        let mut header = Header::new();
+        header.set_number(5);
+        let sig = secret_key.sign(header.bare_hash());
+        header.set_seal(vec![rlp::encode(&RlpSig(&sig))]);
+        assert!(verifier.verify_light(&header).is_ok());
+
+        let proof = header.encoded().into_inner();
+        assert_eq!(
+            verifier.check_finality_proof(&proof),
+            Some(vec![header.hash()])
+        );
+
+        let other_key: SecretKey = rand_065::random();
+        let other_verifier =
+            HbbftEpochVerifier::new(other_key.public_key(), BlockNumber::max_value());
+        assert!(other_verifier.verify_light(&header).is_err());
+        assert_eq!(other_verifier.check_finality_proof(&proof), None);
+    }
+}
@@ -0,0 +1,309 @@
+use super::{hbbft_state::HbMessage, NodeId};
+use hbbft::Epoched;
+use stats::PrometheusRegistry;
+use std::collections::BTreeMap;
+
+/// Bounds of the cache of consensus messages for future honey badger epochs.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct FutureMessagesLimits {
+    /// Maximum number of epochs a message may be ahead of the current epoch.
+    pub max_future_epochs: u64,
+    /// Maximum number of cached messages per sender.
+    pub max_messages_per_sender: usize,
+    /// Maximum total size of the cached messages, in bytes.
+    pub max_bytes: usize,
+}
+
+impl Default for FutureMessagesLimits {
+    fn default() -> Self {
+        FutureMessagesLimits {
+            max_future_epochs: 3,
+            max_messages_per_sender: 1000,
+            max_bytes: 32 * 1024 * 1024,
+        }
+    }
+}
+
+/// Reasons for dropping a message instead of caching it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum Eviction {
+    /// The sender is neither a current nor a pending validator.
+    UnknownSender,
+    /// The epoch of the message is too far ahead of the current epoch.
+    TooFarAhead,
+    /// The sender exceeded its quota of cached messages.
+    SenderQuota,
+    /// The cache exceeded its byte limit.
+    ByteLimit,
+}
+
+const EVICTIONS: [Eviction; 4] = [
+    Eviction::UnknownSender,
+    Eviction::TooFarAhead,
+    Eviction::SenderQuota,
+    Eviction::ByteLimit,
+];
+
+impl Eviction {
+    fn name(&self) -> &'static str {
+        match self {
+            Eviction::UnknownSender => "unknown_sender",
+            Eviction::TooFarAhead => "too_far_ahead",
+            Eviction::SenderQuota => "sender_quota",
+            Eviction::ByteLimit => "byte_limit",
+        }
+    }
+}
+
+struct CachedMessage {
+    sender: NodeId,
+    message: HbMessage,
+    size: usize,
+}
+
+/// Cache of consensus messages for future honey badger epochs, which are replayed
+/// once their epoch becomes current.
+///
+/// The cache is bounded, so faulty or malicious peers sending messages of far future
+/// epochs can not exhaust the memory of the node. If the byte limit is reached,
+/// the messages of the farthest epochs are evicted first.
+pub(crate) struct FutureMessagesCache {
+    limits: FutureMessagesLimits,
+    messages: BTreeMap<u64, Vec<CachedMessage>>,
+    messages_per_sender: BTreeMap<NodeId, usize>,
+    total_bytes: usize,
+    evictions: [u64; 4],
+}
+
+impl FutureMessagesCache {
+    pub fn new(limits: FutureMessagesLimits) -> Self {
+        FutureMessagesCache {
+            limits,
+            messages: BTreeMap::new(),
+            messages_per_sender: BTreeMap::new(),
+            total_bytes: 0,
+            evictions: Default::default(),
+        }
+    }
+
+    /// Returns the maximum number of epochs a message may be ahead of the current epoch.
+    pub fn max_future_epochs(&self) -> u64 {
+        self.limits.max_future_epochs
+    }
+
+    /// Returns the number of cached messages.
+    pub fn len(&self) -> usize {
+        self.messages.values().map(Vec::len).sum()
+    }
+
+    fn evict(&mut self, reason: Eviction, epoch: u64, sender: &NodeId) {
+        trace!(target: "consensus", "Dropping future message of epoch {} from {}: {:?}", epoch, sender, reason);
+        self.evictions[reason as usize] += 1;
+    }
+
+    fn remove_accounting(&mut self, cached: &CachedMessage) {
+        self.total_bytes -= cached.size;
+        if let Some(count) = self.messages_per_sender.get_mut(&cached.sender) {
+            *count -= 1;
+            if *count == 0 {
+                self.messages_per_sender.remove(&cached.sender);
+            }
+        }
+    }
+
+    /// Caches a message of a future epoch, unless it violates the limits of the cache.
+    /// `is_known_sender` tells whether the sender is a current or pending validator.
+    pub fn insert(
+        &mut self,
+        current_epoch: u64,
+        sender: NodeId,
+        message: HbMessage,
+        is_known_sender: bool,
+    ) {
+        let epoch = message.epoch();
+        if !is_known_sender {
+            return self.evict(Eviction::UnknownSender, epoch, &sender);
+        }
+        if epoch > current_epoch + self.limits.max_future_epochs {
+            return self.evict(Eviction::TooFarAhead, epoch, &sender);
+        }
+        if self.messages_per_sender.get(&sender).cloned().unwrap_or(0)
+            >= self.limits.max_messages_per_sender
+        {
+            return self.evict(Eviction::SenderQuota, epoch, &sender);
+        }
+
+        let size = match bincode::serialized_size(&message) {
+            Ok(size) => size as usize,
+            Err(_) => return self.evict(Eviction::ByteLimit, epoch, &sender),
+        };
+        // Make room by evicting messages of epochs farther ahead than the new message.
+        while self.total_bytes + size > self.limits.max_bytes {
+            let farthest_epoch = match self.messages.keys().next_back() {
+                Some(farthest_epoch) if *farthest_epoch > epoch => *farthest_epoch,
+                _ => return self.evict(Eviction::ByteLimit, epoch, &sender),
+            };
+            let evicted = {
+                let messages = self
+                    .messages
+                    .get_mut(&farthest_epoch)
+                    .expect("the epoch is a key of the map; qed");
+                let evicted = messages.pop();
+                if messages.is_empty() {
+                    self.messages.remove(&farthest_epoch);
+                }
+                evicted
+            };
+            if let Some(evicted) = evicted {
+                self.remove_accounting(&evicted);
+                self.evict(Eviction::ByteLimit, farthest_epoch, &evicted.sender);
+            }
+        }
+
+        self.total_bytes += size;
+        *self.messages_per_sender.entry(sender).or_insert(0) += 1;
+        self.messages.entry(epoch).or_default().push(CachedMessage {
+            sender,
+            message,
+            size,
+        });
+    }
+
+    /// Removes the messages of the given and all previous epochs,
+    /// returns the messages of the given epoch.
+    pub fn take_epoch(&mut self, epoch: u64) -> Vec<(NodeId, HbMessage)> {
+        let remaining = self.messages.split_off(&(epoch + 1));
+        let removed = std::mem::replace(&mut self.messages, remaining);
+        let mut taken = Vec::new();
+        for (removed_epoch, messages) in removed {
+            for cached in messages {
+                self.remove_accounting(&cached);
+                if removed_epoch == epoch {
+                    taken.push((cached.sender, cached.message));
+                }
+            }
+        }
+        taken
+    }
+
+    pub fn prometheus_metrics(&self, r: &mut PrometheusRegistry) {
+        r.register_gauge(
+            "hbbft_future_messages_cached",
+            "Number of cached consensus messages of future honey badger epochs",
+            self.len() as i64,
+        );
+        r.register_gauge(
+            "hbbft_future_messages_cached_bytes",
+            "Size of the cached consensus messages of future honey badger epochs",
+            self.total_bytes as i64,
+        );
+        for eviction in EVICTIONS.iter() {
+            r.register_counter(
+                &format!("hbbft_future_messages_evicted_{}", eviction.name()),
+                &format!(
+                    "Number of consensus messages of future epochs dropped due to: {}",
+                    eviction.name()
+                ),
+                self.evictions[*eviction as usize] as i64,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crypto::publickey::{Generator, Random};
+    use engines::hbbft::contribution::{
+        unix_now_secs, Contribution, DEFAULT_RANDOM_BYTES_PER_EPOCH,
+    };
+    use hbbft::{honey_badger::HoneyBadger, NetworkInfo};
+    use rand_065;
+    use std::sync::Arc;
+
+    /// Produces the messages the first validator of a network of four validators sends
+    /// when proposing an empty contribution in the given epoch.
+    fn messages_of_epoch(epoch: u64) -> (NodeId, Vec<HbMessage>) {
+        let mut rng = rand_065::thread_rng();
+        let ids: Vec<NodeId> = (0..4)
+            .map(|_| NodeId(*Random.generate().public()))
+            .collect();
+        let net_infos = NetworkInfo::generate_map(ids.clone(), &mut rng)
+            .expect("NetworkInfo generation is expected to always succeed");
+        let mut honey_badger: HoneyBadger<Contribution, NodeId> =
+            HoneyBadger::builder(Arc::new(net_infos[&ids[0]].clone())).build();
+        honey_badger.skip_to_epoch(epoch);
+        let step = honey_badger
+            .propose(
+                &Contribution::new(
+                    &Vec::new(),
+                    DEFAULT_RANDOM_BYTES_PER_EPOCH,
+                    unix_now_secs(),
+                    &mut rng,
+                ),
+                &mut rng,
+            )
+            .expect("Proposing a contribution must succeed");
+        (
+            ids[0],
+            step.messages.into_iter().map(|m| m.message).collect(),
+        )
+    }
+
+    #[test]
+    fn test_future_messages_epoch_and_sender_limits() {
+        let mut cache = FutureMessagesCache::new(FutureMessagesLimits {
+            max_future_epochs: 2,
+            max_messages_per_sender: 2,
+            max_bytes: usize::max_value(),
+        });
+        let (sender, messages) = messages_of_epoch(3);
+        assert!(messages.len() > 2);
+
+        cache.insert(1, sender, messages[0].clone(), false);
+        assert_eq!(cache.evictions[Eviction::UnknownSender as usize], 1);
+        cache.insert(0, sender, messages[0].clone(), true);
+        assert_eq!(cache.evictions[Eviction::TooFarAhead as usize], 1);
+
+        for message in &messages {
+            cache.insert(1, sender, message.clone(), true);
+        }
+        assert_eq!(cache.len(), 2);
+        assert_eq!(
+            cache.evictions[Eviction::SenderQuota as usize],
+            messages.len() as u64 - 2
+        );
+
+        // Taking the messages of an epoch frees the quota of the sender.
+        assert_eq!(cache.take_epoch(3).len(), 2);
+        assert_eq!(cache.len(), 0);
+        assert_eq!(cache.total_bytes, 0);
+        assert!(cache.messages_per_sender.is_empty());
+    }
+
+    #[test]
+    fn test_future_messages_byte_limit() {
+        let (sender, near_messages) = messages_of_epoch(2);
+        let (_, far_messages) = messages_of_epoch(3);
+        let size = |m: &HbMessage| bincode::serialized_size(m).unwrap() as usize;
+        let mut cache = FutureMessagesCache::new(FutureMessagesLimits {
+            max_future_epochs: 5,
+            max_messages_per_sender: usize::max_value(),
+            max_bytes: size(&far_messages[0]) + size(&near_messages[0]) - 1,
+        });
+
+        cache.insert(1, sender, far_messages[0].clone(), true);
+        assert_eq!(cache.len(), 1);
+
+        // The message of the nearer epoch replaces the message of the farther epoch.
+        cache.insert(1, sender, near_messages[0].clone(), true);
+        assert_eq!(cache.len(), 1);
+        assert_eq!(cache.evictions[Eviction::ByteLimit as usize], 1);
+
+        // A message of a farther epoch does not replace cached messages.
+        cache.insert(1, sender, far_messages[0].clone(), true);
+        assert_eq!(cache.len(), 1);
+        assert_eq!(cache.evictions[Eviction::ByteLimit as usize], 2);
+        assert_eq!(cache.take_epoch(2).len(), 1);
+    }
+}
@@ -111,6 +111,34 @@ pub fn enodes_to_pub_keys(
     )
 }
 
+/// Returns the serialized Parts and Acks of the given validators, in the order of the validators,
+/// as expected by the initialization of the key history contract.
+pub fn serialized_parts_and_acks(
+    parts: &BTreeMap<Public, Part>,
+    acks: &BTreeMap<Public, Vec<PartOutcome>>,
+    validators: &[Public],
+) -> (Vec<Vec<u8>>, Vec<Vec<Vec<u8>>>) {
+    let serialized_parts = validators
+        .iter()
+        .map(|id| bincode::serialize(&parts[id]).expect("Part has to serialize"))
+        .collect();
+    let serialized_acks = validators
+        .iter()
+        .map(|id| {
+            acks[id]
+                .iter()
+                .map(|outcome| match outcome {
+                    PartOutcome::Valid(Some(ack)) => {
+                        bincode::serialize(ack).expect("Ack has to serialize")
+                    }
+                    _ => panic!("Expected Part Outcome to be valid"),
+                })
+                .collect()
+        })
+        .collect();
+    (serialized_parts, serialized_acks)
+}
+
 #[derive(Serialize, Deserialize)]
 struct KeyGenHistoryData {
     validators: Vec<String>,
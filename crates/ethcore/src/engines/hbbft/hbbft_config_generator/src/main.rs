@@ -1,6 +1,7 @@
 extern crate bincode;
 #[macro_use]
 extern crate clap;
+extern crate ethabi;
 extern crate ethcore;
 extern crate ethereum_types;
 extern crate ethkey;
@@ -14,12 +15,15 @@ extern crate serde_json;
 extern crate toml;
 
 mod keygen_history_helpers;
+mod testnet;
 
 use clap::{App, Arg};
 use ethstore::{KeyFile, SafeAccount};
 use keygen_history_helpers::{enodes_to_pub_keys, generate_keygens, key_sync_history_data};
 use parity_crypto::publickey::{Address, Generator, KeyPair, Public, Random, Secret};
-use std::{collections::BTreeMap, fmt::Write, fs, num::NonZeroU32, str::FromStr, sync::Arc};
+use std::{
+    collections::BTreeMap, fmt::Write, fs, num::NonZeroU32, path::Path, str::FromStr, sync::Arc,
+};
 use toml::{map::Map, Value};
 
 pub fn create_account() -> (Secret, Public, Address) {
@@ -37,21 +41,40 @@ pub struct Enode {
     address: Address,
     idx: usize,
     ip: String,
+    port_base: u16,
 }
 
 impl ToString for Enode {
     fn to_string(&self) -> String {
         // Example:
         // enode://30ccdeb8c31972f570e4eea0673cd08cbe7cefc5de1d70119b39c63b1cba33b48e494e9916c0d1eab7d296774f3573da46025d1accdef2f3690bc9e6659a34b4@192.168.0.101:30300
-        let port = 30300usize + self.idx;
+        let port = self.port_base as usize + self.idx;
         format!("enode://{:x}@{}:{}", self.public, self.ip, port)
     }
 }
 
+/// The first ports of the nodes, node `i` listens on the base port plus `i`.
+pub struct PortBase {
+    p2p: u16,
+    rpc: u16,
+    ws: u16,
+}
+
+impl Default for PortBase {
+    fn default() -> Self {
+        PortBase {
+            p2p: 30300,
+            rpc: 8540,
+            ws: 9540,
+        }
+    }
+}
+
 fn generate_enodes(
     num_nodes: usize,
     private_keys: Vec<Secret>,
     external_ip: Option<&str>,
+    port_base: u16,
 ) -> BTreeMap<Public, Enode> {
     let mut map = BTreeMap::new();
     for i in 0..num_nodes {
@@ -81,6 +104,7 @@ fn generate_enodes(
                 address,
                 idx,
                 ip: ip.into(),
+                port_base,
             },
         );
     }
@@ -104,10 +128,11 @@ fn to_toml(
     config_type: &ConfigType,
     external_ip: Option<&str>,
     signer_address: &Address,
+    ports: &PortBase,
 ) -> Value {
-    let base_port = 30300i64;
-    let base_rpc_port = 8540i64;
-    let base_ws_port = 9540i64;
+    let base_port = ports.p2p as i64;
+    let base_rpc_port = ports.rpc as i64;
+    let base_ws_port = ports.ws as i64;
 
     let mut parity = Map::new();
     match config_type {
@@ -276,13 +301,13 @@ fn main() {
         .arg(
             Arg::with_name("validator_nodes")
                 .help("The number of initial validators to generate")
-                .required(true)
+                .required_unless("network")
                 .index(1),
         )
         .arg(
             Arg::with_name("total_nodes")
                 .help("The number of total validators to generate")
-                .required(true)
+                .required_unless("network")
                 .index(2),
         )
         .arg(
@@ -303,8 +328,20 @@ fn main() {
                 .required(false)
                 .takes_value(true),
         )
+        .arg(
+            Arg::with_name("network")
+                .long("network")
+                .help("Generates a complete local network, including the chain spec and start scripts, from the given TOML file")
+                .required(false)
+                .takes_value(true),
+        )
         .get_matches();
 
+    if let Some(network) = matches.value_of("network") {
+        testnet::generate_testnet(Path::new(network));
+        return;
+    }
+
     let num_nodes_validators: usize = matches
         .value_of("validator_nodes")
         .expect("Number of validators input required")
@@ -342,7 +379,8 @@ fn main() {
         assert!(private_keys.len() == num_nodes_total);
     };
 
-    let enodes_map = generate_enodes(num_nodes_total, private_keys, external_ip);
+    let ports = PortBase::default();
+    let enodes_map = generate_enodes(num_nodes_total, private_keys, external_ip, ports.p2p);
     let mut rng = rand::thread_rng();
 
     let pub_keys = enodes_to_pub_keys(&enodes_map);
@@ -370,8 +408,14 @@ fn main() {
             .expect("enode should be written to the reserved peers string");
         let i = enode.idx;
         let file_name = format!("hbbft_validator_{}.toml", i);
-        let toml_string = toml::to_string(&to_toml(i, &config_type, external_ip, &enode.address))
-            .expect("TOML string generation should succeed");
+        let toml_string = toml::to_string(&to_toml(
+            i,
+            &config_type,
+            external_ip,
+            &enode.address,
+            &ports,
+        ))
+        .expect("TOML string generation should succeed");
         fs::write(file_name, toml_string).expect("Unable to write config file");
 
         let file_name = format!("hbbft_validator_key_{}", i);
@@ -398,6 +442,7 @@ fn main() {
         &ConfigType::Rpc,
         external_ip,
         &Address::default(),
+        &ports,
     ))
     .expect("TOML string generation should succeed");
     fs::write("rpc_node.toml", rpc_string).expect("Unable to write rpc config file");
@@ -467,7 +512,7 @@ mod tests {
         let num_nodes = 4;
         let t = 1;
 
-        let enodes = generate_enodes(num_nodes, Vec::new(), None);
+        let enodes = generate_enodes(num_nodes, Vec::new(), None, PortBase::default().p2p);
         let pub_keys = enodes_to_pub_keys(&enodes);
         let mut rng = rand::thread_rng();
 
@@ -0,0 +1,604 @@
+//! Generates a complete, runnable local network from a network description file:
+//! the chain spec with the initialized system contracts, a directory per node and start/stop scripts.
+
+use crate::{
+    generate_enodes,
+    keygen_history_helpers::{enodes_to_pub_keys, generate_keygens, serialized_parts_and_acks},
+    to_toml, write_json_for_secret, ConfigType, Enode, PortBase,
+};
+use ethabi::Token;
+use ethereum_types::U256;
+use parity_crypto::publickey::{Address, Generator, KeyPair, Public, Random};
+use rustc_hex::{FromHex, ToHex};
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+use std::{
+    fmt::Write,
+    fs,
+    net::IpAddr,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::Arc,
+};
+
+const KEY_HISTORY_ABI: &[u8] =
+    include_bytes!("../../../../../res/contracts/key_history_contract.json");
+const VALIDATOR_SET_ABI: &[u8] =
+    include_bytes!("../../../../../res/contracts/validator_set_hbbft.json");
+const STAKING_ABI: &[u8] = include_bytes!("../../../../../res/contracts/staking_contract.json");
+const BLOCK_REWARD_ABI: &[u8] =
+    include_bytes!("../../../../../res/contracts/block_reward_hbbft.json");
+
+/// Length of the ABI encoded constructor arguments of a proxy with empty initialization data:
+/// the implementation address, the admin address, the offset and the length of the data.
+const PROXY_ARGUMENTS_LEN: usize = 4 * 32;
+
+/// Description of the network to generate.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct NetworkConfig {
+    /// Number of initial validators.
+    validators: usize,
+    /// Number of validator nodes, including the nodes that are not initial validators.
+    /// Defaults to the number of initial validators.
+    total_nodes: Option<usize>,
+    /// Threshold of the initial key generation. The engine only processes key generations with
+    /// a threshold of a third of the validators, rounded down, so this is the only accepted value.
+    fault_threshold: Option<usize>,
+    /// The devp2p port of the RPC node, validator `i` listens on the port base plus `i`. Defaults to 30300.
+    port_base: Option<u16>,
+    /// The JSON-RPC port of the RPC node. Defaults to 8540.
+    rpc_port_base: Option<u16>,
+    /// The WebSocket port of the RPC node. Defaults to 9540.
+    ws_port_base: Option<u16>,
+    /// The IP address the nodes are reached at. Defaults to 127.0.0.1.
+    external_ip: Option<String>,
+    /// Chain spec containing the system contracts behind proxies that are not initialized yet.
+    spec_template: PathBuf,
+    /// Directory the network is written to. Defaults to "testnet".
+    output_dir: Option<PathBuf>,
+    /// Balance of every mining and staking address in the genesis block, in whole coins. Defaults to 10000.
+    genesis_balance: Option<u64>,
+    #[serde(default)]
+    staking: StakingConfig,
+    #[serde(default)]
+    contracts: SystemContracts,
+}
+
+/// Parameters the staking contract is initialized with.
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct StakingConfig {
+    /// Minimum stake of a delegator, in whole coins.
+    delegator_min_stake: u64,
+    /// Minimum stake of a candidate, in whole coins.
+    candidate_min_stake: u64,
+    /// Duration of a staking epoch, in seconds.
+    fixed_epoch_duration: u64,
+    /// Duration of the key generation at the end of a staking epoch, in seconds.
+    transition_timeframe_length: u64,
+    /// Duration at the end of a staking epoch in which stakes can not be withdrawn, in seconds.
+    withdraw_disallow_period: u64,
+}
+
+impl Default for StakingConfig {
+    fn default() -> Self {
+        StakingConfig {
+            delegator_min_stake: 100,
+            candidate_min_stake: 1000,
+            fixed_epoch_duration: 3600,
+            transition_timeframe_length: 300,
+            withdraw_disallow_period: 60,
+        }
+    }
+}
+
+/// Addresses of the proxies of the system contracts in the spec template.
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct SystemContracts {
+    validator_set: Address,
+    staking: Address,
+    block_reward: Address,
+    random: Address,
+    keygen_history: Address,
+}
+
+impl Default for SystemContracts {
+    fn default() -> Self {
+        let address = |a: &str| Address::from_str(a).unwrap();
+        SystemContracts {
+            validator_set: address("1000000000000000000000000000000000000001"),
+            staking: address("1100000000000000000000000000000000000001"),
+            block_reward: address("2000000000000000000000000000000000000001"),
+            random: address("3000000000000000000000000000000000000001"),
+            keygen_history: address("7000000000000000000000000000000000000001"),
+        }
+    }
+}
+
+/// An initial validator: its node, and the staking address of its pool.
+struct Validator<'a> {
+    enode: &'a Enode,
+    staking: KeyPair,
+}
+
+fn wei(coins: u64) -> U256 {
+    U256::from(coins) * U256::exp10(18)
+}
+
+fn encode_call(abi: &[u8], function: &str, params: &[Token]) -> Vec<u8> {
+    ethabi::Contract::load(abi)
+        .expect("Contract ABI must be valid")
+        .function(function)
+        .expect("Contract ABI must contain the function")
+        .encode_input(params)
+        .expect("Parameters must match the function ABI")
+}
+
+fn addresses(addresses: impl Iterator<Item = Address>) -> Token {
+    Token::Array(addresses.map(Token::Address).collect())
+}
+
+/// Returns the internet address of a node as stored in the staking contract.
+fn internet_address(ip: &str) -> Token {
+    let octets = match ip.parse() {
+        Ok(IpAddr::V4(ip)) => ip.to_ipv6_mapped().octets(),
+        Ok(IpAddr::V6(ip)) => ip.octets(),
+        Err(_) => [0; 16],
+    };
+    Token::FixedBytes(octets.to_vec())
+}
+
+/// Returns the constructor of a proxy from the spec template, with the initialization data replaced.
+/// The template proxies are expected to be constructed with empty initialization data.
+fn initialize_proxy(constructor: &str, data: Vec<u8>) -> String {
+    let code: Vec<u8> = constructor
+        .trim_start_matches("0x")
+        .from_hex()
+        .expect("Proxy constructor must be hex encoded");
+    assert!(
+        code.len() > PROXY_ARGUMENTS_LEN,
+        "Proxy constructor is too short"
+    );
+    let (creation_code, arguments) = code.split_at(code.len() - PROXY_ARGUMENTS_LEN);
+    let mut arguments = ethabi::decode(
+        &[
+            ethabi::ParamType::Address,
+            ethabi::ParamType::Address,
+            ethabi::ParamType::Bytes,
+        ],
+        arguments,
+    )
+    .expect("Proxy must be constructed with an implementation, an admin and initialization data");
+    assert_eq!(
+        arguments[2],
+        Token::Bytes(Vec::new()),
+        "Proxy must not be initialized in the spec template"
+    );
+    arguments[2] = Token::Bytes(data);
+
+    let mut code = creation_code.to_vec();
+    code.extend(ethabi::encode(&arguments));
+    format!("0x{}", code.to_hex::<String>())
+}
+
+fn account_mut<'a>(accounts: &'a mut Map<String, Value>, address: &Address) -> &'a mut Value {
+    accounts
+        .iter_mut()
+        .find(|(key, _)| Address::from_str(key.trim_start_matches("0x")).ok() == Some(*address))
+        .map(|(_, account)| account)
+        .unwrap_or_else(|| panic!("Spec template must contain the account {:?}", address))
+}
+
+/// Initializes the system contracts in the genesis block of the spec template,
+/// configures the engine and funds the initial validators.
+fn assemble_spec(
+    config: &NetworkConfig,
+    template: &str,
+    validators: &[Validator],
+    parts: Vec<Vec<u8>>,
+    acks: Vec<Vec<Vec<u8>>>,
+) -> Value {
+    let contracts = &config.contracts;
+    let staking = &config.staking;
+    let mining_addresses = || validators.iter().map(|v| v.enode.address);
+    let staking_addresses = || validators.iter().map(|v| v.staking.address());
+
+    let public_keys = validators
+        .iter()
+        .flat_map(|v| {
+            let public = v.enode.public.as_bytes();
+            vec![
+                Token::FixedBytes(public[..32].to_vec()),
+                Token::FixedBytes(public[32..].to_vec()),
+            ]
+        })
+        .collect();
+    let initializations = vec![
+        (
+            contracts.validator_set,
+            encode_call(
+                VALIDATOR_SET_ABI,
+                "initialize",
+                &[
+                    Token::Address(contracts.block_reward),
+                    Token::Address(contracts.random),
+                    Token::Address(contracts.staking),
+                    Token::Address(contracts.keygen_history),
+                    addresses(mining_addresses()),
+                    addresses(staking_addresses()),
+                ],
+            ),
+        ),
+        (
+            contracts.staking,
+            encode_call(
+                STAKING_ABI,
+                "initialize",
+                &[
+                    Token::Address(contracts.validator_set),
+                    addresses(staking_addresses()),
+                    Token::Uint(wei(staking.delegator_min_stake)),
+                    Token::Uint(wei(staking.candidate_min_stake)),
+                    Token::Uint(staking.fixed_epoch_duration.into()),
+                    Token::Uint(staking.transition_timeframe_length.into()),
+                    Token::Uint(staking.withdraw_disallow_period.into()),
+                    Token::Array(public_keys),
+                    Token::Array(
+                        validators
+                            .iter()
+                            .map(|v| internet_address(&v.enode.ip))
+                            .collect(),
+                    ),
+                ],
+            ),
+        ),
+        (
+            contracts.block_reward,
+            encode_call(
+                BLOCK_REWARD_ABI,
+                "initialize",
+                &[Token::Address(contracts.validator_set)],
+            ),
+        ),
+        (
+            contracts.keygen_history,
+            encode_call(
+                KEY_HISTORY_ABI,
+                "initialize",
+                &[
+                    Token::Address(contracts.validator_set),
+                    addresses(mining_addresses()),
+                    Token::Array(parts.into_iter().map(Token::Bytes).collect()),
+                    Token::Array(
+                        acks.into_iter()
+                            .map(|acks| Token::Array(acks.into_iter().map(Token::Bytes).collect()))
+                            .collect(),
+                    ),
+                ],
+            ),
+        ),
+    ];
+
+    let mut spec: Value = serde_json::from_str(template).expect("Spec template must be valid JSON");
+    let accounts = spec["accounts"]
+        .as_object_mut()
+        .expect("Spec template must contain the genesis accounts");
+    for (address, data) in initializations {
+        let account = account_mut(accounts, &address);
+        let constructor = account["constructor"]
+            .as_str()
+            .unwrap_or_else(|| panic!("Proxy {:?} must have a constructor", address));
+        let constructor = initialize_proxy(constructor, data);
+        account["constructor"] = Value::String(constructor);
+    }
+    let balance = wei(config.genesis_balance.unwrap_or(10_000)).to_string();
+    for address in mining_addresses().chain(staking_addresses()) {
+        accounts.insert(format!("{:?}", address), json!({ "balance": balance }));
+    }
+
+    let params = spec["engine"]["hbbft"]["params"]
+        .as_object_mut()
+        .expect("Spec template must use the hbbft engine");
+    params.remove("isUnitTest");
+    params.insert(
+        "blockRewardContractAddress".into(),
+        json!(format!("{:?}", contracts.block_reward)),
+    );
+    for (name, address) in &[
+        ("validatorSetContractAddress", contracts.validator_set),
+        ("stakingContractAddress", contracts.staking),
+        ("keygenHistoryContractAddress", contracts.keygen_history),
+    ] {
+        params.insert(name.to_string(), json!({ "0": format!("{:?}", address) }));
+    }
+    let endpoints: Map<String, Value> = validators
+        .iter()
+        .map(|v| {
+            let endpoint = format!(
+                "{}:{}",
+                v.enode.ip,
+                v.enode.port_base as usize + v.enode.idx
+            );
+            (format!("{:?}", v.enode.address), Value::String(endpoint))
+        })
+        .collect();
+    params.insert("validatorEndpoints".into(), Value::Object(endpoints));
+    spec
+}
+
+const START_SCRIPT: &str = r#"#!/bin/sh
+# Starts all nodes of the network. The client binary is taken from $OPENETHEREUM.
+set -e
+OPENETHEREUM="${OPENETHEREUM:-openethereum}"
+cd "$(dirname "$0")"
+for node in node*/; do
+    node="${node%/}"
+    (cd "$node" && exec "$OPENETHEREUM" --config node.toml > node.out 2>&1) &
+    echo $! > "$node/node.pid"
+done
+"#;
+
+const STOP_SCRIPT: &str = r#"#!/bin/sh
+# Stops all nodes of the network started by start.sh.
+cd "$(dirname "$0")"
+for pid_file in node*/node.pid; do
+    [ -f "$pid_file" ] || continue
+    kill "$(cat "$pid_file")" 2>/dev/null || true
+    rm -f "$pid_file"
+done
+"#;
+
+fn write_script(path: &Path, content: &str) {
+    fs::write(path, content).expect("Unable to write script");
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(path, fs::Permissions::from_mode(0o755))
+            .expect("Unable to make script executable");
+    }
+}
+
+/// Writes the configuration of a node to its own directory.
+fn write_node(
+    dir: &Path,
+    node_toml: toml::Value,
+    spec: &str,
+    data_dir: &str,
+    reserved_peers: &str,
+) {
+    fs::create_dir_all(dir).expect("Unable to create node directory");
+    fs::write(
+        dir.join("node.toml"),
+        toml::to_string(&node_toml).expect("TOML string generation should succeed"),
+    )
+    .expect("Unable to write config file");
+    fs::write(dir.join("spec.json"), spec).expect("Unable to write spec file");
+    fs::write(dir.join("reserved-peers"), reserved_peers)
+        .expect("Unable to write reserved_peers file");
+    fs::write(dir.join("password.txt"), "test").expect("Unable to write password.txt file");
+    fs::create_dir_all(dir.join("data").join("keys").join(data_dir))
+        .expect("Unable to create keys directory");
+}
+
+/// Returns the threshold of the initial key generation.
+/// The nodes build their key generation with the maximum number of faulty validators as threshold,
+/// `hbbft::util::max_faulty`, they could not process the key generation history of a network
+/// generated with another threshold.
+fn fault_threshold(config: &NetworkConfig) -> Result<usize, String> {
+    let threshold = config.validators.saturating_sub(1) / 3;
+    match config.fault_threshold {
+        Some(configured) if configured != threshold => Err(format!(
+            "fault_threshold {} is not supported, the nodes require a threshold of {} for {} validators",
+            configured, threshold, config.validators
+        )),
+        _ => Ok(threshold),
+    }
+}
+
+/// Generates the network described by the given file.
+pub fn generate_testnet(config_path: &Path) {
+    let config: NetworkConfig =
+        toml::from_str(&fs::read_to_string(config_path).expect("Unable to read network file"))
+            .expect("Network file must be valid");
+
+    let num_validators = config.validators;
+    let num_nodes = config.total_nodes.unwrap_or(num_validators);
+    assert!(num_validators > 0, "At least one validator is required");
+    assert!(
+        num_nodes >= num_validators,
+        "total_nodes must be greater than validators"
+    );
+    let threshold = fault_threshold(&config).unwrap_or_else(|e| panic!("{}", e));
+    let default_ports = PortBase::default();
+    let ports = PortBase {
+        p2p: config.port_base.unwrap_or(default_ports.p2p),
+        rpc: config.rpc_port_base.unwrap_or(default_ports.rpc),
+        ws: config.ws_port_base.unwrap_or(default_ports.ws),
+    };
+    let external_ip = config.external_ip.as_deref();
+    let output_dir = config
+        .output_dir
+        .clone()
+        .unwrap_or_else(|| PathBuf::from("testnet"));
+
+    println!(
+        "generating a network of {} nodes with {} initial validators and a fault threshold of {} in {}",
+        num_nodes,
+        num_validators,
+        threshold,
+        output_dir.display()
+    );
+
+    let enodes_map = generate_enodes(num_nodes, Vec::new(), external_ip, ports.p2p);
+    let validator_ids: Vec<Public> = enodes_map.keys().take(num_validators).cloned().collect();
+    let pub_keys = enodes_to_pub_keys(&enodes_map);
+    let pub_keys_for_key_gen = validator_ids
+        .iter()
+        .map(|id| (id.clone(), pub_keys[id].clone()))
+        .collect();
+    let mut rng = rand::thread_rng();
+    let (_sync_keygen, parts, acks) =
+        generate_keygens(Arc::new(pub_keys_for_key_gen), &mut rng, threshold);
+    let (parts, acks) = serialized_parts_and_acks(&parts, &acks, &validator_ids);
+
+    let validators: Vec<Validator> = validator_ids
+        .iter()
+        .map(|id| Validator {
+            enode: &enodes_map[id],
+            staking: Random.generate(),
+        })
+        .collect();
+
+    let template = fs::read_to_string(&config.spec_template).expect("Unable to read spec template");
+    let spec = assemble_spec(&config, &template, &validators, parts, acks);
+    let data_dir = spec["dataDir"]
+        .as_str()
+        .or_else(|| spec["name"].as_str())
+        .expect("Spec template must have a name")
+        .to_string();
+    let spec = serde_json::to_string_pretty(&spec).expect("Spec must serialize");
+
+    let mut reserved_peers = String::new();
+    for enode in enodes_map.values() {
+        writeln!(&mut reserved_peers, "{}", enode.to_string())
+            .expect("enode should be written to the reserved peers string");
+    }
+
+    fs::create_dir_all(&output_dir).expect("Unable to create output directory");
+    fs::write(output_dir.join("spec.json"), &spec).expect("Unable to write spec file");
+    fs::write(output_dir.join("reserved-peers"), &reserved_peers)
+        .expect("Unable to write reserved_peers file");
+
+    // Node 0 is the RPC node, it is not a validator.
+    let rpc_toml = to_toml(
+        0,
+        &ConfigType::Rpc,
+        external_ip,
+        &Address::default(),
+        &ports,
+    );
+    write_node(
+        &output_dir.join("node0"),
+        rpc_toml,
+        &spec,
+        &data_dir,
+        &reserved_peers,
+    );
+
+    for enode in enodes_map.values() {
+        let dir = output_dir.join(format!("node{}", enode.idx));
+        let mut node_toml = to_toml(
+            enode.idx,
+            &ConfigType::Docker,
+            external_ip,
+            &enode.address,
+            &ports,
+        );
+        node_toml["network"]
+            .as_table_mut()
+            .expect("network section must exist")
+            .insert(
+                "node_key".into(),
+                toml::Value::String(enode.secret.to_hex()),
+            );
+        write_node(&dir, node_toml, &spec, &data_dir, &reserved_peers);
+        write_json_for_secret(
+            enode.secret.clone(),
+            dir.join("data")
+                .join("keys")
+                .join(&data_dir)
+                .join("mining_key.json")
+                .to_string_lossy()
+                .into_owned(),
+        );
+    }
+    for validator in &validators {
+        let dir = output_dir.join(format!("node{}", validator.enode.idx));
+        write_json_for_secret(
+            validator.staking.secret().clone(),
+            dir.join("staking_key.json").to_string_lossy().into_owned(),
+        );
+    }
+
+    write_script(&output_dir.join("start.sh"), START_SCRIPT);
+    write_script(&output_dir.join("stop.sh"), STOP_SCRIPT);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_initialize_proxy() {
+        let implementation = Address::from_low_u64_be(1);
+        let admin = Address::from_low_u64_be(2);
+        let mut code = vec![0x60, 0x80, 0x60, 0x40];
+        code.extend(ethabi::encode(&[
+            Token::Address(implementation),
+            Token::Address(admin),
+            Token::Bytes(Vec::new()),
+        ]));
+
+        let data = vec![1, 2, 3];
+        let initialized: Vec<u8> =
+            initialize_proxy(&format!("0x{}", code.to_hex::<String>()), data.clone())
+                .trim_start_matches("0x")
+                .from_hex()
+                .unwrap();
+        assert_eq!(initialized[..4], code[..4]);
+        assert_eq!(
+            ethabi::decode(
+                &[
+                    ethabi::ParamType::Address,
+                    ethabi::ParamType::Address,
+                    ethabi::ParamType::Bytes,
+                ],
+                &initialized[4..],
+            )
+            .unwrap(),
+            vec![
+                Token::Address(implementation),
+                Token::Address(admin),
+                Token::Bytes(data),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_network_config_defaults() {
+        let config: NetworkConfig = toml::from_str(
+            r#"
+validators = 4
+spec_template = "spec_template.json"
+
+[staking]
+candidate_min_stake = 500
+"#,
+        )
+        .unwrap();
+        assert_eq!(config.total_nodes, None);
+        assert_eq!(config.staking.candidate_min_stake, 500);
+        assert_eq!(config.staking.delegator_min_stake, 100);
+        assert_eq!(
+            config.contracts.keygen_history,
+            Address::from_str("7000000000000000000000000000000000000001").unwrap()
+        );
+        assert_eq!(fault_threshold(&config), Ok(1));
+    }
+
+    #[test]
+    fn test_fault_threshold() {
+        let config = |threshold: usize| -> NetworkConfig {
+            toml::from_str(&format!(
+                "validators = 7\nfault_threshold = {}\nspec_template = \"spec_template.json\"",
+                threshold
+            ))
+            .unwrap()
+        };
+        assert_eq!(fault_threshold(&config(2)), Ok(2));
+        assert!(fault_threshold(&config(1)).is_err());
+        assert!(fault_threshold(&config(3)).is_err());
+    }
+}
@@ -1,27 +1,29 @@
 use super::block_reward_hbbft::BlockRewardContract;
 use block::ExecutedBlock;
-use client::traits::{EngineClient, ForceUpdateSealing};
-use crypto::publickey::Signature;
+use client::traits::{EngineClient, ForceUpdateSealing, ShutdownReason};
+use crypto::publickey::{public_to_address, Signature};
 use engines::{
-    default_system_or_code_call, signer::EngineSigner, Engine, EngineError, ForkChoice, Seal,
-    SealingState,
+    default_system_or_code_call, signer::EngineSigner, ConstructedVerifier, Engine, EngineError,
+    EpochChange, ForkChoice, Headers, NoOp, PendingTransitionStore, Seal, SealingState,
 };
 use error::{BlockError, Error};
-use ethereum_types::{H256, H512, U256};
-use ethjson::spec::HbbftParams;
+use ethereum_types::{Address, H256, H512, U256};
+use ethjson::spec::{HbbftParams, HbbftUnavailabilityAction};
 use hbbft::{NetworkInfo, Target};
 use io::{IoContext, IoHandler, IoService, TimerToken};
 use itertools::Itertools;
-use machine::EthereumMachine;
+use machine::{AuxiliaryData, EthereumMachine};
 use parking_lot::RwLock;
 use rlp;
 use serde::Deserialize;
-use serde_json;
+use stats::PrometheusRegistry;
 use std::{
     cmp::{max, min},
-    collections::BTreeMap,
+    collections::{BTreeMap, BTreeSet},
     convert::TryFrom,
+    fmt,
     ops::BitXor,
+    path::Path,
     sync::{atomic::AtomicBool, Arc, Weak},
     time::Duration,
 };
@@ -34,26 +36,47 @@ use types::{
 
 use super::{
     contracts::{
-        keygen_history::{all_parts_acks_available, initialize_synckeygen},
-        staking::start_time_of_next_phase_transition,
-        validator_set::{get_pending_validators, is_pending_validator, ValidatorType},
+        keygen_history::{
+            all_parts_acks_available, get_current_key_gen_round,
+            get_number_of_key_fragments_written, initialize_synckeygen,
+        },
+        staking::{get_posdao_epoch, get_posdao_epoch_start, start_time_of_next_phase_transition},
+        validator_set::{
+            get_pending_validators, get_validator_pubkeys, is_pending_validator, ValidatorType,
+        },
+        ContractAddresses,
     },
-    contribution::{unix_now_millis, unix_now_secs},
+    contribution::{
+        unix_now_millis, unix_now_secs, DEFAULT_MAX_CONTRIBUTION_BYTES,
+        DEFAULT_RANDOM_BYTES_PER_EPOCH, MIN_RANDOM_BYTES_PER_EPOCH,
+    },
+    epoch_proof::{EpochProof, HbbftEpochVerifier},
+    future_messages_cache::FutureMessagesLimits,
+    hbbft_journal::{HbbftJournal, JournalEntry},
+    hbbft_metrics::{HbbftMetrics, MessageKind},
     hbbft_state::{Batch, HbMessage, HbbftState, HoneyBadgerStep},
+    hbbft_status::{
+        BatchStatus, HoneyBadgerStatus, KeygenStatus, PosdaoEpochStatus, SealingStatus,
+        ValidatorPeerStatus, ValidatorScoreStatus, ValidatorStatus,
+    },
     keygen_transactions::KeygenTransactionSender,
+    malice_reports::{EvidenceMessage, FaultEvidence, MaliceReportSender},
+    message_codec::{self, EncodedMessage, MessageEncoding},
+    public_key_cache::public_master_key_at,
+    random_hbbft::RandomnessContract,
     sealing::{self, RlpSig, Sealing},
+    signer_rotation::{holds_key_share, SignerChange, SignerChangeContext},
+    validator_peers::{ValidatorPeers, DEFAULT_VALIDATOR_PORT},
     NodeId,
 };
 use engines::hbbft::{
     contracts::validator_set::{
         get_validator_available_since, send_tx_announce_availability, staking_by_mining_address,
     },
-    hbbft_message_memorium::HbbftMessageDispatcher,
+    hbbft_message_memorium::{is_late_message, HbbftMessageDispatcher, Misbehaviour},
 };
 use std::{ops::Deref, sync::atomic::Ordering};
 
-use std::process::Command;
-
 type TargetedMessage = hbbft::TargetedMessage<Message, NodeId>;
 
 /// A message sent between validators that is part of Honey Badger BFT or the block sealing process.
@@ -70,7 +93,16 @@ pub struct HoneyBadgerBFT {
     transition_service: IoService<()>,
     client: Arc<RwLock<Option<Weak<dyn EngineClient>>>>,
     signer: Arc<RwLock<Option<Box<dyn EngineSigner>>>>,
+    /// The engine signer taking over at the next POSDAO epoch, while the signer keeps the key share
+    /// of the current POSDAO epoch. It takes part in the key generation for the pending validator set.
+    next_signer: Arc<RwLock<Option<Box<dyn EngineSigner>>>>,
+    /// The POSDAO epoch in which the next signer was set.
+    next_signer_epoch: RwLock<u64>,
+    /// The hash and POSDAO epoch of the last block checked for an epoch end.
+    last_posdao_epoch: RwLock<Option<(H256, u64)>>,
     machine: EthereumMachine,
+    /// The addresses of the hbbft system contracts.
+    contracts: ContractAddresses,
     hbbft_state: RwLock<HbbftState>,
     hbbft_message_dispatcher: RwLock<HbbftMessageDispatcher>,
     sealing: RwLock<BTreeMap<BlockNumber, Sealing>>,
@@ -78,6 +110,25 @@ pub struct HoneyBadgerBFT {
     message_counter: RwLock<usize>,
     random_numbers: RwLock<BTreeMap<BlockNumber, U256>>,
     keygen_transaction_sender: RwLock<KeygenTransactionSender>,
+    malice_report_sender: RwLock<MaliceReportSender>,
+    /// The encoding to answer each connected peer in, see `record_message_encoding`.
+    peer_message_encodings: RwLock<BTreeMap<NodeId, MessageEncoding>>,
+    /// The last batch output by the honey badger consensus.
+    last_batch: RwLock<Option<BatchStatus>>,
+    /// Batches output by the honey badger consensus whose parent block is not available yet, by epoch.
+    queued_batches: RwLock<BTreeMap<u64, (Batch, NetworkInfo<NodeId>)>>,
+    /// The journal of the in-flight consensus state, restored after a restart.
+    journal: RwLock<Option<HbbftJournal>>,
+    /// The validators kept connected as reserved peers.
+    validator_peers: RwLock<ValidatorPeers>,
+    /// The nonce of the availability announcement sent since this node was last seen available.
+    availability_announcement: RwLock<Option<U256>>,
+    /// The validators of the pending validator set, by the block number they were read at.
+    pending_validators: RwLock<Option<(BlockNumber, BTreeSet<NodeId>)>>,
+    /// The UNIX time in seconds used instead of the system time, set by simulated networks.
+    #[cfg(test)]
+    simulated_time: RwLock<Option<u64>>,
+    metrics: HbbftMetrics,
 }
 
 struct TransitionHandler {
@@ -135,6 +186,13 @@ impl TransitionHandler {
 // Arbitrary identifier for the timer we register with the event handler.
 const ENGINE_TIMEOUT_TOKEN: TimerToken = 1;
 const ENGINE_SHUTDOWN_IF_UNAVAILABLE: TimerToken = 2;
+const ENGINE_KEYGEN_TOKEN: TimerToken = 3;
+const ENGINE_VALIDATOR_PEERS_TOKEN: TimerToken = 4;
+
+/// Interval of the timer sending the keygen transactions of this node.
+const KEYGEN_TIMER_INTERVAL: Duration = Duration::from_secs(2);
+/// Interval of the timer updating the validators reserved as peers.
+const VALIDATOR_PEERS_TIMER_INTERVAL: Duration = Duration::from_secs(10);
 
 impl IoHandler<()> for TransitionHandler {
     fn initialize(&self, io: &IoContext<()>) {
@@ -146,6 +204,14 @@ impl IoHandler<()> for TransitionHandler {
 
         io.register_timer(ENGINE_SHUTDOWN_IF_UNAVAILABLE, Duration::from_secs(1200))
             .unwrap_or_else(|e| warn!(target: "consensus", "HBBFT Shutdown Timer failed: {}.", e));
+
+        io.register_timer(ENGINE_KEYGEN_TOKEN, KEYGEN_TIMER_INTERVAL)
+            .unwrap_or_else(|e| warn!(target: "consensus", "HBBFT Keygen Timer failed: {}.", e));
+
+        io.register_timer(ENGINE_VALIDATOR_PEERS_TOKEN, VALIDATOR_PEERS_TIMER_INTERVAL)
+            .unwrap_or_else(
+                |e| warn!(target: "consensus", "HBBFT Validator Peers Timer failed: {}.", e),
+            );
     }
 
     fn timeout(&self, io: &IoContext<()>, timer: TimerToken) {
@@ -159,13 +225,20 @@ impl IoHandler<()> for TransitionHandler {
                 }
             }
 
+            // Blocks of other validators may have been imported in the meantime.
+            self.engine.process_queued_batches_if_ready();
+
             // Periodically allow messages received for future epochs to be processed.
             self.engine.replay_cached_messages();
 
+            self.engine.prune_journal();
+
             if let Err(e) = self.engine.do_availability_handling() {
                 error!(target: "engine", "Error during do_availability_handling: {}", e)
             }
 
+            self.engine.send_malice_reports();
+
             // The client may not be registered yet on startup, we set the default duration.
             let mut timer_duration = DEFAULT_DURATION;
             if let Some(ref weak) = *self.client.read() {
@@ -203,6 +276,12 @@ impl IoHandler<()> for TransitionHandler {
                 .unwrap_or_else(
                     |e| warn!(target: "consensus", "Failed to restart consensus step timer: {}.", e),
                 );
+        } else if timer == ENGINE_KEYGEN_TOKEN {
+            // Key generation is handled here instead of on block close,
+            // since creating the key generation state takes a lot of time.
+            self.engine.send_keygen_transactions();
+        } else if timer == ENGINE_VALIDATOR_PEERS_TOKEN {
+            self.engine.update_validator_peers();
         } else if timer == ENGINE_SHUTDOWN_IF_UNAVAILABLE {
             // we do not run this on the first occurence,
             // the first occurence could mean that the client is not fully set up
@@ -244,65 +323,11 @@ impl IoHandler<()> for TransitionHandler {
                         match self.engine.is_available() {
                             Ok(is_available) => {
                                 if !is_available {
-                                    warn!(target: "consensus", "Initiating Shutdown: Honey Badger Consensus detected that this Node has been flagged as unavailable, while it should be available.");
-
-                                    if let Some(ref weak) = *self.client.read() {
-                                        if let Some(c) = weak.upgrade() {
-                                            if let Some(id) = c.block_number(BlockId::Latest) {
-                                                warn!(target: "consensus", "BlockID: {id}");
-                                            }
-                                        }
-                                    }
-                                    //TODO: implement shutdown.
-                                    // panic!("Shutdown hard. Todo: implement Soft Shutdown.");
-                                    //if let c = self.engine.client.read() {
-                                    //}
-
-                                    let id: usize = std::process::id() as usize;
-
-                                    let thread_id = std::thread::current().id();
-
-                                    //let child_id = std::process::en;
-
-                                    info!(target: "engine", "Waiting for Signaling shutdown to process ID: {id} thread: {:?}", thread_id);
-
-                                    // Using libc resulted in errors.
-                                    // can't a process not send a signal to it's own ?!
-
-                                    // unsafe {
-                                    //    let signal_result = libc::signal(libc::SIGTERM, id);
-                                    //    info!(target: "engine", "Signal result: {signal_result}");
-                                    // }
-
-                                    let child = Command::new("/bin/kill")
-                                        .arg(id.to_string())
-                                        .spawn()
-                                        .expect("failed to execute child");
-
-                                    let kill_id = child.id();
-                                    info!(target: "engine", "Signaling shutdown SENT to process ID: {id} with process: {kill_id} ");
-
-                                    // if let Some(ref weak) = *self.client.read() {
-                                    //     if let Some(client) = weak.upgrade() {
-
-                                    // match client.as_full_client() {
-                                    //     Some(full_client) => {
-                                    //         //full_client.shutdown();
-                                    //     }
-                                    //     None => {
-
-                                    //     }
-                                    // }
-
-                                    // match client.as_full_client() {
-                                    //     Some(full_client) => full_client.is_major_syncing(),
-                                    //     // We only support full clients at this point.
-                                    //     None => true,
-                                    // }
-                                    //     }
-                                    // }
+                                    self.engine.handle_unavailability();
+                                } else {
+                                    // if the node is available, everythign is fine!
+                                    self.engine.availability_announcement.write().take();
                                 }
-                                // if the node is available, everythign is fine!
                             }
                             Err(error) => {
                                 warn!(target: "consensus", "Could not query Honey Badger check for unavailability shutdown. {:?}", error);
@@ -322,24 +347,85 @@ impl IoHandler<()> for TransitionHandler {
 impl HoneyBadgerBFT {
     /// Creates an instance of the Honey Badger BFT Engine.
     pub fn new(params: HbbftParams, machine: EthereumMachine) -> Result<Arc<Self>, Error> {
+        let random_bytes_per_epoch = params
+            .random_bytes_per_epoch
+            .unwrap_or(DEFAULT_RANDOM_BYTES_PER_EPOCH);
+        if random_bytes_per_epoch < MIN_RANDOM_BYTES_PER_EPOCH {
+            return Err(EngineError::Custom(format!(
+                "randomBytesPerEpoch must be at least {}, got {}.",
+                MIN_RANDOM_BYTES_PER_EPOCH, random_bytes_per_epoch
+            ))
+            .into());
+        }
+
+        let contracts = ContractAddresses::from_params(&params);
+
+        let messages_directory = params
+            .blocks_to_keep_directory
+            .clone()
+            .unwrap_or("data/messages/".to_string());
+        let journal = if params.is_unit_test.unwrap_or(false) {
+            None
+        } else {
+            Some(HbbftJournal::new(Path::new(&messages_directory)))
+        };
+
+        let default_limits = FutureMessagesLimits::default();
+        let future_messages_limits = FutureMessagesLimits {
+            max_future_epochs: params
+                .max_future_epochs
+                .unwrap_or(default_limits.max_future_epochs),
+            max_messages_per_sender: params
+                .max_future_messages_per_sender
+                .unwrap_or(default_limits.max_messages_per_sender),
+            max_bytes: params
+                .max_future_messages_bytes
+                .unwrap_or(default_limits.max_bytes),
+        };
+
+        let validator_peers = ValidatorPeers::new(
+            contracts.clone(),
+            params.validator_endpoints.clone().unwrap_or_default(),
+            params.validator_port.unwrap_or(DEFAULT_VALIDATOR_PORT),
+        );
+
         let engine = Arc::new(HoneyBadgerBFT {
             transition_service: IoService::<()>::start("Hbbft")?,
             client: Arc::new(RwLock::new(None)),
             signer: Arc::new(RwLock::new(None)),
+            next_signer: Arc::new(RwLock::new(None)),
+            next_signer_epoch: RwLock::new(0),
+            last_posdao_epoch: RwLock::new(None),
             machine,
-            hbbft_state: RwLock::new(HbbftState::new()),
+            hbbft_state: RwLock::new(HbbftState::new(
+                contracts.clone(),
+                random_bytes_per_epoch,
+                params
+                    .max_contribution_bytes
+                    .unwrap_or(DEFAULT_MAX_CONTRIBUTION_BYTES),
+                future_messages_limits,
+            )),
             hbbft_message_dispatcher: RwLock::new(HbbftMessageDispatcher::new(
                 params.blocks_to_keep_on_disk.unwrap_or(0),
-                params
-                    .blocks_to_keep_directory
-                    .clone()
-                    .unwrap_or("data/messages/".to_string()),
+                messages_directory,
             )),
             sealing: RwLock::new(BTreeMap::new()),
             params,
             message_counter: RwLock::new(0),
             random_numbers: RwLock::new(BTreeMap::new()),
-            keygen_transaction_sender: RwLock::new(KeygenTransactionSender::new()),
+            keygen_transaction_sender: RwLock::new(KeygenTransactionSender::new(contracts.clone())),
+            malice_report_sender: RwLock::new(MaliceReportSender::new(contracts.clone())),
+            peer_message_encodings: RwLock::new(BTreeMap::new()),
+            last_batch: RwLock::new(None),
+            queued_batches: RwLock::new(BTreeMap::new()),
+            journal: RwLock::new(journal),
+            validator_peers: RwLock::new(validator_peers),
+            availability_announcement: RwLock::new(None),
+            pending_validators: RwLock::new(None),
+            #[cfg(test)]
+            simulated_time: RwLock::new(None),
+            metrics: HbbftMetrics::new(),
+            contracts,
         });
 
         if !engine.params.is_unit_test.unwrap_or(false) {
@@ -355,24 +441,91 @@ impl HoneyBadgerBFT {
         Ok(engine)
     }
 
-    fn process_output(
+    /// Queues the batches output by the honey badger consensus and creates the blocks of all
+    /// batches whose parent block is available. A step may output several batches,
+    /// e.g. after cached messages of future epochs have been replayed.
+    pub(crate) fn process_output(
         &self,
         client: Arc<dyn EngineClient>,
         output: Vec<Batch>,
         network_info: &NetworkInfo<NodeId>,
     ) {
-        // TODO: Multiple outputs are possible,
-        //       process all outputs, respecting their epoch context.
-        if output.len() > 1 {
-            error!(target: "consensus", "UNHANDLED EPOCH OUTPUTS!");
-            panic!("UNHANDLED EPOCH OUTPUTS!");
+        if output.is_empty() {
+            return;
         }
-        let batch = match output.first() {
-            None => return,
-            Some(batch) => batch,
-        };
+        {
+            let mut queued_batches = self.queued_batches.write();
+            for batch in output {
+                trace!(target: "consensus", "Batch received for epoch {}.", batch.epoch);
+                self.metrics.on_batch_output(batch.epoch);
+                queued_batches.insert(batch.epoch, (batch, network_info.clone()));
+            }
+        }
+        self.process_queued_batches(client);
+    }
+
+    /// Creates the blocks of the queued batches in order, as long as the parent block
+    /// of the next batch is the latest block.
+    fn process_queued_batches(&self, client: Arc<dyn EngineClient>) {
+        loop {
+            let latest = match client.block_number(BlockId::Latest) {
+                Some(latest) => latest,
+                None => return,
+            };
+            let (batch, network_info) = {
+                let mut queued_batches = self.queued_batches.write();
+                // The blocks of older batches are already part of the chain.
+                *queued_batches = queued_batches.split_off(&(latest + 1));
+                match queued_batches.remove(&(latest + 1)) {
+                    Some(queued) => queued,
+                    None => return,
+                }
+            };
 
-        trace!(target: "consensus", "Batch received for epoch {}, creating new Block.", batch.epoch);
+            // The parent block may have switched to a new POSDAO epoch, in which case the batch
+            // was agreed on by a validator set that is not in charge of this block anymore.
+            let current_network_info = self.hbbft_state.write().network_info_for(
+                client.clone(),
+                &self.signer,
+                batch.epoch,
+            );
+            if current_network_info.map_or(true, |current| {
+                current.public_key_set() != network_info.public_key_set()
+            }) {
+                warn!(target: "consensus", "Discarding the batch of epoch {}, it was output by a replaced validator set.", batch.epoch);
+                continue;
+            }
+
+            self.process_batch(client.clone(), &batch, &network_info);
+        }
+    }
+
+    /// Creates the pending block of the batch and starts sealing it.
+    fn process_batch(
+        &self,
+        client: Arc<dyn EngineClient>,
+        batch: &Batch,
+        network_info: &NetworkInfo<NodeId>,
+    ) {
+        trace!(target: "consensus", "Creating new Block for the batch of epoch {}.", batch.epoch);
+
+        {
+            let dispatcher = self.hbbft_message_dispatcher.read();
+            for node_id in network_info.all_ids().filter(|node_id| {
+                *node_id != network_info.our_id() && !batch.contributions.contains_key(node_id)
+            }) {
+                dispatcher.report_missing_contribution(node_id, batch.epoch);
+            }
+        }
+
+        *self.last_batch.write() = Some(BatchStatus {
+            epoch: batch.epoch,
+            contributors: batch
+                .contributions
+                .keys()
+                .map(|node_id| public_to_address(&node_id.0))
+                .collect(),
+        });
 
         // Decode and de-duplicate transactions
         let batch_txns: Vec<_> = batch
@@ -405,15 +558,16 @@ impl HoneyBadgerBFT {
             }
         };
 
+        // The seed is the XOR of the random data of all contributions,
+        // it is unpredictable as long as a single contributor is honest.
         let random_number = batch
             .contributions
             .iter()
-            .fold(U256::zero(), |acc, (n, c)| {
-                if c.random_data.len() >= 32 {
-                    U256::from(&c.random_data[0..32]).bitxor(acc)
-                } else {
-                    // TODO: Report malicious behavior by node!
+            .fold(U256::zero(), |acc, (n, c)| match c.random_seed() {
+                Some(seed) => seed.bitxor(acc),
+                None => {
                     error!(target: "consensus", "Insufficient random data from node {}", n);
+                    self.report_misbehaviour(n, batch.epoch, Misbehaviour::InvalidMessage);
                     acc
                 }
             });
@@ -440,7 +594,7 @@ impl HoneyBadgerBFT {
                     return;
                 }
             };
-            self.process_seal_step(client, step, block_num, network_info);
+            self.process_seal_step(client, step, block_num, network_info, None);
         } else {
             error!(target: "consensus", "Could not create pending block for hbbft epoch {}: ", batch.epoch);
         }
@@ -452,23 +606,49 @@ impl HoneyBadgerBFT {
         message: HbMessage,
         sender_id: NodeId,
     ) -> Result<(), EngineError> {
-        let client = self.client_arc().ok_or(EngineError::RequiresClient)?;
         trace!(target: "consensus", "Received message of idx {}  {:?} from {}", msg_idx, message, sender_id);
+        self.metrics
+            .on_message_received(MessageKind::of_hb_message(&message));
 
         // store received messages here.
         self.hbbft_message_dispatcher
             .write()
-            .on_message_received(&message);
+            .on_message_received(&message, &sender_id);
+
+        // Messages of past epochs are dropped by honey badger. Messages of the epoch decided
+        // last are still on their way from honest validators, older ones were sent too late.
+        let current_epoch = self.hbbft_state.read().honey_badger_status();
+        if let Some((epoch, _)) = current_epoch {
+            if epoch > 0 && is_late_message(message.epoch(), epoch - 1) {
+                self.report_misbehaviour(&sender_id, message.epoch(), Misbehaviour::LateMessage);
+            }
+        }
 
+        self.handle_hb_message(message, sender_id)
+    }
+
+    /// Passes the honey badger message to the hbbft state, without recording it.
+    /// Used for received messages, and for messages replayed from the journal.
+    fn handle_hb_message(&self, message: HbMessage, sender_id: NodeId) -> Result<(), EngineError> {
+        let client = self.client_arc().ok_or(EngineError::RequiresClient)?;
+        let evidence = EvidenceMessage::HoneyBadger(message.clone());
+        // Messages of future epochs are only cached if they are from a known sender.
+        // The honey badger epoch is at least the block after the latest block.
+        let is_future_message = client
+            .block_number(BlockId::Latest)
+            .map_or(false, |latest_block| message.epoch() > latest_block + 1);
+        let is_known_sender =
+            is_future_message && self.is_current_or_pending_validator(&*client, &sender_id);
         let step = self.hbbft_state.write().process_message(
             client.clone(),
             &self.signer,
             sender_id,
             message,
+            is_known_sender,
         );
 
         if let Some((step, network_info)) = step {
-            self.process_step(client, step, &network_info);
+            self.process_step(client, step, &network_info, Some((&sender_id, evidence)));
             self.join_hbbft_epoch()?;
         }
         Ok(())
@@ -480,6 +660,9 @@ impl HoneyBadgerBFT {
         sender_id: NodeId,
         block_num: BlockNumber,
     ) -> Result<(), EngineError> {
+        self.metrics
+            .on_message_received(MessageKind::of_sealing_message(&message));
+
         // store received messages here.
         self.hbbft_message_dispatcher
             .write()
@@ -487,6 +670,29 @@ impl HoneyBadgerBFT {
 
         let client = self.client_arc().ok_or(EngineError::RequiresClient)?;
         trace!(target: "consensus", "Received sealing message for block {} from {} : {:?} ",block_num, sender_id, message);
+        if let Some(latest) = client.block_number(BlockId::Latest) {
+            if latest >= block_num {
+                // Shares of a block that was sealed with the shares of other validators are
+                // still on their way from honest validators, shares of older blocks are late.
+                if is_late_message(block_num, latest) {
+                    self.report_misbehaviour(&sender_id, block_num, Misbehaviour::LateMessage);
+                }
+                return Ok(()); // Message is obsolete.
+            }
+        }
+
+        self.handle_sealing_message(message, sender_id, block_num)
+    }
+
+    /// Passes the signature share to the sealing of the block, without recording it.
+    /// Used for received messages, and for messages replayed from the journal.
+    fn handle_sealing_message(
+        &self,
+        message: sealing::Message,
+        sender_id: NodeId,
+        block_num: BlockNumber,
+    ) -> Result<(), EngineError> {
+        let client = self.client_arc().ok_or(EngineError::RequiresClient)?;
         if let Some(latest) = client.block_number(BlockId::Latest) {
             if latest >= block_num {
                 return Ok(()); // Message is obsolete.
@@ -506,6 +712,7 @@ impl HoneyBadgerBFT {
         };
 
         trace!(target: "consensus", "Received signature share for block {} from {}", block_num, sender_id);
+        let evidence = EvidenceMessage::Sealing(message.clone());
         let step_result = self
             .sealing
             .write()
@@ -513,12 +720,74 @@ impl HoneyBadgerBFT {
             .or_insert_with(|| self.new_sealing(&network_info))
             .handle_message(&sender_id, message);
         match step_result {
-            Ok(step) => self.process_seal_step(client, step, block_num, &network_info),
-            Err(err) => error!(target: "consensus", "Error on ThresholdSign step: {:?}", err), // TODO: Errors
+            Ok(step) => {
+                self.process_seal_step(
+                    client.clone(),
+                    step,
+                    block_num,
+                    &network_info,
+                    Some((&sender_id, evidence)),
+                );
+                // The sealed block may be the parent of a queued batch.
+                self.process_queued_batches(client);
+            }
+            Err(err) => {
+                error!(target: "consensus", "Error on ThresholdSign step: {:?}", err);
+                self.report_misbehaviour(&sender_id, block_num, Misbehaviour::InvalidMessage);
+            }
         }
         Ok(())
     }
 
+    /// Returns whether consensus messages for the next block are sent in the binary encoding.
+    fn binary_messages_enabled(&self, client: &dyn EngineClient) -> bool {
+        match (
+            self.params.binary_consensus_messages_transition,
+            client.block_number(BlockId::Latest),
+        ) {
+            (Some(transition), Some(latest)) => latest + 1 >= transition,
+            _ => false,
+        }
+    }
+
+    /// Records the encoding of a consensus message received from the given peer.
+    ///
+    /// A peer that sent a binary encoded message decodes them, so it keeps receiving binary
+    /// encoded messages. JSON encoded messages are only recorded once the binary encoding is
+    /// enabled: before the transition every peer sends JSON, upgraded or not.
+    fn record_message_encoding(
+        &self,
+        binary_enabled: bool,
+        node_id: NodeId,
+        encoding: MessageEncoding,
+    ) {
+        let mut encodings = self.peer_message_encodings.write();
+        match encoding {
+            MessageEncoding::BincodeV1 => {
+                encodings.insert(node_id, MessageEncoding::BincodeV1);
+            }
+            MessageEncoding::Json if binary_enabled => {
+                encodings.entry(node_id).or_insert(MessageEncoding::Json);
+            }
+            MessageEncoding::Json => {}
+        }
+    }
+
+    /// Returns the encoding to use for consensus messages sent to the given peer.
+    ///
+    /// Binary encoding is used once the configured transition block is reached,
+    /// unless the peer only sent JSON encoded messages since then, which allows
+    /// networks to upgrade on a rolling basis.
+    fn message_encoding_for(&self, binary_enabled: bool, node_id: &NodeId) -> MessageEncoding {
+        if !binary_enabled {
+            return MessageEncoding::Json;
+        }
+        match self.peer_message_encodings.read().get(node_id) {
+            Some(MessageEncoding::Json) => MessageEncoding::Json,
+            _ => MessageEncoding::BincodeV1,
+        }
+    }
+
     fn dispatch_messages<I>(
         &self,
         client: &Arc<dyn EngineClient>,
@@ -527,15 +796,28 @@ impl HoneyBadgerBFT {
     ) where
         I: IntoIterator<Item = TargetedMessage>,
     {
+        let binary_enabled = self.binary_messages_enabled(client.as_ref());
+
         for m in messages {
-            let ser =
-                serde_json::to_vec(&m.message).expect("Serialization of consensus message failed");
+            let kind = match m.message {
+                Message::HoneyBadger(_, ref hb_msg) => MessageKind::of_hb_message(hb_msg),
+                Message::Sealing(_, ref seal_msg) => MessageKind::of_sealing_message(seal_msg),
+            };
+            let mut encoded = EncodedMessage::new(&m.message);
+            let mut send_to = |node_id: &NodeId| {
+                let encoding = self.message_encoding_for(binary_enabled, node_id);
+                let ser = encoded
+                    .get(encoding)
+                    .expect("Serialization of consensus message failed");
+                client.send_consensus_message(ser, Some(node_id.0));
+                self.metrics.on_message_sent(kind);
+            };
             match m.target {
                 Target::Nodes(set) => {
                     trace!(target: "consensus", "Dispatching message {:?} to {:?}", m.message, set);
                     for node_id in set.into_iter().filter(|p| p != net_info.our_id()) {
                         trace!(target: "consensus", "Sending message to {}", node_id.0);
-                        client.send_consensus_message(ser.clone(), Some(node_id.0));
+                        send_to(&node_id);
                     }
                 }
                 Target::AllExcept(set) => {
@@ -545,20 +827,26 @@ impl HoneyBadgerBFT {
                         .filter(|p| (p != &net_info.our_id() && !set.contains(p)))
                     {
                         trace!(target: "consensus", "Sending exclusive message to {}", node_id.0);
-                        client.send_consensus_message(ser.clone(), Some(node_id.0));
+                        send_to(node_id);
                     }
                 }
             }
         }
     }
 
+    /// Processes the step of the sealing of a block.
+    /// `cause` is the sender and the message the step is the result of, if any.
     fn process_seal_step(
         &self,
         client: Arc<dyn EngineClient>,
         step: sealing::Step,
         block_num: BlockNumber,
         network_info: &NetworkInfo<NodeId>,
+        cause: Option<(&NodeId, EvidenceMessage)>,
     ) {
+        for fault in &step.fault_log.0 {
+            self.report_fault(&fault.node_id, block_num, &fault.kind, cause.as_ref());
+        }
         let messages = step
             .messages
             .into_iter()
@@ -566,18 +854,41 @@ impl HoneyBadgerBFT {
         self.dispatch_messages(&client, messages, network_info);
         if let Some(sig) = step.output.into_iter().next() {
             trace!(target: "consensus", "Signature for block {} is ready", block_num);
+            self.metrics.on_seal_complete(block_num);
             let state = Sealing::Complete(sig);
             self.sealing.write().insert(block_num, state);
             client.update_sealing(ForceUpdateSealing::No);
         }
     }
 
+    /// Processes the step of honey badger.
+    /// `cause` is the sender and the message the step is the result of, if any.
     fn process_step(
         &self,
         client: Arc<dyn EngineClient>,
         step: HoneyBadgerStep,
         network_info: &NetworkInfo<NodeId>,
+        cause: Option<(&NodeId, EvidenceMessage)>,
     ) {
+        let epoch = self
+            .hbbft_state
+            .read()
+            .honey_badger_status()
+            .map(|(epoch, _)| epoch);
+        if let Some(epoch) = epoch {
+            self.metrics.on_epoch_started(epoch);
+        }
+        // Handling the message may have advanced honey badger to the next epoch already,
+        // faults are attributed to the epoch of the message that caused them.
+        let fault_epoch = match cause {
+            Some((_, EvidenceMessage::HoneyBadger(ref message))) => Some(message.epoch()),
+            _ => epoch,
+        };
+        if let Some(fault_epoch) = fault_epoch {
+            for fault in &step.fault_log.0 {
+                self.report_fault(&fault.node_id, fault_epoch, &fault.kind, cause.as_ref());
+            }
+        }
         let mut message_counter = self.message_counter.write();
         let messages = step.messages.into_iter().map(|msg| {
             *message_counter += 1;
@@ -590,6 +901,117 @@ impl HoneyBadgerBFT {
         self.process_output(client, step.output, network_info);
     }
 
+    /// Seeds the sources of randomness of the engine, making our contributions
+    /// and key generation transactions reproducible.
+    #[cfg(test)]
+    pub(crate) fn seed_rng(&self, seed: [u8; 32]) {
+        use rand_065::{rngs::StdRng, Rng, SeedableRng};
+
+        let mut rng = StdRng::from_seed(seed);
+        self.hbbft_state.write().seed_rng(rng.gen());
+        self.keygen_transaction_sender.write().seed_rng(rng.gen());
+    }
+
+    /// Makes the engine use the given UNIX time in seconds instead of the system time.
+    #[cfg(test)]
+    pub(crate) fn set_simulated_time(&self, secs: u64) {
+        *self.simulated_time.write() = Some(secs);
+    }
+
+    /// Returns the current UNIX time in seconds.
+    #[cfg(not(test))]
+    fn now_secs(&self) -> u64 {
+        unix_now_secs()
+    }
+
+    /// Returns the simulated UNIX time in seconds, if set, the system time otherwise.
+    #[cfg(test)]
+    fn now_secs(&self) -> u64 {
+        self.simulated_time.read().unwrap_or_else(unix_now_secs)
+    }
+
+    /// Returns the network info of the validator set in charge of the given block.
+    #[cfg(test)]
+    pub(crate) fn network_info_for(&self, block_nr: BlockNumber) -> Option<NetworkInfo<NodeId>> {
+        let client = self.client_arc()?;
+        self.hbbft_state
+            .write()
+            .network_info_for(client, &self.signer, block_nr)
+    }
+
+    /// Creates the blocks of queued batches whose parent block has been imported.
+    fn process_queued_batches_if_ready(&self) {
+        if self.queued_batches.read().is_empty() {
+            return;
+        }
+        if let Some(client) = self.client_arc() {
+            self.process_queued_batches(client);
+        }
+    }
+
+    /// Records a misbehaviour in the message memorium if the node is a current validator.
+    /// Other peers have no score, their messages are not handled by the consensus.
+    fn report_misbehaviour(&self, node_id: &NodeId, epoch: u64, misbehaviour: Misbehaviour) {
+        if !self.hbbft_state.read().is_current_validator(node_id) {
+            debug!(target: "consensus", "Ignoring {:?} of non-validator {} in epoch {}", misbehaviour, node_id, epoch);
+            return;
+        }
+        self.hbbft_message_dispatcher
+            .read()
+            .report_misbehaviour(node_id, epoch, misbehaviour);
+    }
+
+    /// Records a fault reported by the consensus algorithm in the message memorium
+    /// and queues it for reporting to the validator set contract.
+    /// If the faulty node is the sender of `cause`, its message is reported as evidence.
+    fn report_fault(
+        &self,
+        node_id: &NodeId,
+        epoch: u64,
+        kind: &dyn fmt::Debug,
+        cause: Option<&(&NodeId, EvidenceMessage)>,
+    ) {
+        warn!(target: "consensus", "Fault of validator {} in epoch {}: {:?}", node_id, epoch, kind);
+        self.report_misbehaviour(node_id, epoch, Misbehaviour::Fault);
+        let client = match self.client_arc() {
+            Some(client) => client,
+            None => return,
+        };
+        let latest = match client.block_number(BlockId::Latest) {
+            Some(latest) => latest,
+            None => return,
+        };
+        let max_block = self.hbbft_state.read().max_message_block(latest);
+        let evidence = FaultEvidence {
+            block: epoch,
+            kind: format!("{:?}", kind),
+            message: cause
+                .filter(|(sender, _)| *sender == node_id)
+                .map(|(_, message)| message.clone()),
+        };
+        self.malice_report_sender.write().queue_report(
+            public_to_address(&node_id.0),
+            evidence,
+            latest,
+            max_block,
+        );
+    }
+
+    /// Sends the queued fault reports to the validator set contract.
+    fn send_malice_reports(&self) {
+        let client = match self.client_arc() {
+            Some(client) => client,
+            None => return,
+        };
+        if let Err(e) = self
+            .malice_report_sender
+            .write()
+            .send_reports(&*client, &self.signer)
+        {
+            warn!(target: "consensus", "Error sending malice reports: {:?}", e);
+        }
+    }
+
     /// Conditionally joins the current hbbft epoch if the number of received
     /// contributions exceeds the maximum number of tolerated faulty nodes.
     fn join_hbbft_epoch(&self) -> Result<(), EngineError> {
@@ -601,9 +1023,14 @@ impl HoneyBadgerBFT {
         let step = self
             .hbbft_state
             .write()
-            .contribute_if_contribution_threshold_reached(client.clone(), &self.signer);
-        if let Some((step, network_info)) = step {
-            self.process_step(client, step, &network_info)
+            .contribute_if_contribution_threshold_reached(
+                client.clone(),
+                &self.signer,
+                self.now_secs(),
+            );
+        if let Some((step, network_info, proposal)) = step {
+            self.journal(|| JournalEntry::Contribution(proposal));
+            self.process_step(client, step, &network_info, None)
         }
         Ok(())
     }
@@ -612,12 +1039,135 @@ impl HoneyBadgerBFT {
         if self.is_syncing(&client) {
             return;
         }
-        let step = self
-            .hbbft_state
-            .write()
-            .try_send_contribution(client.clone(), &self.signer);
-        if let Some((step, network_info)) = step {
-            self.process_step(client, step, &network_info)
+        let step = self.hbbft_state.write().try_send_contribution(
+            client.clone(),
+            &self.signer,
+            self.now_secs(),
+        );
+        if let Some((step, network_info, proposal)) = step {
+            self.journal(|| JournalEntry::Contribution(proposal));
+            self.process_step(client, step, &network_info, None)
+        }
+    }
+
+    /// Appends the entry to the journal of the in-flight consensus state, if journaling is enabled.
+    fn journal<F: FnOnce() -> JournalEntry>(&self, entry: F) {
+        if let Some(journal) = self.journal.write().as_mut() {
+            if let Err(e) = journal.append(&entry()) {
+                warn!(target: "consensus", "Could not write to the hbbft journal: {:?}", e);
+            }
+        }
+    }
+
+    /// Journals a received message, if it belongs to the in-flight consensus state:
+    /// it is from a current or pending validator, and for a block that is not imported yet
+    /// and at most as far ahead as the messages cached for future epochs.
+    fn journal_received_message(&self, message: &Message, sender_id: NodeId) {
+        if self.journal.read().is_none() {
+            return;
+        }
+        let client = match self.client_arc() {
+            Some(client) => client,
+            None => return,
+        };
+        let block = match message {
+            Message::HoneyBadger(_, hb_msg) => hb_msg.epoch(),
+            Message::Sealing(block_num, _) => *block_num,
+        };
+        let latest_block = match client.block_number(BlockId::Latest) {
+            Some(block) => block,
+            None => return,
+        };
+        let max_block = self.hbbft_state.read().max_message_block(latest_block);
+        if block <= latest_block
+            || block > max_block
+            || !self.is_current_or_pending_validator(&*client, &sender_id)
+        {
+            return;
+        }
+        self.journal(|| match message {
+            Message::HoneyBadger(_, hb_msg) => JournalEntry::HoneyBadgerMessage {
+                sender: sender_id,
+                message: hb_msg.clone(),
+            },
+            Message::Sealing(block_num, seal_msg) => JournalEntry::SealingMessage {
+                block: *block_num,
+                sender: sender_id,
+                message: seal_msg.clone(),
+            },
+        });
+    }
+
+    /// Reads the in-flight consensus state journaled before the node was restarted.
+    fn restore_journal(&self, client: &Arc<dyn EngineClient>) {
+        let latest = match client.block_number(BlockId::Latest) {
+            Some(latest) => latest,
+            None => return,
+        };
+        let max_block = self.hbbft_state.read().max_message_block(latest);
+        if let Some(journal) = self.journal.write().as_mut() {
+            if let Err(e) = journal.restore(latest, max_block) {
+                warn!(target: "consensus", "Could not restore the hbbft journal: {:?}", e);
+            }
+        }
+    }
+
+    /// Replays the restored consensus state, as soon as this node takes part in the consensus.
+    /// Our journaled contribution is proposed again, so the restart does not count as missed participation.
+    fn replay_journal(&self, client: Arc<dyn EngineClient>) {
+        if self.hbbft_state.read().honey_badger_status().is_none() {
+            return;
+        }
+        let entries = match self.journal.write().as_mut() {
+            Some(journal) => journal.take_restored(),
+            None => return,
+        };
+        if !entries.is_empty() {
+            info!(target: "consensus", "Replaying {} entries of the hbbft journal.", entries.len());
+        }
+        for entry in entries {
+            let result = match entry {
+                JournalEntry::Contribution(proposal) => {
+                    let step = self.hbbft_state.write().restore_proposal(
+                        client.clone(),
+                        &self.signer,
+                        &proposal,
+                    );
+                    if let Some((step, network_info)) = step {
+                        self.process_step(client.clone(), step, &network_info, None);
+                    }
+                    Ok(())
+                }
+                // The messages were counted and recorded when they were received before the restart.
+                JournalEntry::HoneyBadgerMessage { sender, message } => {
+                    self.handle_hb_message(message, sender)
+                }
+                JournalEntry::SealingMessage {
+                    block,
+                    sender,
+                    message,
+                } => self.handle_sealing_message(message, sender, block),
+            };
+            if let Err(e) = result {
+                warn!(target: "consensus", "Could not replay an entry of the hbbft journal: {:?}", e);
+            }
+        }
+    }
+
+    /// Removes the entries of imported blocks, and of blocks too far ahead, from the journal.
+    fn prune_journal(&self) {
+        let latest = match self
+            .client_arc()
+            .and_then(|client| client.block_number(BlockId::Latest))
+        {
+            Some(latest) => latest,
+            None => return,
+        };
+        let max_block = self.hbbft_state.read().max_message_block(latest);
+        if let Some(journal) = self.journal.write().as_mut() {
+            if let Err(e) = journal.prune(latest, max_block) {
+                warn!(target: "consensus", "Could not prune the hbbft journal: {:?}", e);
+            }
         }
     }
 
@@ -627,7 +1177,7 @@ impl HoneyBadgerBFT {
     ) -> bool {
         if let Some(block_header) = client.block_header(BlockId::Latest) {
             let target_min_timestamp = block_header.timestamp() + self.params.minimum_block_time;
-            let now = unix_now_secs();
+            let now = self.now_secs();
             let queue_length = client.queued_transactions().len();
             (self.params.minimum_block_time == 0 || target_min_timestamp <= now)
                 && queue_length >= self.params.transaction_queue_size_trigger
@@ -649,13 +1199,14 @@ impl HoneyBadgerBFT {
             None => return,
             Some(client) => {
                 // Get the next phase start time
-                let genesis_transition_time = match start_time_of_next_phase_transition(&*client) {
-                    Ok(time) => time,
-                    Err(_) => return,
-                };
+                let genesis_transition_time =
+                    match start_time_of_next_phase_transition(&*client, &self.contracts) {
+                        Ok(time) => time,
+                        Err(_) => return,
+                    };
 
                 // If current time larger than phase start time, start a new block.
-                if genesis_transition_time.as_u64() < unix_now_secs() {
+                if genesis_transition_time.as_u64() < self.now_secs() {
                     self.start_hbbft_epoch(client);
                 }
             }
@@ -675,7 +1226,7 @@ impl HoneyBadgerBFT {
                     Ok(step) => {
                         trace!(target: "engine", "Processing cached message step");
                         processed_step = true;
-                        self.process_step(client.clone(), step, &network_info)
+                        self.process_step(client.clone(), step, &network_info, None)
                     }
                     Err(e) => error!(target: "engine", "Error handling replayed message: {}", e),
                 }
@@ -710,7 +1261,7 @@ impl HoneyBadgerBFT {
                     if !self.is_syncing(&client) {
                         let engine_client = client.deref();
 
-                        match staking_by_mining_address(engine_client, &address) {
+                        match staking_by_mining_address(engine_client, &self.contracts, &address) {
                             Ok(staking_address) => {
                                 if staking_address.is_zero() {
                                     //TODO: here some fine handling can improve performance.
@@ -727,7 +1278,11 @@ impl HoneyBadgerBFT {
                             }
                         }
 
-                        match get_validator_available_since(engine_client, &address) {
+                        match get_validator_available_since(
+                            engine_client,
+                            &self.contracts,
+                            &address,
+                        ) {
                             Ok(s) => {
                                 if s.is_zero() {
                                     //let c : &dyn BlockChainClient = client.into();
@@ -735,8 +1290,15 @@ impl HoneyBadgerBFT {
                                         Some(c) => {
                                             //debug!(target: "engine", "sending announce availability transaction");
                                             info!(target: "engine", "sending announce availability transaction");
-                                            match send_tx_announce_availability(c, &address) {
-                                                Ok(()) => {}
+                                            match send_tx_announce_availability(
+                                                c,
+                                                &self.contracts,
+                                                &address,
+                                            ) {
+                                                Ok(nonce) => {
+                                                    *self.availability_announcement.write() =
+                                                        Some(nonce);
+                                                }
                                                 Err(call_error) => {
                                                     //error!(target: "engine", "CallError during announce availability. {:?}", call_error);
                                                     return Err(format!("CallError during announce availability. {:?}", call_error));
@@ -780,7 +1342,7 @@ impl HoneyBadgerBFT {
             None => false,
             Some(client) => {
                 // If we are not in key generation phase, return false.
-                let num_validators = match get_pending_validators(&*client) {
+                let num_validators = match get_pending_validators(&*client, &self.contracts) {
                     Err(_) => return false,
                     Ok(validators) => {
                         // If the validator set is empty then we are not in the key generation phase.
@@ -794,13 +1356,17 @@ impl HoneyBadgerBFT {
                 // Check if a new key is ready to be generated, return true to switch to the new epoch in that case.
                 // The execution needs to be *identical* on all nodes, which means it should *not* use the local signer
                 // when attempting to initialize the synckeygen.
-                if let Ok(all_available) =
-                    all_parts_acks_available(&*client, block_timestamp, num_validators)
-                {
+                if let Ok(all_available) = all_parts_acks_available(
+                    &*client,
+                    &self.contracts,
+                    block_timestamp,
+                    num_validators,
+                ) {
                     if all_available {
                         let null_signer = Arc::new(RwLock::new(None));
                         if let Ok(synckeygen) = initialize_synckeygen(
                             &*client,
+                            &self.contracts,
                             &null_signer,
                             BlockId::Latest,
                             ValidatorType::Pending,
@@ -812,34 +1378,194 @@ impl HoneyBadgerBFT {
                     }
                 }
 
-                // Otherwise check if we are in the pending validator set and send Parts and Acks transactions.
-                // @todo send_keygen_transactions initializes another synckeygen structure, a potentially
-                //       time consuming process. Move sending of keygen transactions into a separate function
-                //       and call it periodically using timer events instead of on close block.
-                if let Some(signer) = self.signer.read().as_ref() {
-                    if let Ok(is_pending) = is_pending_validator(&*client, &signer.address()) {
-                        trace!(target: "engine", "is_pending_validator: {}", is_pending);
-                        if is_pending {
-                            let _err = self
-                                .keygen_transaction_sender
-                                .write()
-                                .send_keygen_transactions(&*client, &self.signer);
-                            match _err {
-                                Ok(()) => {}
-                                Err(e) => {
-                                    error!(target: "engine", "Error sending keygen transactions {:?}", e);
-                                }
-                            }
-                        }
-                    }
-                }
+                // Parts and Acks of this node are sent by the keygen timer of the `TransitionHandler`.
                 false
             }
         }
     }
 
+    /// Sends the Part and Acks transactions of this node if it is a pending validator.
+    pub(crate) fn send_keygen_transactions(&self) {
+        let client = match self.client_arc() {
+            Some(client) => client,
+            None => return,
+        };
+        if let Err(e) = self
+            .keygen_transaction_sender
+            .write()
+            .send_keygen_transactions(&*client, self.keygen_signer())
+        {
+            error!(target: "engine", "Error sending keygen transactions {:?}", e);
+        }
+    }
+
+    /// Returns true if the node is a validator of the current or the pending validator set.
+    /// The pending validators are read at most once per block, and without holding the lock
+    /// of the hbbft state, since reading them requires contract calls.
+    fn is_current_or_pending_validator(&self, client: &dyn EngineClient, node_id: &NodeId) -> bool {
+        if self.hbbft_state.read().is_current_validator(node_id) {
+            return true;
+        }
+        let latest_block = match client.block_number(BlockId::Latest) {
+            Some(block) => block,
+            None => return false,
+        };
+        if let Some((block, ref validators)) = *self.pending_validators.read() {
+            if block == latest_block {
+                return validators.contains(node_id);
+            }
+        }
+        match get_validator_pubkeys(
+            client,
+            &self.contracts,
+            BlockId::Number(latest_block),
+            ValidatorType::Pending,
+        ) {
+            Ok(validators) => {
+                let validators: BTreeSet<_> = validators.values().map(|p| NodeId(*p)).collect();
+                let is_pending_validator = validators.contains(node_id);
+                *self.pending_validators.write() = Some((latest_block, validators));
+                is_pending_validator
+            }
+            Err(e) => {
+                warn!(target: "consensus", "Could not read the pending validators: {:?}", e);
+                false
+            }
+        }
+    }
+
+    /// Reserves the validators of the current and the pending validator set as peers,
+    /// and reports the validators that are not connected.
+    fn update_validator_peers(&self) {
+        let client = match self.client_arc() {
+            Some(client) => client,
+            None => return,
+        };
+        let our_address = self.signer.read().as_ref().map(|signer| signer.address());
+        let mut validator_peers = self.validator_peers.write();
+        if let Err(e) = validator_peers.update(&*client, our_address) {
+            warn!(target: "engine", "Could not update the validators reserved as peers: {:?}", e);
+        }
+        validator_peers.report_unreachable();
+    }
+
+    /// The signer taking part in the key generation for the pending validator set.
+    fn keygen_signer(&self) -> &Arc<RwLock<Option<Box<dyn EngineSigner>>>> {
+        if self.next_signer.read().is_some() {
+            &self.next_signer
+        } else {
+            &self.signer
+        }
+    }
+
+    /// Decides how the engine signer is changed to the given address.
+    fn plan_signer_change(&self, new_address: &Address) -> Result<SignerChange, String> {
+        let current_address = match self.signer.read().as_ref() {
+            Some(signer) if signer.address() != *new_address => signer.address(),
+            _ => return Ok(SignerChange::Immediate),
+        };
+        let client = match self.client_arc() {
+            Some(client) => client,
+            None => return Ok(SignerChange::Immediate),
+        };
+        let holds_key_share = self.hbbft_state.read().is_validator();
+        SignerChangeContext::read(
+            &*client,
+            &self.contracts,
+            current_address,
+            *new_address,
+            holds_key_share,
+        )
+        .map_err(|e| format!("Error reading the validator set: {:?}", e))?
+        .plan()
+    }
+
+    /// Lets the next signer take over once the POSDAO epoch changed,
+    /// and creates the honey badger instance with its key share.
+    /// The rotation is dropped if the next signer is not a validator of the new POSDAO epoch
+    /// or holds no key share of it, and the current signer is kept.
+    fn promote_next_signer(&self, client: &Arc<dyn EngineClient>) {
+        if self.next_signer.read().is_none() {
+            return;
+        }
+        let posdao_epoch = match get_posdao_epoch(&**client, &self.contracts, BlockId::Latest) {
+            Ok(posdao_epoch) => posdao_epoch.low_u64(),
+            Err(_) => return,
+        };
+        if posdao_epoch <= *self.next_signer_epoch.read() {
+            return;
+        }
+        let holds_key_share = match holds_key_share(
+            &**client,
+            &self.contracts,
+            &self.next_signer,
+            BlockId::Latest,
+        ) {
+            Ok(holds_key_share) => holds_key_share,
+            Err(e) => {
+                error!(target: "engine", "Error checking the key share of the next engine signer: {:?}", e);
+                return;
+            }
+        };
+        let next_signer = self.next_signer.write().take();
+        if let Some(signer) = next_signer {
+            if !holds_key_share {
+                error!(target: "engine", "The engine signer {} holds no key share of POSDAO epoch {}, keeping the current signer.", signer.address(), posdao_epoch);
+                return;
+            }
+            info!(target: "engine", "POSDAO epoch {} started, the engine signer {} takes over.", posdao_epoch, signer.address());
+            *self.signer.write() = Some(signer);
+            if let None = self.hbbft_state.write().update_honeybadger(
+                client.clone(),
+                &self.signer,
+                BlockId::Latest,
+                true,
+            ) {
+                error!(target: "consensus", "Fatal: Updating Honey Badger instance failed!");
+            }
+        }
+    }
+
+    /// Checks the proof of the first recorded epoch transition, which can not be proven by the
+    /// key of an earlier epoch, against the key of the genesis validator set.
+    fn verify_initial_epoch_proof(&self, header: &Header, proof: &EpochProof) -> Result<(), Error> {
+        if header.number() != 1 {
+            return Err(EngineError::InsufficientProof(format!(
+                "The epoch transition at block {} lacks the sealed transition header.",
+                header.number()
+            ))
+            .into());
+        }
+        let client = self.client_arc().ok_or(EngineError::RequiresClient)?;
+        let genesis = BlockId::Number(0);
+        let genesis_posdao_epoch = get_posdao_epoch(&*client, &self.contracts, genesis)
+            .map_err(|e| {
+                EngineError::InsufficientProof(format!(
+                    "Could not read the POSDAO epoch of the genesis block: {:?}",
+                    e
+                ))
+            })?
+            .low_u64();
+        let genesis_key =
+            public_master_key_at(&*client, &self.contracts, genesis).ok_or_else(|| {
+                EngineError::InsufficientProof(
+                    "Could not reconstruct the public master key of the genesis validator set."
+                        .into(),
+                )
+            })?;
+        if proof.posdao_epoch != genesis_posdao_epoch || proof.public_master_key != genesis_key {
+            return Err(EngineError::InsufficientProof(
+                "The public master key of the first epoch transition does not match the genesis validator set."
+                    .into(),
+            )
+            .into());
+        }
+        Ok(())
+    }
+
     fn check_for_epoch_change(&self) -> Option<()> {
         let client = self.client_arc()?;
+        self.promote_next_signer(&client);
         if let None = self.hbbft_state.write().update_honeybadger(
             client,
             &self.signer,
@@ -874,6 +1600,7 @@ impl HoneyBadgerBFT {
                         }
                         match super::contracts::validator_set::get_validator_available_since(
                             engine_client,
+                            &self.contracts,
                             &mining_address,
                         ) {
                             Ok(available_since) => {
@@ -920,6 +1647,7 @@ impl HoneyBadgerBFT {
 
                         match super::contracts::validator_set::staking_by_mining_address(
                             engine_client,
+                            &self.contracts,
                             &mining_address,
                         ) {
                             Ok(staking_address) => {
@@ -929,6 +1657,7 @@ impl HoneyBadgerBFT {
                                 }
                                 match super::contracts::staking::stake_amount(
                                     engine_client,
+                                    &self.contracts,
                                     &staking_address,
                                     &staking_address,
                                 ) {
@@ -938,6 +1667,7 @@ impl HoneyBadgerBFT {
                                         // we need to check if the pool stake amount is >= minimum stake
                                         match super::contracts::staking::candidate_min_stake(
                                             engine_client,
+                                            &self.contracts,
                                         ) {
                                             Ok(min_stake) => {
                                                 debug!(target: "consensus", "min_stake: {}", min_stake);
@@ -969,6 +1699,209 @@ impl HoneyBadgerBFT {
         }
         return Ok(false);
     }
+
+    /// Reacts to this staked validator being flagged as unavailable,
+    /// as configured by the `unavailability_action` parameter.
+    fn handle_unavailability(&self) {
+        let client = match self.client_arc() {
+            Some(client) => client,
+            None => return,
+        };
+        let block_number = client.block_number(BlockId::Latest).unwrap_or_default();
+
+        match self
+            .params
+            .unavailability_action
+            .unwrap_or(HbbftUnavailabilityAction::Shutdown)
+        {
+            HbbftUnavailabilityAction::Warn => {
+                warn!(target: "consensus", "Honey Badger Consensus detected that this Node has been flagged as unavailable at block {}, while it should be available.", block_number);
+            }
+            HbbftUnavailabilityAction::Reannounce => {
+                warn!(target: "consensus", "Announcing availability again: Honey Badger Consensus detected that this Node has been flagged as unavailable at block {}.", block_number);
+                if let Err(e) = self.reannounce_availability(&*client) {
+                    error!(target: "consensus", "Error announcing availability: {}", e);
+                }
+            }
+            HbbftUnavailabilityAction::Shutdown => {
+                warn!(target: "consensus", "Initiating Shutdown: Honey Badger Consensus detected that this Node has been flagged as unavailable at block {}, while it should be available.", block_number);
+                client.request_shutdown(ShutdownReason::ValidatorUnavailable);
+            }
+        }
+    }
+
+    /// Announces the availability of this node, unless it already did since it was last seen
+    /// available: while the announcement is pending, or if it was mined without effect.
+    fn reannounce_availability(&self, client: &dyn EngineClient) -> Result<(), String> {
+        let address = match self.signer.read().as_ref() {
+            Some(signer) => signer.address(),
+            None => return Err("No signer available".into()),
+        };
+        let full_client = client
+            .as_full_client()
+            .ok_or("Unable to retrieve client.as_full_client()")?;
+
+        let mut announcement = self.availability_announcement.write();
+        if let Some(nonce) = *announcement {
+            let mined_nonce = full_client
+                .nonce(&address, BlockId::Latest)
+                .unwrap_or_default();
+            if mined_nonce > nonce {
+                warn!(target: "consensus", "The availability announcement with nonce {} was mined, but this node is still flagged as unavailable.", nonce);
+                return Ok(());
+            }
+            // An announcement dropped from the transaction queue is sent again.
+            if full_client.next_nonce(&address) > nonce {
+                debug!(target: "consensus", "The availability announcement with nonce {} is still pending.", nonce);
+                return Ok(());
+            }
+        }
+        let nonce = send_tx_announce_availability(full_client, &self.contracts, &address)
+            .map_err(|e| format!("CallError during announce availability. {:?}", e))?;
+        *announcement = Some(nonce);
+        Ok(())
+    }
+
+    /// Returns the POSDAO epoch of the latest block.
+    pub fn posdao_epoch_status(&self) -> Result<PosdaoEpochStatus, String> {
+        let client = self.client_arc().ok_or("Client not available")?;
+        let epoch = get_posdao_epoch(&*client, &self.contracts, BlockId::Latest)
+            .map_err(|e| format!("Error reading POSDAO epoch: {:?}", e))?;
+        let start_block = get_posdao_epoch_start(&*client, &self.contracts, BlockId::Latest)
+            .map_err(|e| format!("Error reading POSDAO epoch start block: {:?}", e))?;
+        Ok(PosdaoEpochStatus {
+            epoch: epoch.low_u64(),
+            start_block: start_block.low_u64(),
+        })
+    }
+
+    /// Returns the role of the engine signer in the current and pending validator set.
+    pub fn validator_status(&self) -> Result<ValidatorStatus, String> {
+        let client = self.client_arc().ok_or("Client not available")?;
+        let mining_address = match self.signer.read().as_ref() {
+            Some(signer) => signer.address(),
+            None => return Ok(ValidatorStatus::default()),
+        };
+        let staking_address = staking_by_mining_address(&*client, &self.contracts, &mining_address)
+            .map_err(|e| format!("Error reading staking address: {:?}", e))?;
+        let is_pending_validator = is_pending_validator(&*client, &self.contracts, &mining_address)
+            .map_err(|e| format!("Error reading pending validators: {:?}", e))?;
+        Ok(ValidatorStatus {
+            mining_address: Some(mining_address),
+            next_mining_address: self
+                .next_signer
+                .read()
+                .as_ref()
+                .map(|signer| signer.address()),
+            staking_address: Some(staking_address).filter(|address| !address.is_zero()),
+            is_current_validator: self.hbbft_state.read().is_validator(),
+            is_pending_validator,
+            is_staked: self.is_staked().map_err(|e| e.to_string())?,
+            is_available: self.is_available().map_err(|e| e.to_string())?,
+        })
+    }
+
+    /// Returns the progress of the key generation for the pending validator set.
+    pub fn keygen_status(&self) -> Result<KeygenStatus, String> {
+        let client = self.client_arc().ok_or("Client not available")?;
+        let round = get_current_key_gen_round(&*client, &self.contracts)
+            .map_err(|e| format!("Error reading key generation round: {:?}", e))?;
+        let num_pending_validators = get_pending_validators(&*client, &self.contracts)
+            .map_err(|e| format!("Error reading pending validators: {:?}", e))?
+            .len();
+        let (num_parts, num_acks) =
+            get_number_of_key_fragments_written(&*client, &self.contracts, BlockId::Latest)
+                .map_err(|e| format!("Error reading number of Parts and Acks: {:?}", e))?;
+        Ok(KeygenStatus {
+            round: round.low_u64(),
+            num_pending_validators,
+            num_parts: num_parts.low_u64(),
+            num_acks: num_acks.low_u64(),
+            all_parts_acks_available: num_parts.low_u64() == num_pending_validators as u64
+                && num_acks.low_u64() == num_pending_validators as u64,
+        })
+    }
+
+    /// Caches the values of the system contracts exported as metrics.
+    /// Skipped while syncing, the values are only of interest at the head of the chain.
+    fn update_contract_metrics(&self) {
+        match self.client_arc() {
+            Some(ref client) if !self.is_syncing(client) => {}
+            _ => return,
+        }
+        let keygen = match self.keygen_status() {
+            Ok(status) => Some(status),
+            Err(e) => {
+                debug!(target: "consensus", "Key generation metrics not available: {}", e);
+                None
+            }
+        };
+        self.metrics.update_contract_values(
+            keygen,
+            self.is_available().ok(),
+            self.is_staked().ok(),
+        );
+    }
+
+    /// Returns the state of the honey badger consensus.
+    pub fn honey_badger_status(&self) -> HoneyBadgerStatus {
+        let mut status = HoneyBadgerStatus {
+            last_batch: self.last_batch.read().clone(),
+            ..Default::default()
+        };
+        let hbbft_state = self.hbbft_state.read();
+        if let Some((epoch, has_contributed)) = hbbft_state.honey_badger_status() {
+            status.epoch = Some(epoch);
+            status.has_contributed = has_contributed;
+            status.contributors = hbbft_state
+                .contributors()
+                .iter()
+                .map(|node_id| public_to_address(&node_id.0))
+                .collect();
+        }
+        status
+    }
+
+    /// Returns the misbehaviour of each validator within the recent history.
+    pub fn validator_scores(&self) -> Vec<ValidatorScoreStatus> {
+        self.hbbft_message_dispatcher
+            .read()
+            .validator_scores()
+            .into_iter()
+            .map(|(node_id, score)| ValidatorScoreStatus {
+                mining_address: public_to_address(&node_id.0),
+                late_messages: score.late_messages,
+                missing_contributions: score.missing_contributions,
+                invalid_messages: score.invalid_messages,
+                faults: score.faults,
+            })
+            .collect()
+    }
+
+    /// Returns the state of the blocks currently being sealed, by block number.
+    pub fn sealing_status(&self) -> BTreeMap<BlockNumber, SealingStatus> {
+        self.sealing
+            .read()
+            .iter()
+            .map(|(block_num, sealing)| {
+                let status = match sealing.signature() {
+                    Some(_) => SealingStatus::Complete,
+                    None => SealingStatus::Ongoing,
+                };
+                (*block_num, status)
+            })
+            .collect()
+    }
+
+    /// Returns the addresses of the hbbft system contracts.
+    pub(crate) fn contracts(&self) -> &ContractAddresses {
+        &self.contracts
+    }
+
+    /// Returns the connection status of the validators reserved as peers.
+    pub fn validator_peers_status(&self) -> Vec<ValidatorPeerStatus> {
+        self.validator_peers.read().status()
+    }
 }
 
 impl Engine<EthereumMachine> for HoneyBadgerBFT {
@@ -976,6 +1909,24 @@ impl Engine<EthereumMachine> for HoneyBadgerBFT {
         "HoneyBadgerBFT"
     }
 
+    fn as_hbbft(&self) -> Option<&HoneyBadgerBFT> {
+        Some(self)
+    }
+
+    fn prometheus_metrics(&self, r: &mut PrometheusRegistry) {
+        self.metrics.prometheus_metrics(r);
+
+        self.hbbft_state
+            .read()
+            .future_messages_prometheus_metrics(r);
+
+        r.register_gauge(
+            "hbbft_unreachable_validators",
+            "Number of validators reserved as peers that are not connected",
+            self.validator_peers.read().unreachable_validators().len() as i64,
+        );
+    }
+
     fn machine(&self) -> &EthereumMachine {
         &self.machine
     }
@@ -1034,11 +1985,109 @@ impl Engine<EthereumMachine> for HoneyBadgerBFT {
         Ok(())
     }
 
+    /// POSDAO epoch transitions are enacted immediately, there is nothing to signal in advance.
+    fn signals_epoch_end(
+        &self,
+        _header: &Header,
+        _aux: AuxiliaryData,
+    ) -> EpochChange<EthereumMachine> {
+        EpochChange::No
+    }
+
+    /// A block is the end of an epoch if the POSDAO epoch of its state differs from the one of
+    /// its parent. The first block is always treated as a transition, to record the initial
+    /// threshold public master key.
+    fn is_epoch_end(
+        &self,
+        chain_head: &Header,
+        _finalized: &[H256],
+        _chain: &Headers<Header>,
+        _transition_store: &PendingTransitionStore,
+    ) -> Option<Vec<u8>> {
+        if chain_head.number() == 0 {
+            return None;
+        }
+        let client = self.client_arc()?;
+        let head_id = BlockId::Hash(chain_head.hash());
+        let posdao_epoch = get_posdao_epoch(&*client, &self.contracts, head_id)
+            .ok()?
+            .low_u64();
+        // The POSDAO epoch of the parent is usually the one of the previous chain head.
+        let cached_parent_posdao_epoch = self
+            .last_posdao_epoch
+            .write()
+            .replace((chain_head.hash(), posdao_epoch))
+            .filter(|(hash, _)| hash == chain_head.parent_hash())
+            .map(|(_, posdao_epoch)| posdao_epoch);
+
+        let transition_header = if chain_head.number() == 1 {
+            None
+        } else {
+            let parent_posdao_epoch = match cached_parent_posdao_epoch {
+                Some(parent_posdao_epoch) => parent_posdao_epoch,
+                None => get_posdao_epoch(
+                    &*client,
+                    &self.contracts,
+                    BlockId::Hash(*chain_head.parent_hash()),
+                )
+                .ok()?
+                .low_u64(),
+            };
+            if parent_posdao_epoch == posdao_epoch {
+                return None;
+            }
+            Some(chain_head)
+        };
+
+        let public_master_key = public_master_key_at(&*client, &self.contracts, head_id)?;
+        trace!(target: "engine", "POSDAO epoch transition to epoch {} at block #{}", posdao_epoch, chain_head.number());
+        let proof = EpochProof {
+            posdao_epoch,
+            public_master_key,
+        };
+        Some(proof.encode(transition_header))
+    }
+
+    fn epoch_verifier<'a>(
+        &self,
+        header: &Header,
+        proof: &'a [u8],
+    ) -> ConstructedVerifier<'a, EthereumMachine> {
+        // The genesis transition does not carry a proof.
+        if proof.is_empty() {
+            return ConstructedVerifier::Trusted(Box::new(NoOp));
+        }
+
+        match EpochProof::decode(proof) {
+            Ok((proof, transition_header)) => {
+                let verifier = Box::new(HbbftEpochVerifier::new(
+                    proof.public_master_key,
+                    self.machine.params().eip1559_transition,
+                ));
+                match transition_header {
+                    Some(finality_proof) => {
+                        ConstructedVerifier::Unconfirmed(verifier, finality_proof, header.hash())
+                    }
+                    None => match self.verify_initial_epoch_proof(header, &proof) {
+                        Ok(()) => ConstructedVerifier::Trusted(verifier),
+                        Err(e) => ConstructedVerifier::Err(e),
+                    },
+                }
+            }
+            Err(e) => ConstructedVerifier::Err(e.into()),
+        }
+    }
+
+    fn snapshot_components(&self) -> Option<Box<dyn crate::snapshot::SnapshotComponents>> {
+        // The epoch transitions of hbbft are chunked exactly like the ones of PoA chains.
+        Some(Box::new(::snapshot::PoaSnapshot))
+    }
+
     fn register_client(&self, client: Weak<dyn EngineClient>) {
         *self.client.write() = Some(client.clone());
         if let Some(client) = self.client_arc() {
             if let None = self.hbbft_state.write().update_honeybadger(
-                client,
+                client.clone(),
                 &self.signer,
                 BlockId::Latest,
                 true,
@@ -1046,20 +2095,49 @@ impl Engine<EthereumMachine> for HoneyBadgerBFT {
                 // As long as the client is set we should be able to initialize as a regular node.
                 error!(target: "engine", "Error during HoneyBadger initialization!");
             }
+            if let Some(latest) = client.block_number(BlockId::Latest) {
+                self.hbbft_message_dispatcher.write().free_memory(latest);
+            }
+            self.restore_journal(&client);
+            self.replay_journal(client);
         }
     }
 
+    fn check_signer_change(&self, address: &Address) -> Result<(), EngineError> {
+        self.plan_signer_change(address)
+            .map(|_| ())
+            .map_err(EngineError::Custom)
+    }
+
     fn set_signer(&self, signer: Option<Box<dyn EngineSigner>>) {
+        if let Some(new_address) = signer.as_ref().map(|signer| signer.address()) {
+            match self.plan_signer_change(&new_address) {
+                Ok(SignerChange::Immediate) => {}
+                Ok(SignerChange::AtNextEpoch) => {
+                    info!(target: "engine", "The engine signer {} takes over at the next POSDAO epoch, the current signer keeps its key share until then.", new_address);
+                    *self.next_signer_epoch.write() =
+                        self.hbbft_state.read().current_posdao_epoch();
+                    *self.next_signer.write() = signer;
+                    return;
+                }
+                Err(e) => {
+                    error!(target: "engine", "The engine signer was not changed to {}: {}", new_address, e);
+                    return;
+                }
+            }
+        }
+        *self.next_signer.write() = None;
         *self.signer.write() = signer;
         if let Some(client) = self.client_arc() {
             if let None = self.hbbft_state.write().update_honeybadger(
-                client,
+                client.clone(),
                 &self.signer,
                 BlockId::Latest,
                 true,
             ) {
                 info!(target: "engine", "HoneyBadger Algorithm could not be created, Client possibly not set yet.");
             }
+            self.replay_journal(client);
         }
     }
 
@@ -1074,18 +2152,10 @@ impl Engine<EthereumMachine> for HoneyBadgerBFT {
 
     fn generate_engine_transactions(
         &self,
-        block: &ExecutedBlock,
+        _block: &ExecutedBlock,
     ) -> Result<Vec<SignedTransaction>, Error> {
         self.check_for_epoch_change();
-        let _random_number = match self.random_numbers.read().get(&block.header.number()) {
-            None => {
-                return Err(EngineError::Custom(
-                    "No value available for calling randomness contract.".into(),
-                )
-                .into())
-            }
-            Some(r) => r,
-        };
+        // The random seed is fed into the randomness contract by a system call in `on_close_block`.
         Ok(Vec::new())
     }
 
@@ -1111,6 +2181,10 @@ impl Engine<EthereumMachine> for HoneyBadgerBFT {
         SealingState::NotReady
     }
 
+    fn on_best_block_imported(&self) {
+        self.update_contract_metrics();
+    }
+
     fn on_transactions_imported(&self) {
         self.check_for_epoch_change();
         if let Some(client) = self.client_arc() {
@@ -1123,19 +2197,34 @@ impl Engine<EthereumMachine> for HoneyBadgerBFT {
     fn handle_message(&self, message: &[u8], node_id: Option<H512>) -> Result<(), EngineError> {
         self.check_for_epoch_change();
         let node_id = NodeId(node_id.ok_or(EngineError::UnexpectedMessage)?);
-        match serde_json::from_slice(message) {
-            Ok(Message::HoneyBadger(msg_idx, hb_msg)) => {
+        let (message, encoding) = message_codec::decode(message).map_err(|e| {
+            EngineError::MalformedMessage(format!("Consensus message decoding failed: {}", e))
+        })?;
+        let binary_enabled = self.client_arc().map_or(false, |client| {
+            self.binary_messages_enabled(client.as_ref())
+        });
+        self.record_message_encoding(binary_enabled, node_id, encoding);
+        self.journal_received_message(&message, node_id);
+        match message {
+            Message::HoneyBadger(msg_idx, hb_msg) => {
                 self.process_hb_message(msg_idx, hb_msg, node_id)
             }
-            Ok(Message::Sealing(block_num, seal_msg)) => {
+            Message::Sealing(block_num, seal_msg) => {
                 self.process_sealing_message(seal_msg, node_id, block_num)
             }
-            Err(_) => Err(EngineError::MalformedMessage(
-                "Serde message decoding failed.".into(),
-            )),
         }
     }
 
+    fn on_peer_connected(&self, node_id: H512) {
+        self.validator_peers.write().on_peer_connected(node_id);
+    }
+
+    fn on_peer_disconnected(&self, node_id: H512) {
+        self.validator_peers.write().on_peer_disconnected(node_id);
+        // The peer may use another encoding once it reconnects.
+        self.peer_message_encodings.write().remove(&NodeId(node_id));
+    }
+
     fn seal_fields(&self, _header: &Header) -> usize {
         1
     }
@@ -1172,9 +2261,60 @@ impl Engine<EthereumMachine> for HoneyBadgerBFT {
         false
     }
 
+    /// Writes the random seed agreed on in the honey badger epoch into the extra data
+    /// of blocks we create, so it is covered by the threshold signature.
+    fn on_new_block(
+        &self,
+        block: &mut ExecutedBlock,
+        _epoch_begin: bool,
+        _ancestry: &mut dyn Iterator<Item = ExtendedHeader>,
+    ) -> Result<(), Error> {
+        let block_number = block.header.number();
+        let mut random_numbers = self.random_numbers.write();
+        *random_numbers = random_numbers.split_off(&block_number);
+        if self
+            .params
+            .randomness_contract_address_at(block_number)
+            .is_none()
+        {
+            return Ok(());
+        }
+
+        if let Some(random_number) = random_numbers.get(&block_number) {
+            let mut seed = vec![0u8; MIN_RANDOM_BYTES_PER_EPOCH];
+            random_number.to_big_endian(&mut seed);
+            block.header.set_extra_data(seed);
+        }
+        Ok(())
+    }
+
     fn on_close_block(&self, block: &mut ExecutedBlock) -> Result<(), Error> {
         self.check_for_epoch_change();
 
+        if let Some(address) = self
+            .params
+            .randomness_contract_address_at(block.header.number())
+        {
+            let block_number = block.header.number();
+            let agreed_seed = self.random_numbers.read().get(&block_number).cloned();
+            match block_random_seed(block.header.extra_data(), agreed_seed) {
+                Ok(Some(seed)) => {
+                    trace!(target: "consensus", "calling randomness contract {} for block {} with seed {}", address, block_number, seed);
+                    let mut call = default_system_or_code_call(&self.machine, block);
+                    RandomnessContract::new_from_address(address)
+                        .set_current_seed(&mut call, seed)?;
+                }
+                Ok(None) => {
+                    warn!(target: "consensus", "Block {} does not carry a random seed in its extra data, the seed of the randomness contract is not updated.", block_number);
+                }
+                Err(e) => {
+                    return Err(
+                        EngineError::Custom(format!("Block {}: {}", block_number, e)).into(),
+                    )
+                }
+            }
+        }
+
         if let Some(address) = self.params.block_reward_contract_address {
             // only if no block reward skips are defined for this block.
             let header_number = block.header.number();
@@ -1210,18 +2350,56 @@ impl Engine<EthereumMachine> for HoneyBadgerBFT {
     }
 }
 
+/// Returns the random seed carried in the extra data of a block, `None` if the extra data
+/// is not a seed. If this node knows the seed agreed on in the contributions of the
+/// block's batch, the extra data must carry exactly that seed.
+fn block_random_seed(extra_data: &[u8], agreed_seed: Option<U256>) -> Result<Option<U256>, String> {
+    let seed = if extra_data.len() == MIN_RANDOM_BYTES_PER_EPOCH {
+        Some(U256::from(extra_data))
+    } else {
+        None
+    };
+    match agreed_seed {
+        Some(agreed_seed) if seed != Some(agreed_seed) => Err(format!(
+            "the random seed in the extra data does not match the contributions, expected {}",
+            agreed_seed
+        )),
+        _ => Ok(seed),
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::super::{contribution::Contribution, test::create_transactions::create_transaction};
-    use crypto::publickey::{Generator, Random};
-    use ethereum_types::U256;
+    use super::{
+        super::{
+            contracts::staking::get_posdao_epoch,
+            contribution::{
+                unix_now_secs, Contribution, DEFAULT_RANDOM_BYTES_PER_EPOCH,
+                MIN_RANDOM_BYTES_PER_EPOCH,
+            },
+            test::{
+                create_transactions::create_transaction,
+                hbbft_test_client::{
+                    create_hbbft_client, create_hbbft_client_with_spec, hbbft_spec_with,
+                    hbbft_spec_with_randomness_contract,
+                },
+                MASTER_OF_CEREMONIES_KEYPAIR,
+            },
+        },
+        block_random_seed, HoneyBadgerBFT, MessageEncoding, NodeId,
+    };
+    use client::{BlockChainClient, PrepareOpenBlock};
+    use crypto::publickey::{Generator, KeyPair, Random};
+    use engines::signer::from_keypair;
+    use ethereum_types::{Address, H256, U256};
     use hbbft::{
         honey_badger::{HoneyBadger, HoneyBadgerBuilder},
         NetworkInfo,
     };
     use rand_065;
     use std::sync::Arc;
-    use types::transaction::SignedTransaction;
+    use test_helpers::new_db;
+    use types::{ids::BlockId, transaction::SignedTransaction};
 
     #[test]
     fn test_single_contribution() {
@@ -1241,7 +2419,12 @@ mod tests {
         let mut pending: Vec<SignedTransaction> = Vec::new();
         let keypair = Random.generate();
         pending.push(create_transaction(&keypair, &U256::from(1)));
-        let input_contribution = Contribution::new(&pending);
+        let input_contribution = Contribution::new(
+            &pending,
+            DEFAULT_RANDOM_BYTES_PER_EPOCH,
+            unix_now_secs(),
+            &mut rng,
+        );
 
         let step = honey_badger
             .propose(&input_contribution, &mut rng)
@@ -1254,4 +2437,209 @@ mod tests {
         assert_eq!(out.contributions.len(), 1);
         assert_eq!(out.contributions.get(&0).unwrap(), &input_contribution);
     }
+
+    #[test]
+    fn test_signer_handover_requires_key_share() {
+        let mut moc = create_hbbft_client(MASTER_OF_CEREMONIES_KEYPAIR.clone());
+        let transactor: KeyPair = Random.generate();
+        moc.transfer_to(&transactor.address(), &U256::from(9000000000000000000u64));
+        let engine = moc.client.engine();
+        let engine = engine
+            .as_hbbft()
+            .expect("The test client uses the hbbft engine");
+        assert!(engine.hbbft_state.read().is_validator());
+
+        // The MOC holds the key share of the current POSDAO epoch, so the signer can only be
+        // replaced by a mining address of the same pool.
+        let unregistered: KeyPair = Random.generate();
+        engine.set_signer(Some(from_keypair(unregistered.clone())));
+        assert_eq!(
+            engine.signer.read().as_ref().map(|signer| signer.address()),
+            Some(moc.address())
+        );
+        assert!(engine.next_signer.read().is_none());
+
+        // A scheduled rotation to a signer outside of the validator set of the next POSDAO epoch
+        // is dropped once the epoch starts, and the current signer keeps sealing blocks.
+        *engine.next_signer.write() = Some(from_keypair(unregistered));
+        *engine.next_signer_epoch.write() = 0;
+        moc.send_keygen_transactions();
+        moc.send_keygen_transactions();
+        moc.create_some_transaction(Some(&transactor));
+        assert_eq!(
+            get_posdao_epoch(moc.client.as_ref(), &moc.contracts(), BlockId::Latest)
+                .expect("Constant call must succeed"),
+            U256::from(1)
+        );
+        moc.create_some_transaction(Some(&transactor));
+
+        assert!(engine.next_signer.read().is_none());
+        assert_eq!(
+            engine.signer.read().as_ref().map(|signer| signer.address()),
+            Some(moc.address())
+        );
+        assert!(engine.hbbft_state.read().is_validator());
+        let block_number = moc.client.chain().best_block_number();
+        moc.create_some_transaction(Some(&transactor));
+        assert_eq!(moc.client.chain().best_block_number(), block_number + 1);
+
+        // The current signer holding the key share of the new POSDAO epoch takes over.
+        *engine.next_signer.write() = Some(from_keypair(MASTER_OF_CEREMONIES_KEYPAIR.clone()));
+        engine.promote_next_signer(&engine.client_arc().expect("The client is registered"));
+        assert!(engine.next_signer.read().is_none());
+        assert_eq!(
+            engine.signer.read().as_ref().map(|signer| signer.address()),
+            Some(moc.address())
+        );
+        assert!(engine.hbbft_state.read().is_validator());
+    }
+
+    #[test]
+    fn test_block_random_seed() {
+        let seed = U256::from(0x2a);
+        let mut extra_data = vec![0u8; MIN_RANDOM_BYTES_PER_EPOCH];
+        seed.to_big_endian(&mut extra_data);
+
+        assert_eq!(block_random_seed(&extra_data, None), Ok(Some(seed)));
+        assert_eq!(block_random_seed(&extra_data, Some(seed)), Ok(Some(seed)));
+        assert!(block_random_seed(&extra_data, Some(U256::from(1))).is_err());
+
+        // Extra data of other lengths does not carry a seed, unless one was agreed on.
+        assert_eq!(block_random_seed(b"OpenEthereum", None), Ok(None));
+        assert_eq!(block_random_seed(&[0u8; 40], None), Ok(None));
+        assert!(block_random_seed(&[], Some(seed)).is_err());
+    }
+
+    #[test]
+    fn test_random_seed_in_extra_data() {
+        let randomness_contract = Address::from_low_u64_be(0x8000);
+        let mut moc = create_hbbft_client_with_spec(
+            MASTER_OF_CEREMONIES_KEYPAIR.clone(),
+            hbbft_spec_with_randomness_contract(&randomness_contract),
+            new_db(),
+        );
+        let engine = moc.client.engine();
+        let engine = engine
+            .as_hbbft()
+            .expect("The test client uses the hbbft engine");
+
+        // The seed agreed on in the batch of block 1 is written into its extra data and
+        // fed into the randomness contract.
+        moc.create_some_transaction(None);
+        let header = moc.client.chain().best_block_header();
+        assert_eq!(header.number(), 1);
+        assert_eq!(header.extra_data().len(), MIN_RANDOM_BYTES_PER_EPOCH);
+        assert_eq!(
+            moc.client
+                .latest_storage_at(&randomness_contract, &H256::zero()),
+            H256::from_slice(header.extra_data())
+        );
+
+        // A block carrying the agreed seed of its batch closes.
+        let seed = U256::from(0x2a);
+        engine.random_numbers.write().insert(2, seed);
+        let mut seed_bytes = vec![0u8; MIN_RANDOM_BYTES_PER_EPOCH];
+        seed.to_big_endian(&mut seed_bytes);
+        let open_block = moc
+            .client
+            .prepare_open_block(moc.address(), (0.into(), 10_000_000.into()), Vec::new())
+            .expect("The block opens");
+        assert_eq!(open_block.header.extra_data(), &seed_bytes);
+        assert!(open_block.close().is_ok());
+
+        // A block with a tampered seed is rejected.
+        let mut open_block = moc
+            .client
+            .prepare_open_block(moc.address(), (0.into(), 10_000_000.into()), Vec::new())
+            .expect("The block opens");
+        seed_bytes[0] ^= 1;
+        open_block.block_mut().header.set_extra_data(seed_bytes);
+        assert!(open_block.close().is_err());
+    }
+
+    #[test]
+    fn test_binary_encoding_negotiated_across_transition() {
+        let spec = || {
+            hbbft_spec_with(|json| {
+                json["engine"]["hbbft"]["params"]["binaryConsensusMessagesTransition"] =
+                    serde_json::json!(2);
+            })
+        };
+        let mut node_a =
+            create_hbbft_client_with_spec(MASTER_OF_CEREMONIES_KEYPAIR.clone(), spec(), new_db());
+        let mut node_b = create_hbbft_client_with_spec(Random.generate(), spec(), new_db());
+        let id_a = NodeId(*node_a.keypair.public());
+        let id_b = NodeId(*node_b.keypair.public());
+        let engine_a = node_a.client.engine();
+        let engine_a = engine_a
+            .as_hbbft()
+            .expect("The test client uses the hbbft engine");
+        let engine_b = node_b.client.engine();
+        let engine_b = engine_b
+            .as_hbbft()
+            .expect("The test client uses the hbbft engine");
+
+        // Sends a message as the first node would and records its encoding at the second node.
+        let send = |from: &HoneyBadgerBFT, from_id: NodeId, to: &HoneyBadgerBFT, to_id: NodeId| {
+            let from_client = from.client_arc().expect("The client is registered");
+            let to_client = to.client_arc().expect("The client is registered");
+            let encoding =
+                from.message_encoding_for(from.binary_messages_enabled(&*from_client), &to_id);
+            to.record_message_encoding(to.binary_messages_enabled(&*to_client), from_id, encoding);
+            encoding
+        };
+
+        // Before the transition both nodes talk JSON.
+        assert_eq!(send(engine_a, id_a, engine_b, id_b), MessageEncoding::Json);
+        assert_eq!(send(engine_b, id_b, engine_a, id_a), MessageEncoding::Json);
+
+        // The first node reaches the transition before the second one.
+        node_a.create_some_transaction(None);
+        assert_eq!(node_a.client.chain().best_block_number(), 1);
+        assert_eq!(
+            send(engine_a, id_a, engine_b, id_b),
+            MessageEncoding::BincodeV1
+        );
+        assert_eq!(send(engine_b, id_b, engine_a, id_a), MessageEncoding::Json);
+        assert_eq!(send(engine_a, id_a, engine_b, id_b), MessageEncoding::Json);
+
+        // Once both nodes passed the transition they switch to the binary encoding.
+        node_a.sync_blocks_to(&mut node_b);
+        assert_eq!(node_b.client.chain().best_block_number(), 1);
+        assert_eq!(
+            send(engine_b, id_b, engine_a, id_a),
+            MessageEncoding::BincodeV1
+        );
+        assert_eq!(
+            send(engine_a, id_a, engine_b, id_b),
+            MessageEncoding::BincodeV1
+        );
+        assert_eq!(
+            send(engine_b, id_b, engine_a, id_a),
+            MessageEncoding::BincodeV1
+        );
+    }
+
+    #[test]
+    fn test_reannounce_availability_once() {
+        let moc = create_hbbft_client(MASTER_OF_CEREMONIES_KEYPAIR.clone());
+        let engine = moc.client.engine();
+        let engine = engine
+            .as_hbbft()
+            .expect("The test client uses the hbbft engine");
+        let client = engine.client_arc().expect("The client is registered");
+        let announcements = || {
+            moc.miner
+                .all_transactions()
+                .iter()
+                .filter(|tx| tx.signed().sender() == moc.address())
+                .count()
+        };
+
+        engine.reannounce_availability(&*client).unwrap();
+        assert_eq!(announcements(), 1);
+        // The announcement is still pending.
+        engine.reannounce_availability(&*client).unwrap();
+        assert_eq!(announcements(), 1);
+    }
 }
@@ -0,0 +1,299 @@
+use super::{
+    hbbft_state::{HbMessage, Proposal},
+    sealing, NodeId,
+};
+use hash::keccak;
+use hbbft::Epoched;
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, Read, Write},
+    path::{Path, PathBuf},
+};
+use types::BlockNumber;
+
+/// Name of the journal file, stored next to the directories of the kept hbbft messages.
+const JOURNAL_FILE_NAME: &str = "hbbft_journal";
+/// Number of checksum bytes following every record.
+const CHECKSUM_LEN: usize = 4;
+/// Maximum size of the journal. Received messages exceeding it are not journaled,
+/// our own contributions always are.
+const MAX_JOURNAL_BYTES: usize = 64 * 1024 * 1024;
+
+/// A part of the in-flight consensus state of this node.
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) enum JournalEntry {
+    /// Our contribution to a honey badger epoch.
+    Contribution(Proposal),
+    /// A honey badger message received from a validator.
+    HoneyBadgerMessage { sender: NodeId, message: HbMessage },
+    /// A signature share for the seal of a block, received from a validator.
+    SealingMessage {
+        block: BlockNumber,
+        sender: NodeId,
+        message: sealing::Message,
+    },
+}
+
+impl JournalEntry {
+    /// Returns the block the entry belongs to. The entry is obsolete once that block is imported.
+    fn block(&self) -> BlockNumber {
+        match self {
+            JournalEntry::Contribution(proposal) => proposal.epoch,
+            JournalEntry::HoneyBadgerMessage { message, .. } => message.epoch(),
+            JournalEntry::SealingMessage { block, .. } => *block,
+        }
+    }
+}
+
+/// An append-only journal of the in-flight consensus state of this node: our contribution to
+/// the current honey badger epoch and the consensus messages of blocks that are not imported yet.
+///
+/// Every record is prefixed with its length and followed by a checksum, so a record that was
+/// only partially written when the node crashed is discarded on restore.
+/// Our own messages are not journaled, replaying the received messages and proposing the
+/// journaled contribution again produces the same messages.
+pub(crate) struct HbbftJournal {
+    path: PathBuf,
+    file: Option<File>,
+    /// The encoded records of the journal, by the block they belong to.
+    records: Vec<(BlockNumber, Vec<u8>)>,
+    total_bytes: usize,
+    /// The entries read on restore, which have not been replayed yet.
+    restored: Vec<JournalEntry>,
+}
+
+impl HbbftJournal {
+    /// Creates a journal in the given directory. Nothing is read or written before `restore` is called.
+    pub fn new(directory: &Path) -> Self {
+        HbbftJournal {
+            path: directory.join(JOURNAL_FILE_NAME),
+            file: None,
+            records: Vec::new(),
+            total_bytes: 0,
+            restored: Vec::new(),
+        }
+    }
+
+    /// Reads the entries of blocks after the latest block, up to `max_block`, from the journal,
+    /// and opens the journal for appending new entries.
+    pub fn restore(&mut self, latest_block: BlockNumber, max_block: BlockNumber) -> io::Result<()> {
+        let mut bytes = Vec::new();
+        match File::open(&self.path) {
+            Ok(mut file) => {
+                file.read_to_end(&mut bytes)?;
+            }
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e),
+        }
+
+        self.records.clear();
+        self.restored.clear();
+        for (payload, entry) in decode_records(&bytes) {
+            let block = entry.block();
+            if block > latest_block && block <= max_block {
+                self.records.push((block, encode_record(&payload)));
+                self.restored.push(entry);
+            }
+        }
+        self.rewrite()
+    }
+
+    /// Returns the restored entries that have not been replayed yet.
+    ///
+    /// Our contributions come first, so they are proposed again before replaying
+    /// the received messages could make us propose a different one.
+    pub fn take_restored(&mut self) -> Vec<JournalEntry> {
+        let (mut entries, messages): (Vec<_>, Vec<_>) =
+            self.restored.drain(..).partition(|entry| match entry {
+                JournalEntry::Contribution(_) => true,
+                _ => false,
+            });
+        entries.extend(messages);
+        entries
+    }
+
+    /// Appends the entry to the journal. Our contributions are synced to disk before returning,
+    /// since proposing a different contribution in the same epoch after a crash would be a fault.
+    pub fn append(&mut self, entry: &JournalEntry) -> io::Result<()> {
+        let file = match self.file.as_mut() {
+            Some(file) => file,
+            // The journal has not been restored yet.
+            None => return Ok(()),
+        };
+        let payload =
+            bincode::serialize(entry).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        let record = encode_record(&payload);
+        let is_contribution = match entry {
+            JournalEntry::Contribution(_) => true,
+            _ => false,
+        };
+        if !is_contribution && self.total_bytes + record.len() > MAX_JOURNAL_BYTES {
+            return Err(io::Error::new(
+                io::ErrorKind::Other,
+                "the hbbft journal size limit is reached",
+            ));
+        }
+        file.write_all(&record)?;
+        if is_contribution {
+            file.sync_data()?;
+        }
+        self.total_bytes += record.len();
+        self.records.push((entry.block(), record));
+        Ok(())
+    }
+
+    /// Removes the entries of blocks up to the latest block, and of blocks after `max_block`,
+    /// from the journal.
+    pub fn prune(&mut self, latest_block: BlockNumber, max_block: BlockNumber) -> io::Result<()> {
+        let is_in_window = |block: BlockNumber| block > latest_block && block <= max_block;
+        if self.file.is_none() || self.records.iter().all(|(block, _)| is_in_window(*block)) {
+            return Ok(());
+        }
+        self.records.retain(|(block, _)| is_in_window(*block));
+        self.rewrite()
+    }
+
+    /// Replaces the journal file with the current records.
+    /// The records are written to a temporary file first, which is then moved in place.
+    fn rewrite(&mut self) -> io::Result<()> {
+        self.file = None;
+        if let Some(directory) = self.path.parent() {
+            fs::create_dir_all(directory)?;
+        }
+        let tmp_path = self.path.with_extension("tmp");
+        {
+            let mut tmp_file = File::create(&tmp_path)?;
+            for (_, record) in &self.records {
+                tmp_file.write_all(record)?;
+            }
+            tmp_file.sync_all()?;
+        }
+        fs::rename(&tmp_path, &self.path)?;
+        self.total_bytes = self.records.iter().map(|(_, record)| record.len()).sum();
+        self.file = Some(OpenOptions::new().append(true).open(&self.path)?);
+        Ok(())
+    }
+}
+
+fn checksum(payload: &[u8]) -> [u8; CHECKSUM_LEN] {
+    let mut checksum = [0u8; CHECKSUM_LEN];
+    checksum.copy_from_slice(&keccak(payload)[..CHECKSUM_LEN]);
+    checksum
+}
+
+fn encode_record(payload: &[u8]) -> Vec<u8> {
+    let mut record = Vec::with_capacity(4 + payload.len() + CHECKSUM_LEN);
+    record.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+    record.extend_from_slice(payload);
+    record.extend_from_slice(&checksum(payload));
+    record
+}
+
+/// Decodes the records of the journal, up to the first incomplete or corrupted record.
+fn decode_records(mut bytes: &[u8]) -> Vec<(Vec<u8>, JournalEntry)> {
+    let mut records = Vec::new();
+    while !bytes.is_empty() {
+        if bytes.len() < 4 {
+            warn!(target: "consensus", "Discarding an incomplete record of the hbbft journal.");
+            break;
+        }
+        let mut len = [0u8; 4];
+        len.copy_from_slice(&bytes[..4]);
+        let len = u32::from_le_bytes(len) as usize;
+        if bytes.len() < 4 + len + CHECKSUM_LEN {
+            warn!(target: "consensus", "Discarding an incomplete record of the hbbft journal.");
+            break;
+        }
+        let payload = &bytes[4..4 + len];
+        if bytes[4 + len..4 + len + CHECKSUM_LEN] != checksum(payload) {
+            warn!(target: "consensus", "Discarding a corrupted record of the hbbft journal.");
+            break;
+        }
+        match bincode::deserialize(payload) {
+            Ok(entry) => records.push((payload.to_vec(), entry)),
+            Err(e) => {
+                warn!(target: "consensus", "Discarding an undecodable record of the hbbft journal: {:?}", e);
+                break;
+            }
+        }
+        bytes = &bytes[4 + len + CHECKSUM_LEN..];
+    }
+    records
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use engines::hbbft::contribution::{Contribution, DEFAULT_RANDOM_BYTES_PER_EPOCH};
+    use rand_065;
+    use tempdir::TempDir;
+
+    fn proposal(epoch: u64) -> JournalEntry {
+        JournalEntry::Contribution(Proposal {
+            epoch,
+            contribution: Contribution::new(
+                &Vec::new(),
+                DEFAULT_RANDOM_BYTES_PER_EPOCH,
+                0,
+                &mut rand_065::thread_rng(),
+            ),
+            seed: [epoch as u8; 32],
+        })
+    }
+
+    fn restored_epochs(journal: &mut HbbftJournal) -> Vec<u64> {
+        journal
+            .take_restored()
+            .iter()
+            .map(JournalEntry::block)
+            .collect()
+    }
+
+    #[test]
+    fn test_journal_restore_and_prune() {
+        let tempdir = TempDir::new("hbbft_journal").unwrap();
+        let mut journal = HbbftJournal::new(tempdir.path());
+        journal.restore(0, 10).unwrap();
+        assert!(journal.take_restored().is_empty());
+        for epoch in 1..=6 {
+            journal.append(&proposal(epoch)).unwrap();
+        }
+
+        let mut restarted = HbbftJournal::new(tempdir.path());
+        restarted.restore(2, 5).unwrap();
+        assert_eq!(restored_epochs(&mut restarted), vec![3, 4, 5]);
+
+        restarted.prune(3, 4).unwrap();
+        let mut restarted = HbbftJournal::new(tempdir.path());
+        restarted.restore(0, 10).unwrap();
+        assert_eq!(restored_epochs(&mut restarted), vec![4]);
+    }
+
+    #[test]
+    fn test_journal_discards_torn_record() {
+        let tempdir = TempDir::new("hbbft_journal").unwrap();
+        let mut journal = HbbftJournal::new(tempdir.path());
+        journal.restore(0, 10).unwrap();
+        journal.append(&proposal(1)).unwrap();
+        journal.append(&proposal(2)).unwrap();
+
+        // Simulate a crash while writing the last record.
+        let path = tempdir.path().join(JOURNAL_FILE_NAME);
+        let len = fs::metadata(&path).unwrap().len();
+        OpenOptions::new()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_len(len - 1)
+            .unwrap();
+
+        let mut restarted = HbbftJournal::new(tempdir.path());
+        restarted.restore(0, 10).unwrap();
+        assert_eq!(restored_epochs(&mut restarted), vec![1]);
+        restarted.append(&proposal(3)).unwrap();
+
+        let mut restarted = HbbftJournal::new(tempdir.path());
+        restarted.restore(0, 10).unwrap();
+        assert_eq!(restored_epochs(&mut restarted), vec![1, 3]);
+    }
+}
@@ -1,7 +1,11 @@
 //use hbbft::honey_badger::{self, MessageContent};
-use hbbft::honey_badger::{self};
+use hbbft::{
+    broadcast,
+    honey_badger::{self, MessageContent},
+    subset::{self, MessageContent as SubsetMessageContent},
+};
 use parking_lot::RwLock;
-use std::collections::VecDeque;
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
 
 // use threshold_crypto::{SignatureShare};
 use engines::hbbft::{sealing, NodeId};
@@ -17,6 +21,68 @@ use std::{
 
 pub type HbMessage = honey_badger::Message<NodeId>;
 
+/// Number of epochs the misbehaviour of validators is remembered for.
+const MISBEHAVIOUR_HISTORY_EPOCHS: u64 = 1000;
+/// Number of epochs after the latest block misbehaviour is recorded for.
+/// Messages of later epochs are not handled, so misbehaviour reported for them is bogus
+/// and would never be pruned from the history.
+const MAX_FUTURE_MISBEHAVIOUR_EPOCHS: u64 = 100;
+/// Number of decided epochs whose messages are still expected without penalty.
+/// The messages of the last decided epoch are not needed anymore once a threshold
+/// of validators sent theirs, but honest validators keep sending them.
+const LATE_MESSAGE_GRACE_EPOCHS: u64 = 1;
+
+/// Returns true if a message of `message_epoch` is late, given the latest decided epoch.
+/// Messages of the epochs within `LATE_MESSAGE_GRACE_EPOCHS` are not late.
+pub(crate) fn is_late_message(message_epoch: u64, decided_epoch: u64) -> bool {
+    message_epoch.saturating_add(LATE_MESSAGE_GRACE_EPOCHS) <= decided_epoch
+}
+
+/// Kinds of validator misbehaviour tracked by the memorium.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum Misbehaviour {
+    /// A message for an epoch or block that was decided before the grace window.
+    LateMessage,
+    /// No participation in the broadcasts of an epoch.
+    MissingContribution,
+    /// A message that could not be decoded or handled.
+    InvalidMessage,
+    /// A fault reported by the consensus algorithm.
+    Fault,
+}
+
+/// Misbehaviour counters of a single validator.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ValidatorScore {
+    pub late_messages: u64,
+    pub missing_contributions: u64,
+    pub invalid_messages: u64,
+    pub faults: u64,
+}
+
+impl ValidatorScore {
+    fn record(&mut self, misbehaviour: Misbehaviour) {
+        match misbehaviour {
+            Misbehaviour::LateMessage => self.late_messages += 1,
+            Misbehaviour::MissingContribution => self.missing_contributions += 1,
+            Misbehaviour::InvalidMessage => self.invalid_messages += 1,
+            Misbehaviour::Fault => self.faults += 1,
+        }
+    }
+
+    fn add(&mut self, other: &ValidatorScore) {
+        self.late_messages += other.late_messages;
+        self.missing_contributions += other.missing_contributions;
+        self.invalid_messages += other.invalid_messages;
+        self.faults += other.faults;
+    }
+
+    /// Total number of misbehaviours, regardless of their kind.
+    pub fn total(&self) -> u64 {
+        self.late_messages + self.missing_contributions + self.invalid_messages + self.faults
+    }
+}
+
 /**
 Hbbft Message Process
 // Broadcast - Echo
@@ -50,6 +116,14 @@ pub(crate) struct HbbftMessageMemorium {
     last_block_deleted_from_disk: u64,
     dispatched_messages: VecDeque<HbMessage>,
     dispatched_seals: VecDeque<(sealing::Message, u64)>,
+
+    /// The latest block passed to `free_memory`.
+    latest_block: u64,
+    /// Misbehaviour of the validators by epoch, bounded to `MISBEHAVIOUR_HISTORY_EPOCHS` before
+    /// and `MAX_FUTURE_MISBEHAVIOUR_EPOCHS` after the latest block.
+    misbehaviour: BTreeMap<u64, BTreeMap<NodeId, ValidatorScore>>,
+    /// The senders of broadcast values and echoes by epoch, for the epochs after the latest block.
+    broadcast_senders: BTreeMap<u64, BTreeSet<NodeId>>,
 }
 
 pub(crate) struct HbbftMessageDispatcher {
@@ -77,13 +151,13 @@ impl HbbftMessageDispatcher {
         self.ensure_worker_thread();
     }
 
-    pub fn on_message_received(&mut self, message: &HbMessage) {
+    pub fn on_message_received(&mut self, message: &HbMessage, sender_id: &NodeId) {
         //performance: dispatcher pattern + multithreading could improve performance a lot.
 
-        self.memorial
-            .write()
-            .dispatched_messages
-            .push_back(message.clone());
+        let mut memorial = self.memorial.write();
+        memorial.on_broadcast_participation(message, sender_id);
+        memorial.dispatched_messages.push_back(message.clone());
+        drop(memorial);
 
         self.ensure_worker_thread();
     }
@@ -102,8 +176,35 @@ impl HbbftMessageDispatcher {
         }
     }
 
-    pub fn free_memory(&mut self, _current_block: u64) {
-        // TODO: make memorium freeing memory of ancient block.
+    /// Records a misbehaviour of the given validator in the given epoch.
+    pub fn report_misbehaviour(&self, node_id: &NodeId, epoch: u64, misbehaviour: Misbehaviour) {
+        self.memorial
+            .write()
+            .report_misbehaviour(node_id, epoch, misbehaviour);
+    }
+
+    /// Records a missing contribution of the given validator to the batch of the given epoch,
+    /// unless it sent a broadcast value or echo in that epoch.
+    /// Only `N - f` contributions make it into a batch, so validators that took part in the
+    /// broadcasts are not at fault for being left out.
+    pub fn report_missing_contribution(&self, node_id: &NodeId, epoch: u64) {
+        let mut memorial = self.memorial.write();
+        let participated = memorial
+            .broadcast_senders
+            .get(&epoch)
+            .map_or(false, |senders| senders.contains(node_id));
+        if !participated {
+            memorial.report_misbehaviour(node_id, epoch, Misbehaviour::MissingContribution);
+        }
+    }
+
+    /// Returns the accumulated misbehaviour of each validator within the history window.
+    pub fn validator_scores(&self) -> BTreeMap<NodeId, ValidatorScore> {
+        self.memorial.read().validator_scores()
+    }
+
+    pub fn free_memory(&mut self, current_block: u64) {
+        self.memorial.write().free_memory(current_block);
     }
 }
 
@@ -119,6 +220,9 @@ impl HbbftMessageMemorium {
             last_block_deleted_from_disk: 0,
             dispatched_messages: VecDeque::new(),
             dispatched_seals: VecDeque::new(),
+            latest_block: 0,
+            misbehaviour: BTreeMap::new(),
+            broadcast_senders: BTreeMap::new(),
         }
     }
 
@@ -152,47 +256,6 @@ impl HbbftMessageMemorium {
             if let Err(e) = file.write(message_json.as_bytes()) {
                 warn!(target: "consensus", "Error writing hbbft memorial file: {:?}", e);
             }
-
-            //figure out if we have to delete a old block
-            // 1. protect against integer underflow.
-            // 2. block is so new, that we have to trigger a cleanup
-            if epoch > self.config_blocks_to_keep_on_disk
-                && epoch > self.last_block_deleted_from_disk + self.config_blocks_to_keep_on_disk
-            {
-                let paths = fs::read_dir("data/messages/").unwrap();
-
-                for dir_entry_result in paths {
-                    //println!("Name: {}", path.unwrap().path().display())
-
-                    match dir_entry_result {
-                        Ok(dir_entry) => {
-                            let path_buf = dir_entry.path();
-
-                            if path_buf.is_dir() {
-                                let dir_name = path_buf.file_name().unwrap().to_str().unwrap();
-
-                                match dir_name.parse::<u64>() {
-                                    Ok(dir_epoch) => {
-                                        if dir_epoch <= epoch - self.config_blocks_to_keep_on_disk {
-                                            match fs::remove_dir_all(path_buf.clone()) {
-                                                Ok(_) => {
-                                                    info!(target: "consensus", "deleted old message directory: {:?}", path_buf);
-                                                }
-                                                Err(e) => {
-                                                    warn!(target: "consensus", "could not delete old directories reason: {:?}", e);
-                                                }
-                                            }
-                                        }
-                                    }
-                                    Err(_) => {}
-                                }
-                            }
-                        }
-                        Err(_) => {}
-                    }
-                    self.last_block_deleted_from_disk = epoch;
-                }
-            }
         }
     }
 
@@ -229,7 +292,7 @@ impl HbbftMessageMemorium {
             return true;
         }
 
-        return false;
+        return self.prune_files();
 
         // let content = message.content();
         //match content {
@@ -248,7 +311,218 @@ impl HbbftMessageMemorium {
         //}
     }
 
-    pub fn free_memory(&mut self, _current_block: u64) {
-        // self.signature_shares.remove(&epoch);
+    /// Deletes the message directories of blocks that are no longer kept on disk.
+    /// Returns true if directories were pruned.
+    fn prune_files(&mut self) -> bool {
+        let prune_until = self
+            .latest_block
+            .saturating_sub(self.config_blocks_to_keep_on_disk);
+        if self.config_blocks_to_keep_on_disk == 0
+            || prune_until <= self.last_block_deleted_from_disk
+        {
+            return false;
+        }
+
+        let paths = match fs::read_dir(&self.config_block_to_keep_directory) {
+            Ok(paths) => paths,
+            Err(e) => {
+                warn!(target: "consensus", "could not read hbbft message directory: {:?}", e);
+                self.last_block_deleted_from_disk = prune_until;
+                return false;
+            }
+        };
+
+        for dir_entry in paths.filter_map(Result::ok) {
+            let path_buf = dir_entry.path();
+            let dir_epoch = match path_buf
+                .file_name()
+                .and_then(|name| name.to_str())
+                .and_then(|name| name.parse::<u64>().ok())
+            {
+                Some(dir_epoch) => dir_epoch,
+                None => continue,
+            };
+            if path_buf.is_dir() && dir_epoch <= prune_until {
+                match fs::remove_dir_all(&path_buf) {
+                    Ok(_) => {
+                        debug!(target: "consensus", "deleted old message directory: {:?}", path_buf);
+                    }
+                    Err(e) => {
+                        warn!(target: "consensus", "could not delete old directories reason: {:?}", e);
+                    }
+                }
+            }
+        }
+        self.last_block_deleted_from_disk = prune_until;
+        true
+    }
+
+    /// Remembers the sender of a broadcast value or echo as a participant of the message's epoch.
+    fn on_broadcast_participation(&mut self, message: &HbMessage, sender_id: &NodeId) {
+        let epoch = message.epoch();
+        if epoch <= self.latest_block || epoch > self.latest_block + MAX_FUTURE_MISBEHAVIOUR_EPOCHS
+        {
+            return;
+        }
+        match message.content() {
+            MessageContent::Subset(subset::Message {
+                content: SubsetMessageContent::Broadcast(broadcast::Message::Value(_)),
+                ..
+            })
+            | MessageContent::Subset(subset::Message {
+                content: SubsetMessageContent::Broadcast(broadcast::Message::Echo(_)),
+                ..
+            }) => {
+                self.broadcast_senders
+                    .entry(epoch)
+                    .or_default()
+                    .insert(*sender_id);
+            }
+            _ => {}
+        }
+    }
+
+    fn report_misbehaviour(&mut self, node_id: &NodeId, epoch: u64, misbehaviour: Misbehaviour) {
+        if epoch.saturating_add(MISBEHAVIOUR_HISTORY_EPOCHS) <= self.latest_block
+            || epoch > self.latest_block + MAX_FUTURE_MISBEHAVIOUR_EPOCHS
+        {
+            return;
+        }
+        debug!(target: "consensus", "Validator {} misbehaved in epoch {}: {:?}", node_id, epoch, misbehaviour);
+        self.misbehaviour
+            .entry(epoch)
+            .or_default()
+            .entry(*node_id)
+            .or_default()
+            .record(misbehaviour);
+    }
+
+    fn validator_scores(&self) -> BTreeMap<NodeId, ValidatorScore> {
+        let mut scores: BTreeMap<NodeId, ValidatorScore> = BTreeMap::new();
+        for (node_id, score) in self.misbehaviour.values().flat_map(|epoch| epoch.iter()) {
+            scores.entry(*node_id).or_default().add(score);
+        }
+        scores
+    }
+
+    pub fn free_memory(&mut self, current_block: u64) {
+        self.latest_block = current_block;
+        let first_kept_epoch = current_block.saturating_sub(MISBEHAVIOUR_HISTORY_EPOCHS) + 1;
+        self.misbehaviour = self.misbehaviour.split_off(&first_kept_epoch);
+        self.broadcast_senders = self.broadcast_senders.split_off(&(current_block + 1));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crypto::publickey::{Generator, Random};
+    use tempdir::TempDir;
+
+    fn node_id() -> NodeId {
+        NodeId(*Random.generate().public())
+    }
+
+    #[test]
+    fn test_validator_scores_history() {
+        let mut memorium = HbbftMessageMemorium::new(0, String::new());
+        let (good, bad) = (node_id(), node_id());
+        memorium.report_misbehaviour(&bad, 1, Misbehaviour::Fault);
+        memorium.report_misbehaviour(&bad, 2, Misbehaviour::LateMessage);
+        memorium.report_misbehaviour(&bad, 2, Misbehaviour::LateMessage);
+        memorium.report_misbehaviour(&good, 5, Misbehaviour::MissingContribution);
+
+        let scores = memorium.validator_scores();
+        assert_eq!(
+            scores[&bad],
+            ValidatorScore {
+                late_messages: 2,
+                faults: 1,
+                ..Default::default()
+            }
+        );
+        assert_eq!(scores[&good].total(), 1);
+
+        // Misbehaviour outside of the history window is forgotten and no longer recorded.
+        memorium.free_memory(MISBEHAVIOUR_HISTORY_EPOCHS + 1);
+        memorium.report_misbehaviour(&bad, 1, Misbehaviour::InvalidMessage);
+        let scores = memorium.validator_scores();
+        assert_eq!(scores[&bad].total(), 2);
+        assert_eq!(scores[&good].total(), 1);
+    }
+
+    #[test]
+    fn test_future_misbehaviour_is_not_recorded() {
+        let mut memorium = HbbftMessageMemorium::new(0, String::new());
+        let bad = node_id();
+        memorium.free_memory(10);
+        memorium.report_misbehaviour(
+            &bad,
+            10 + MAX_FUTURE_MISBEHAVIOUR_EPOCHS,
+            Misbehaviour::Fault,
+        );
+        memorium.report_misbehaviour(
+            &bad,
+            11 + MAX_FUTURE_MISBEHAVIOUR_EPOCHS,
+            Misbehaviour::Fault,
+        );
+        memorium.report_misbehaviour(&bad, u64::max_value(), Misbehaviour::InvalidMessage);
+        assert_eq!(memorium.validator_scores()[&bad].total(), 1);
+        assert_eq!(memorium.misbehaviour.len(), 1);
+    }
+
+    #[test]
+    fn test_late_message_grace_window() {
+        assert!(!is_late_message(10, 10));
+        assert!(!is_late_message(11, 10));
+        assert!(is_late_message(9, 10));
+        assert!(is_late_message(0, 10));
+        assert!(!is_late_message(u64::max_value(), 0));
+    }
+
+    #[test]
+    fn test_missing_contribution_of_broadcast_participant() {
+        let dispatcher = HbbftMessageDispatcher::new(0, String::new());
+        let (participant, absent) = (node_id(), node_id());
+        dispatcher
+            .memorial
+            .write()
+            .broadcast_senders
+            .entry(3)
+            .or_default()
+            .insert(participant);
+
+        dispatcher.report_missing_contribution(&participant, 3);
+        dispatcher.report_missing_contribution(&absent, 3);
+        dispatcher.report_missing_contribution(&participant, 4);
+        let scores = dispatcher.validator_scores();
+        assert_eq!(scores[&absent].missing_contributions, 1);
+        assert_eq!(scores[&participant].missing_contributions, 1);
+
+        // The participants of decided epochs are forgotten.
+        dispatcher.memorial.write().free_memory(3);
+        assert!(dispatcher.memorial.read().broadcast_senders.is_empty());
+    }
+
+    #[test]
+    fn test_prune_files() {
+        let tempdir = TempDir::new("hbbft_memorium").unwrap();
+        let directory = format!("{}/", tempdir.path().display());
+        let mut memorium = HbbftMessageMemorium::new(10, directory.clone());
+        for epoch in 1..=20 {
+            fs::create_dir_all(format!("{}{}", directory, epoch)).unwrap();
+        }
+
+        assert!(!memorium.prune_files());
+        memorium.free_memory(20);
+        assert!(memorium.prune_files());
+        assert!(!memorium.prune_files());
+
+        let mut remaining: Vec<u64> = fs::read_dir(&directory)
+            .unwrap()
+            .filter_map(|entry| entry.ok()?.file_name().to_str()?.parse().ok())
+            .collect();
+        remaining.sort();
+        assert_eq!(remaining, (11..=20).collect::<Vec<_>>());
     }
 }
@@ -0,0 +1,350 @@
+use super::{hbbft_state::HbMessage, hbbft_status::KeygenStatus, sealing};
+use hbbft::{
+    broadcast,
+    honey_badger::MessageContent,
+    subset::{self, MessageContent as SubsetMessageContent},
+};
+use parking_lot::RwLock;
+use stats::PrometheusRegistry;
+use std::{
+    collections::BTreeMap,
+    sync::atomic::{AtomicU64, Ordering},
+    time::{Duration, Instant},
+};
+use types::BlockNumber;
+
+/// The types of consensus messages that are counted separately.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum MessageKind {
+    BroadcastValue,
+    BroadcastEcho,
+    BroadcastEchoHash,
+    BroadcastReady,
+    BroadcastCanDecode,
+    Agreement,
+    DecryptionShare,
+    Sealing,
+}
+
+const MESSAGE_KINDS: [MessageKind; 8] = [
+    MessageKind::BroadcastValue,
+    MessageKind::BroadcastEcho,
+    MessageKind::BroadcastEchoHash,
+    MessageKind::BroadcastReady,
+    MessageKind::BroadcastCanDecode,
+    MessageKind::Agreement,
+    MessageKind::DecryptionShare,
+    MessageKind::Sealing,
+];
+
+impl MessageKind {
+    pub fn of_hb_message(message: &HbMessage) -> Self {
+        match message.content() {
+            MessageContent::Subset(subset::Message { content, .. }) => match content {
+                SubsetMessageContent::Broadcast(broadcast::Message::Value(_)) => {
+                    MessageKind::BroadcastValue
+                }
+                SubsetMessageContent::Broadcast(broadcast::Message::Echo(_)) => {
+                    MessageKind::BroadcastEcho
+                }
+                SubsetMessageContent::Broadcast(broadcast::Message::EchoHash(_)) => {
+                    MessageKind::BroadcastEchoHash
+                }
+                SubsetMessageContent::Broadcast(broadcast::Message::Ready(_)) => {
+                    MessageKind::BroadcastReady
+                }
+                SubsetMessageContent::Broadcast(broadcast::Message::CanDecode(_)) => {
+                    MessageKind::BroadcastCanDecode
+                }
+                SubsetMessageContent::Agreement(_) => MessageKind::Agreement,
+            },
+            MessageContent::DecryptionShare { .. } => MessageKind::DecryptionShare,
+        }
+    }
+
+    pub fn of_sealing_message(_message: &sealing::Message) -> Self {
+        MessageKind::Sealing
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            MessageKind::BroadcastValue => "broadcast_value",
+            MessageKind::BroadcastEcho => "broadcast_echo",
+            MessageKind::BroadcastEchoHash => "broadcast_echo_hash",
+            MessageKind::BroadcastReady => "broadcast_ready",
+            MessageKind::BroadcastCanDecode => "broadcast_can_decode",
+            MessageKind::Agreement => "agreement",
+            MessageKind::DecryptionShare => "decryption_share",
+            MessageKind::Sealing => "sealing",
+        }
+    }
+}
+
+/// Sum and count of observed durations, exported like a prometheus summary without quantiles.
+#[derive(Default)]
+struct DurationStats {
+    last: Duration,
+    sum: Duration,
+    count: u64,
+}
+
+impl DurationStats {
+    fn observe(&mut self, duration: Duration) {
+        self.last = duration;
+        self.sum += duration;
+        self.count += 1;
+    }
+
+    fn register(&self, r: &mut PrometheusRegistry, name: &str, help: &str) {
+        r.register_gauge(
+            &format!("{}_last_ms", name),
+            &format!("{}, last observed value in milliseconds", help),
+            self.last.as_millis() as i64,
+        );
+        r.register_counter(
+            &format!("{}_sum_ms", name),
+            &format!("{}, sum of all observed values in milliseconds", help),
+            self.sum.as_millis() as i64,
+        );
+        r.register_counter(
+            &format!("{}_count", name),
+            &format!("{}, number of observed values", help),
+            self.count as i64,
+        );
+    }
+}
+
+/// Timestamps of the consensus progress, used to measure the latency of each stage.
+#[derive(Default)]
+struct Timings {
+    epoch_started: BTreeMap<u64, Instant>,
+    batch_output: BTreeMap<BlockNumber, Instant>,
+    epoch_to_batch: DurationStats,
+    batch_to_seal: DurationStats,
+}
+
+/// Values read from the system contracts, `None` if they could not be read.
+#[derive(Default)]
+struct ContractValues {
+    keygen: Option<KeygenStatus>,
+    is_available: Option<bool>,
+    is_staked: Option<bool>,
+}
+
+/// Metrics of the observable state of the hbbft engine.
+///
+/// Message counters are updated lock-free since they are hit for every consensus message.
+/// Values of the system contracts are cached when a block is imported, so exporting the
+/// metrics does not make contract calls.
+pub(crate) struct HbbftMetrics {
+    messages_sent: [AtomicU64; 8],
+    messages_received: [AtomicU64; 8],
+    timings: RwLock<Timings>,
+    contract_values: RwLock<ContractValues>,
+}
+
+impl HbbftMetrics {
+    pub fn new() -> Self {
+        HbbftMetrics {
+            messages_sent: Default::default(),
+            messages_received: Default::default(),
+            timings: RwLock::new(Timings::default()),
+            contract_values: RwLock::new(ContractValues::default()),
+        }
+    }
+
+    pub fn on_message_sent(&self, kind: MessageKind) {
+        self.messages_sent[kind as usize].fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn on_message_received(&self, kind: MessageKind) {
+        self.messages_received[kind as usize].fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Records the time this node started to take part in the given honey badger epoch,
+    /// if it is not known yet. Epochs following a batch output start with the output,
+    /// this is only needed for the first epoch after joining the consensus.
+    pub fn on_epoch_started(&self, epoch: u64) {
+        self.timings
+            .write()
+            .epoch_started
+            .entry(epoch)
+            .or_insert_with(Instant::now);
+    }
+
+    /// Records the output of the batch of the given honey badger epoch.
+    pub fn on_batch_output(&self, epoch: u64) {
+        let mut timings = self.timings.write();
+        let now = Instant::now();
+        if let Some(started) = timings.epoch_started.get(&epoch).cloned() {
+            timings.epoch_to_batch.observe(now - started);
+        }
+        // Epochs do not overlap, the start times of past epochs are not needed anymore.
+        timings.epoch_started = timings.epoch_started.split_off(&(epoch + 1));
+        // Honey badger moves on to the next epoch as soon as the batch is output.
+        timings.epoch_started.entry(epoch + 1).or_insert(now);
+        timings.batch_output.insert(epoch, now);
+    }
+
+    /// Records the completion of the seal of the given block.
+    pub fn on_seal_complete(&self, block_num: BlockNumber) {
+        let mut timings = self.timings.write();
+        if let Some(output) = timings.batch_output.get(&block_num).cloned() {
+            timings.batch_to_seal.observe(output.elapsed());
+        }
+        timings.batch_output = timings.batch_output.split_off(&(block_num + 1));
+    }
+
+    /// Replaces the cached values of the system contracts.
+    pub fn update_contract_values(
+        &self,
+        keygen: Option<KeygenStatus>,
+        is_available: Option<bool>,
+        is_staked: Option<bool>,
+    ) {
+        *self.contract_values.write() = ContractValues {
+            keygen,
+            is_available,
+            is_staked,
+        };
+    }
+
+    pub fn prometheus_metrics(&self, r: &mut PrometheusRegistry) {
+        for kind in MESSAGE_KINDS.iter() {
+            r.register_counter(
+                &format!("hbbft_messages_sent_{}", kind.name()),
+                &format!("Number of {} consensus messages sent", kind.name()),
+                self.messages_sent[*kind as usize].load(Ordering::Relaxed) as i64,
+            );
+            r.register_counter(
+                &format!("hbbft_messages_received_{}", kind.name()),
+                &format!("Number of {} consensus messages received", kind.name()),
+                self.messages_received[*kind as usize].load(Ordering::Relaxed) as i64,
+            );
+        }
+
+        let timings = self.timings.read();
+        timings.epoch_to_batch.register(
+            r,
+            "hbbft_epoch_to_batch",
+            "Time from the start of a honey badger epoch to its batch output",
+        );
+        timings.batch_to_seal.register(
+            r,
+            "hbbft_batch_to_seal",
+            "Time from the batch output to the completed block seal",
+        );
+
+        let contract_values = self.contract_values.read();
+        if let Some(ref status) = contract_values.keygen {
+            r.register_gauge(
+                "hbbft_keygen_round",
+                "Key generation round of the pending validator set",
+                status.round as i64,
+            );
+            r.register_gauge(
+                "hbbft_keygen_pending_validators",
+                "Number of pending validators taking part in the key generation",
+                status.num_pending_validators as i64,
+            );
+            r.register_gauge(
+                "hbbft_keygen_parts",
+                "Number of key generation Parts written",
+                status.num_parts as i64,
+            );
+            r.register_gauge(
+                "hbbft_keygen_acks",
+                "Number of key generation Acks written",
+                status.num_acks as i64,
+            );
+        }
+        if let Some(is_available) = contract_values.is_available {
+            r.register_gauge(
+                "hbbft_validator_available",
+                "Whether this node is flagged as available (1) or not (0)",
+                is_available as i64,
+            );
+        }
+        if let Some(is_staked) = contract_values.is_staked {
+            r.register_gauge(
+                "hbbft_validator_staked",
+                "Whether the pool of this node is staked (1) or not (0)",
+                is_staked as i64,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_metrics_message_counters() {
+        let metrics = HbbftMetrics::new();
+        metrics.on_message_sent(MessageKind::BroadcastEcho);
+        metrics.on_message_sent(MessageKind::BroadcastEcho);
+        metrics.on_message_received(MessageKind::Sealing);
+
+        let mut registry = PrometheusRegistry::new("".into());
+        metrics.prometheus_metrics(&mut registry);
+        let families = registry.registry().gather();
+        let value_of = |name: &str| {
+            families
+                .iter()
+                .find(|f| f.get_name() == name)
+                .map(|f| f.get_metric()[0].get_counter().get_value())
+        };
+        assert_eq!(value_of("hbbft_messages_sent_broadcast_echo"), Some(2.0));
+        assert_eq!(value_of("hbbft_messages_received_sealing"), Some(1.0));
+        assert_eq!(value_of("hbbft_messages_sent_agreement"), Some(0.0));
+    }
+
+    #[test]
+    fn test_metrics_timings() {
+        let metrics = HbbftMetrics::new();
+        metrics.on_epoch_started(5);
+        metrics.on_batch_output(5);
+        metrics.on_seal_complete(5);
+        // Sealing a block without a recorded batch output is not observed.
+        metrics.on_seal_complete(9);
+
+        let timings = metrics.timings.read();
+        assert_eq!(timings.epoch_to_batch.count, 1);
+        assert_eq!(timings.batch_to_seal.count, 1);
+        // The next epoch started with the batch output.
+        assert_eq!(timings.epoch_started.keys().collect::<Vec<_>>(), vec![&6]);
+        assert!(timings.batch_output.is_empty());
+    }
+
+    #[test]
+    fn test_metrics_contract_values() {
+        let metrics = HbbftMetrics::new();
+        let gauge_of = |metrics: &HbbftMetrics, name: &str| {
+            let mut registry = PrometheusRegistry::new("".into());
+            metrics.prometheus_metrics(&mut registry);
+            registry
+                .registry()
+                .gather()
+                .iter()
+                .find(|f| f.get_name() == name)
+                .map(|f| f.get_metric()[0].get_gauge().get_value())
+        };
+        assert_eq!(gauge_of(&metrics, "hbbft_validator_staked"), None);
+
+        metrics.update_contract_values(
+            Some(KeygenStatus {
+                round: 2,
+                num_pending_validators: 4,
+                num_parts: 3,
+                num_acks: 1,
+                all_parts_acks_available: false,
+            }),
+            None,
+            Some(true),
+        );
+        assert_eq!(gauge_of(&metrics, "hbbft_keygen_parts"), Some(3.0));
+        assert_eq!(gauge_of(&metrics, "hbbft_validator_available"), None);
+        assert_eq!(gauge_of(&metrics, "hbbft_validator_staked"), Some(1.0));
+    }
+}
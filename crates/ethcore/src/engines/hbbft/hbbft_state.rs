@@ -1,21 +1,29 @@
 use client::traits::EngineClient;
 use engines::signer::EngineSigner;
+use ethereum_types::U256;
 use hbbft::{
     crypto::{PublicKey, Signature},
-    honey_badger::{self, HoneyBadgerBuilder},
+    honey_badger::{self, HoneyBadgerBuilder, MessageContent},
+    subset::{self, MessageContent as SubsetMessageContent},
     Epoched, NetworkInfo,
 };
 use parking_lot::RwLock;
-use std::{collections::BTreeMap, sync::Arc};
-use types::{header::Header, ids::BlockId};
+use rand_065::{self, rngs::StdRng, Rng, SeedableRng};
+use stats::PrometheusRegistry;
+use std::{collections::BTreeSet, sync::Arc};
+use types::{header::Header, ids::BlockId, BlockNumber};
 
 use super::{
     contracts::{
         keygen_history::{initialize_synckeygen, synckeygen_to_network_info},
         staking::{get_posdao_epoch, get_posdao_epoch_start},
         validator_set::ValidatorType,
+        ContractAddresses,
     },
-    contribution::Contribution,
+    contribution::{select_transactions, Contribution, ContributionLimits},
+    epoch_proof::EpochProof,
+    future_messages_cache::{FutureMessagesCache, FutureMessagesLimits},
+    public_key_cache::{cache_public_master_key, public_master_key_at},
     NodeId,
 };
 
@@ -25,25 +33,135 @@ pub(crate) type Batch = honey_badger::Batch<Contribution, NodeId>;
 pub(crate) type HoneyBadgerStep = honey_badger::Step<Contribution, NodeId>;
 pub(crate) type HoneyBadgerResult = honey_badger::Result<HoneyBadgerStep>;
 
+/// Our contribution to a honey badger epoch, with the seed of the random number generator
+/// used to encrypt it. Proposing it again with the same seed produces the same messages.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub(crate) struct Proposal {
+    pub epoch: u64,
+    pub contribution: Contribution,
+    pub seed: [u8; 32],
+}
+
 pub(crate) struct HbbftState {
+    contracts: ContractAddresses,
     network_info: Option<NetworkInfo<NodeId>>,
     honey_badger: Option<HoneyBadger>,
     public_master_key: Option<PublicKey>,
     current_posdao_epoch: u64,
-    future_messages_cache: BTreeMap<u64, Vec<(NodeId, HbMessage)>>,
+    future_messages_cache: FutureMessagesCache,
+    /// Validators whose proposals are being broadcast in the honey badger epoch `proposers_epoch`.
+    proposers: BTreeSet<NodeId>,
+    proposers_epoch: u64,
+    random_bytes_per_epoch: usize,
+    max_contribution_bytes: usize,
+    /// The source of randomness of our contributions.
+    rng: StdRng,
 }
 
 impl HbbftState {
-    pub fn new() -> Self {
+    pub fn new(
+        contracts: ContractAddresses,
+        random_bytes_per_epoch: usize,
+        max_contribution_bytes: usize,
+        future_messages_limits: FutureMessagesLimits,
+    ) -> Self {
         HbbftState {
+            contracts,
             network_info: None,
             honey_badger: None,
             public_master_key: None,
             current_posdao_epoch: 0,
-            future_messages_cache: BTreeMap::new(),
+            future_messages_cache: FutureMessagesCache::new(future_messages_limits),
+            proposers: BTreeSet::new(),
+            proposers_epoch: 0,
+            random_bytes_per_epoch,
+            max_contribution_bytes,
+            rng: StdRng::from_seed(rand_065::random()),
+        }
+    }
+
+    /// Seeds the source of randomness, making our contributions reproducible.
+    #[cfg(test)]
+    pub fn seed_rng(&mut self, seed: [u8; 32]) {
+        self.rng = StdRng::from_seed(seed);
+    }
+
+    /// Returns true if this node is a validator of the current POSDAO epoch.
+    pub fn is_validator(&self) -> bool {
+        self.network_info
+            .as_ref()
+            .map_or(false, |network_info| network_info.is_validator())
+    }
+
+    /// Returns the POSDAO epoch the hbbft state was last updated for.
+    pub fn current_posdao_epoch(&self) -> u64 {
+        self.current_posdao_epoch
+    }
+
+    /// Returns the honey badger epoch and whether we have contributed to it.
+    pub fn honey_badger_status(&self) -> Option<(u64, bool)> {
+        self.honey_badger
+            .as_ref()
+            .map(|honey_badger| (honey_badger.epoch(), honey_badger.has_input()))
+    }
+
+    /// Returns the validators that have contributed to the current honey badger epoch,
+    /// as far as their proposals were broadcast to us.
+    pub fn contributors(&self) -> Vec<NodeId> {
+        let (honey_badger, network_info) = match (&self.honey_badger, &self.network_info) {
+            (Some(honey_badger), Some(network_info)) => (honey_badger, network_info),
+            _ => return Vec::new(),
+        };
+        let mut contributors = BTreeSet::new();
+        if self.proposers_epoch == honey_badger.epoch() {
+            contributors.extend(self.proposers.iter().cloned());
+        }
+        if honey_badger.has_input() {
+            contributors.insert(network_info.our_id().clone());
+        }
+        contributors.into_iter().collect()
+    }
+
+    /// Records the proposer of a broadcast message of the current honey badger epoch.
+    fn record_proposer(&mut self, message: &HbMessage) {
+        let current_epoch = match self.honey_badger {
+            Some(ref honey_badger) => honey_badger.epoch(),
+            None => return,
+        };
+        if message.epoch() != current_epoch {
+            return;
+        }
+        if let MessageContent::Subset(subset::Message {
+            proposer_id,
+            content: SubsetMessageContent::Broadcast(_),
+        }) = message.content()
+        {
+            if self.proposers_epoch != current_epoch {
+                self.proposers.clear();
+                self.proposers_epoch = current_epoch;
+            }
+            self.proposers.insert(proposer_id.clone());
         }
     }
 
+    /// Registers the metrics of the cache of messages of future honey badger epochs.
+    pub fn future_messages_prometheus_metrics(&self, r: &mut PrometheusRegistry) {
+        self.future_messages_cache.prometheus_metrics(r);
+    }
+
+    /// Returns the latest block whose consensus messages are accepted, given the latest imported block.
+    /// Messages of later blocks are too far ahead to be cached or journaled.
+    pub fn max_message_block(&self, latest_block: BlockNumber) -> BlockNumber {
+        latest_block + 1 + self.future_messages_cache.max_future_epochs()
+    }
+
+    /// Returns true if the node is a validator of the current validator set.
+    pub fn is_current_validator(&self, node_id: &NodeId) -> bool {
+        self.network_info.as_ref().map_or(false, |network_info| {
+            network_info.is_node_validator(node_id)
+        })
+    }
+
     fn new_honey_badger(&self, network_info: NetworkInfo<NodeId>) -> Option<HoneyBadger> {
         let mut builder: HoneyBadgerBuilder<Contribution, _> =
             HoneyBadger::builder(Arc::new(network_info));
@@ -57,16 +175,20 @@ impl HbbftState {
         block_id: BlockId,
         force: bool,
     ) -> Option<()> {
-        let target_posdao_epoch = get_posdao_epoch(&*client, block_id).ok()?.low_u64();
+        let target_posdao_epoch = get_posdao_epoch(&*client, &self.contracts, block_id)
+            .ok()?
+            .low_u64();
         if !force && self.current_posdao_epoch == target_posdao_epoch {
             // hbbft state is already up to date.
             // @todo Return proper error codes.
             return Some(());
         }
 
-        let posdao_epoch_start = get_posdao_epoch_start(&*client, block_id).ok()?;
+        let posdao_epoch_start =
+            get_posdao_epoch_start(&*client, &self.contracts, block_id).ok()?;
         let synckeygen = initialize_synckeygen(
             &*client,
+            &self.contracts,
             signer,
             BlockId::Number(posdao_epoch_start.low_u64()),
             ValidatorType::Current,
@@ -76,6 +198,7 @@ impl HbbftState {
 
         let (pks, sks) = synckeygen.generate().ok()?;
         self.public_master_key = Some(pks.public_key());
+        cache_public_master_key(&*client, target_posdao_epoch, &pks.public_key());
         // Clear network info and honey badger instance, since we may not be in this POSDAO epoch any more.
         info!(target: "engine", "public master key: {:?}", pks.public_key());
 
@@ -118,7 +241,7 @@ impl HbbftState {
         // We have to attempt to switch to the newest block, and then check if the hbbft epoch's parent
         // block is already imported. If not we have to wait until that block is available.
         let parent_block = honey_badger.epoch() - 1;
-        match get_posdao_epoch(&*client, BlockId::Number(parent_block)) {
+        match get_posdao_epoch(&*client, &self.contracts, BlockId::Number(parent_block)) {
             Ok(epoch) => {
                 if epoch.low_u64() != self.current_posdao_epoch {
                     trace!(target: "engine", "replay_cached_messages: Parent block(#{}) imported, but hbbft state not updated yet, re-trying later.", parent_block);
@@ -131,26 +254,25 @@ impl HbbftState {
             }
         }
 
-        let messages = self.future_messages_cache.get(&honey_badger.epoch())?;
+        let messages = self.future_messages_cache.take_epoch(honey_badger.epoch());
         if messages.is_empty() {
             return None;
         }
 
+        for (_, message) in &messages {
+            self.record_proposer(message);
+        }
+        let honey_badger = self.honey_badger.as_mut()?;
         let network_info = self.network_info.as_ref()?.clone();
 
         let all_steps: Vec<_> = messages
-			.iter()
-			.map(|m| {
-				trace!(target: "engine", "Replaying cached consensus message {:?} from {}", m.1, m.0);
-				honey_badger.handle_message(&m.0, m.1.clone())
+			.into_iter()
+			.map(|(sender, message)| {
+				trace!(target: "engine", "Replaying cached consensus message {:?} from {}", message, sender);
+				honey_badger.handle_message(&sender, message)
 			})
 			.collect();
 
-        // Delete current epoch and all previous messages
-        self.future_messages_cache = self
-            .future_messages_cache
-            .split_off(&(honey_badger.epoch() + 1));
-
         Some((all_steps, network_info))
     }
 
@@ -189,25 +311,27 @@ impl HbbftState {
         signer: &Arc<RwLock<Option<Box<dyn EngineSigner>>>>,
         sender_id: NodeId,
         message: HbMessage,
+        is_known_sender: bool,
     ) -> Option<(HoneyBadgerStep, NetworkInfo<NodeId>)> {
-        self.skip_to_current_epoch(client, signer)?;
+        self.skip_to_current_epoch(client.clone(), signer)?;
 
         // If honey_badger is None we are not a validator, nothing to do.
-        let honey_badger = self.honey_badger.as_mut()?;
+        let current_epoch = self.honey_badger.as_ref()?.epoch();
 
         let message_epoch = message.epoch();
         // Note that if the message is for a future epoch we do not know if the current honey_badger
         // instance is the correct one to use. Tt may change if the the POSDAO epoch changes, causing
         // consensus messages to get lost.
-        if message_epoch > honey_badger.epoch() {
-            trace!(target: "consensus", "Message from future epoch, caching it for handling it in when the epoch is current. Current hbbft epoch is: {}", honey_badger.epoch());
+        if message_epoch > current_epoch {
+            trace!(target: "consensus", "Message from future epoch, caching it for handling it in when the epoch is current. Current hbbft epoch is: {}", current_epoch);
             self.future_messages_cache
-                .entry(message.epoch())
-                .or_default()
-                .push((sender_id, message));
+                .insert(current_epoch, sender_id, message, is_known_sender);
             return None;
         }
 
+        self.record_proposer(&message);
+        let honey_badger = self.honey_badger.as_mut()?;
+
         let network_info = self.network_info.as_ref()?.clone();
 
         match honey_badger.handle_message(&sender_id, message) {
@@ -227,22 +351,25 @@ impl HbbftState {
         &mut self,
         client: Arc<dyn EngineClient>,
         signer: &Arc<RwLock<Option<Box<dyn EngineSigner>>>>,
-    ) -> Option<(HoneyBadgerStep, NetworkInfo<NodeId>)> {
+        timestamp: u64,
+    ) -> Option<(HoneyBadgerStep, NetworkInfo<NodeId>, Proposal)> {
         // If honey_badger is None we are not a validator, nothing to do.
         let honey_badger = self.honey_badger.as_mut()?;
         let network_info = self.network_info.as_ref()?;
 
         if honey_badger.received_proposals() > network_info.num_faulty() {
-            return self.try_send_contribution(client, signer);
+            return self.try_send_contribution(client, signer, timestamp);
         }
         None
     }
 
+    /// Proposes our contribution to the current honey badger epoch, with the given timestamp.
     pub fn try_send_contribution(
         &mut self,
         client: Arc<dyn EngineClient>,
         signer: &Arc<RwLock<Option<Box<dyn EngineSigner>>>>,
-    ) -> Option<(HoneyBadgerStep, NetworkInfo<NodeId>)> {
+        timestamp: u64,
+    ) -> Option<(HoneyBadgerStep, NetworkInfo<NodeId>, Proposal)> {
         // Make sure we are in the most current epoch.
         self.skip_to_current_epoch(client.clone(), signer)?;
 
@@ -256,9 +383,13 @@ impl HbbftState {
         // If the parent block of the block we would contribute to is not in the hbbft state's
         // epoch we cannot start to contribute, since we would write into a hbbft instance
         // which will be destroyed.
-        let posdao_epoch = get_posdao_epoch(&*client, BlockId::Number(honey_badger.epoch() - 1))
-            .ok()?
-            .low_u64();
+        let posdao_epoch = get_posdao_epoch(
+            &*client,
+            &self.contracts,
+            BlockId::Number(honey_badger.epoch() - 1),
+        )
+        .ok()?
+        .low_u64();
         if self.current_posdao_epoch != posdao_epoch {
             trace!(target: "consensus", "hbbft_state epoch mismatch: hbbft_state epoch is {}, honey badger instance epoch is: {}.",
 				   self.current_posdao_epoch, posdao_epoch);
@@ -270,18 +401,61 @@ impl HbbftState {
         trace!(target: "consensus", "Writing contribution for hbbft epoch(block) {}.", honey_badger.epoch());
 
         // Now we can select the transactions to include in our contribution.
-        // TODO: Select a random *subset* of transactions to propose
-        let input_contribution = Contribution::new(
-            &client
+        // Every validator proposes its share of the block gas limit.
+        let our_index = network_info.node_index(network_info.our_id())?;
+        let num_validators = network_info.num_nodes();
+        let block_gas_limit = client.block_header(BlockId::Latest)?.gas_limit();
+        let limits = ContributionLimits {
+            max_gas: block_gas_limit / U256::from(num_validators),
+            max_bytes: self.max_contribution_bytes,
+        };
+        let transactions = select_transactions(
+            client
                 .queued_transactions()
                 .iter()
                 .map(|txn| txn.signed().clone())
                 .collect(),
+            honey_badger.epoch(),
+            our_index,
+            num_validators,
+            &limits,
         );
+        trace!(target: "consensus", "Selected {} transactions for the contribution to hbbft epoch {}.", transactions.len(), honey_badger.epoch());
+        let proposal = Proposal {
+            epoch: honey_badger.epoch(),
+            contribution: Contribution::new(
+                &transactions,
+                self.random_bytes_per_epoch,
+                timestamp,
+                &mut self.rng,
+            ),
+            seed: self.rng.gen(),
+        };
+        let (step, network_info) = self.propose(&proposal)?;
+        Some((step, network_info, proposal))
+    }
+
+    /// Proposes our contribution journaled before the node was restarted,
+    /// if we did not contribute to its epoch yet.
+    pub fn restore_proposal(
+        &mut self,
+        client: Arc<dyn EngineClient>,
+        signer: &Arc<RwLock<Option<Box<dyn EngineSigner>>>>,
+        proposal: &Proposal,
+    ) -> Option<(HoneyBadgerStep, NetworkInfo<NodeId>)> {
+        self.skip_to_current_epoch(client, signer)?;
+        let honey_badger = self.honey_badger.as_ref()?;
+        if honey_badger.epoch() != proposal.epoch || honey_badger.has_input() {
+            return None;
+        }
+        self.propose(proposal)
+    }
 
-        let mut rng = rand_065::thread_rng();
-        let step = honey_badger.propose(&input_contribution, &mut rng);
-        match step {
+    fn propose(&mut self, proposal: &Proposal) -> Option<(HoneyBadgerStep, NetworkInfo<NodeId>)> {
+        let honey_badger = self.honey_badger.as_mut()?;
+        let network_info = self.network_info.as_ref()?.clone();
+        let mut rng = StdRng::from_seed(proposal.seed);
+        match honey_badger.propose(&proposal.contribution, &mut rng) {
             Ok(step) => Some((step, network_info)),
             _ => {
                 // TODO: Report detailed consensus step errors
@@ -302,8 +476,11 @@ impl HbbftState {
 
         // Check if posdao epoch fits the parent block of the header seal to verify.
         let parent_block_nr = header.number() - 1;
-        let target_posdao_epoch = match get_posdao_epoch(&*client, BlockId::Number(parent_block_nr))
-        {
+        let target_posdao_epoch = match get_posdao_epoch(
+            &*client,
+            &self.contracts,
+            BlockId::Number(parent_block_nr),
+        ) {
             Ok(number) => number.low_u64(),
             Err(e) => {
                 error!(target: "consensus", "Failed to verify seal - reading POSDAO epoch from contract failed! Error: {:?}", e);
@@ -312,49 +489,30 @@ impl HbbftState {
         };
         if self.current_posdao_epoch != target_posdao_epoch {
             trace!(target: "consensus", "verify_seal - hbbft state epoch does not match epoch at the header's parent, attempting to reconstruct the appropriate public key share from scratch.");
-            // If the requested block nr is already imported we try to generate the public master key from scratch.
-            let posdao_epoch_start = match get_posdao_epoch_start(
-                &*client,
-                BlockId::Number(parent_block_nr),
-            ) {
-                Ok(epoch_start) => epoch_start,
-                Err(e) => {
-                    error!(target: "consensus", "Querying epoch start block failed with error: {:?}", e);
-                    return false;
-                }
-            };
-
-            let synckeygen = match initialize_synckeygen(
-                &*client,
-                &Arc::new(RwLock::new(Option::None)),
-                BlockId::Number(posdao_epoch_start.low_u64()),
-                ValidatorType::Current,
-            ) {
-                Ok(synckeygen) => synckeygen,
-                Err(e) => {
-                    error!(target: "consensus", "Synckeygen failed with error: {:?}", e);
-                    return false;
-                }
-            };
-
-            if !synckeygen.is_ready() {
-                error!(target: "consensus", "Synckeygen not ready when it sohuld be!");
-                return false;
+            // If the requested block nr is already imported we use the cached public master key,
+            // or try to generate it from scratch.
+            if let Some(key) =
+                public_master_key_at(&*client, &self.contracts, BlockId::Number(parent_block_nr))
+            {
+                trace!(target: "consensus", "verify_seal - successfully retrieved public key share of past posdao epoch.");
+                return key.verify(signature, header.bare_hash());
             }
 
-            let pks = match synckeygen.generate() {
-                Ok((pks, _)) => pks,
-                Err(e) => {
-                    error!(target: "consensus", "Generating of public key share failed with error: {:?}", e);
-                    return false;
+            // After a snapshot restoration the state required for the reconstruction is missing,
+            // fall back to the public master key recorded with the epoch transition.
+            return match public_master_key_from_epoch_transition(&*client, header) {
+                Some(key) => key.verify(signature, header.bare_hash()),
+                None => {
+                    error!(target: "consensus", "Failed to verify seal - no public master key available for block #{}", header.number());
+                    false
                 }
             };
-
-            trace!(target: "consensus", "verify_seal - successfully reconstructed public key share of past posdao epoch.");
-            return pks.public_key().verify(signature, header.bare_hash());
         }
 
-        match self.public_master_key {
+        match self
+            .public_master_key
+            .or_else(|| public_master_key_from_epoch_transition(&*client, header))
+        {
             Some(key) => key.verify(signature, header.bare_hash()),
             None => {
                 error!(target: "consensus", "Failed to verify seal - public master key not available!");
@@ -371,9 +529,10 @@ impl HbbftState {
     ) -> Option<NetworkInfo<NodeId>> {
         self.skip_to_current_epoch(client.clone(), signer);
 
-        let posdao_epoch = get_posdao_epoch(&*client, BlockId::Number(block_nr - 1))
-            .ok()?
-            .low_u64();
+        let posdao_epoch =
+            get_posdao_epoch(&*client, &self.contracts, BlockId::Number(block_nr - 1))
+                .ok()?
+                .low_u64();
 
         if self.current_posdao_epoch != posdao_epoch {
             error!(target: "consensus", "Trying to get the network info from a different epoch. Current epoch: {}, Requested epoch: {}",
@@ -384,3 +543,62 @@ impl HbbftState {
         self.network_info.clone()
     }
 }
+
+/// Reconstructs the threshold public master key used to seal the children of the given block
+/// from the keygen history contract.
+pub(crate) fn reconstruct_public_master_key(
+    client: &dyn EngineClient,
+    contracts: &ContractAddresses,
+    block_id: BlockId,
+) -> Option<PublicKey> {
+    let posdao_epoch_start = match get_posdao_epoch_start(client, contracts, block_id) {
+        Ok(epoch_start) => epoch_start,
+        Err(e) => {
+            error!(target: "consensus", "Querying epoch start block failed with error: {:?}", e);
+            return None;
+        }
+    };
+
+    let synckeygen = match initialize_synckeygen(
+        client,
+        contracts,
+        &Arc::new(RwLock::new(Option::None)),
+        BlockId::Number(posdao_epoch_start.low_u64()),
+        ValidatorType::Current,
+    ) {
+        Ok(synckeygen) => synckeygen,
+        Err(e) => {
+            error!(target: "consensus", "Synckeygen failed with error: {:?}", e);
+            return None;
+        }
+    };
+
+    if !synckeygen.is_ready() {
+        error!(target: "consensus", "Synckeygen not ready when it sohuld be!");
+        return None;
+    }
+
+    match synckeygen.generate() {
+        Ok((pks, _)) => Some(pks.public_key()),
+        Err(e) => {
+            error!(target: "consensus", "Generating of public key share failed with error: {:?}", e);
+            None
+        }
+    }
+}
+
+/// Returns the threshold public master key recorded with the epoch transition
+/// the given header belongs to.
+fn public_master_key_from_epoch_transition(
+    client: &dyn EngineClient,
+    header: &Header,
+) -> Option<PublicKey> {
+    let transition = client.epoch_transition_for(*header.parent_hash())?;
+    match EpochProof::decode(&transition.proof) {
+        Ok((proof, _)) => Some(proof.public_master_key),
+        Err(e) => {
+            trace!(target: "consensus", "Epoch transition at block #{} holds no hbbft epoch proof: {:?}", transition.block_number, e);
+            None
+        }
+    }
+}
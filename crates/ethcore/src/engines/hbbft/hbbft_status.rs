@@ -0,0 +1,105 @@
+//! Status of the hbbft engine and the POSDAO contracts, as seen by this node.
+//! Used to give validator operators insight into the consensus, e.g. over RPC.
+
+use ethereum_types::Address;
+use types::BlockNumber;
+
+/// The POSDAO epoch of the latest block.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PosdaoEpochStatus {
+    /// The POSDAO epoch number.
+    pub epoch: u64,
+    /// The block the POSDAO epoch started at.
+    pub start_block: BlockNumber,
+}
+
+/// The role of the engine signer in the validator set.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ValidatorStatus {
+    /// Address of the engine signer, if one is configured.
+    pub mining_address: Option<Address>,
+    /// Address of the engine signer taking over at the next POSDAO epoch, if a rotation is scheduled.
+    pub next_mining_address: Option<Address>,
+    /// Address of the staking pool of the mining address, if one exists.
+    pub staking_address: Option<Address>,
+    /// Whether the node is a validator of the current POSDAO epoch.
+    pub is_current_validator: bool,
+    /// Whether the node is a validator of the next POSDAO epoch.
+    pub is_pending_validator: bool,
+    /// Whether the pool of the node has staked at least the minimum candidate stake.
+    pub is_staked: bool,
+    /// Whether the node is flagged as available.
+    pub is_available: bool,
+}
+
+/// The progress of the key generation for the pending validator set.
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeygenStatus {
+    /// The key generation round, increased whenever a key generation attempt failed.
+    pub round: u64,
+    /// Number of pending validators taking part in the key generation.
+    pub num_pending_validators: usize,
+    /// Number of Parts written to the key history contract.
+    pub num_parts: u64,
+    /// Number of Acks written to the key history contract.
+    pub num_acks: u64,
+    /// Whether the Parts and Acks of all pending validators are available.
+    pub all_parts_acks_available: bool,
+}
+
+/// The contributions to a honey badger epoch that produced a batch.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BatchStatus {
+    /// The honey badger epoch, which equals the number of the block created from the batch.
+    pub epoch: u64,
+    /// Mining addresses of the validators whose contributions are part of the batch.
+    pub contributors: Vec<Address>,
+}
+
+/// The state of the honey badger consensus.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct HoneyBadgerStatus {
+    /// The current honey badger epoch, if the node is a validator.
+    pub epoch: Option<u64>,
+    /// Whether the node has contributed to the current epoch.
+    pub has_contributed: bool,
+    /// Mining addresses of the validators that have contributed to the current epoch.
+    pub contributors: Vec<Address>,
+    /// The last batch output by the consensus.
+    pub last_batch: Option<BatchStatus>,
+}
+
+/// The connection to a validator of the current or pending validator set.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValidatorPeerStatus {
+    /// Mining address of the validator.
+    pub mining_address: Address,
+    /// The enode URL the validator is reserved as peer with, if its endpoint is known.
+    pub enode: Option<String>,
+    /// Whether the validator is connected.
+    pub is_connected: bool,
+}
+
+/// The misbehaviour of a validator within the recent history, as recorded by this node.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValidatorScoreStatus {
+    /// Mining address of the validator.
+    pub mining_address: Address,
+    /// Number of messages for epochs or blocks that were decided before the grace window.
+    pub late_messages: u64,
+    /// Number of epochs the validator did not take part in the broadcasts of.
+    pub missing_contributions: u64,
+    /// Number of messages that could not be decoded or handled.
+    pub invalid_messages: u64,
+    /// Number of faults reported by the consensus algorithm.
+    pub faults: u64,
+}
+
+/// The state of threshold signing a block.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SealingStatus {
+    /// Signature shares are still being collected.
+    Ongoing,
+    /// The signature shares have been combined into the block seal.
+    Complete,
+}
@@ -1,103 +1,156 @@
 use client::traits::{EngineClient, TransactionRequest};
+use crypto::publickey::Public;
 use engines::{
     hbbft::{
         contracts::{
             keygen_history::{
                 engine_signer_to_synckeygen, get_current_key_gen_round, has_acks_of_address_data,
-                key_history_contract, part_of_address, PublicWrapper, KEYGEN_HISTORY_ADDRESS,
+                key_history_contract, part_of_address, PublicWrapper,
             },
             staking::get_posdao_epoch,
             validator_set::{
-                get_pending_validator_key_generation_mode, get_validator_pubkeys, KeyGenMode,
-                ValidatorType,
+                get_pending_validator_key_generation_mode, get_validator_pubkeys,
+                is_pending_validator, KeyGenMode, ValidatorType,
             },
+            ContractAddresses, SystemContract,
         },
         utils::bound_contract::CallError,
     },
     signer::EngineSigner,
 };
 use ethereum_types::{Address, U256};
-use itertools::Itertools;
+use hbbft::sync_key_gen::{Ack, Part, SyncKeyGen};
 use parking_lot::RwLock;
+use rand_065::{self, rngs::StdRng, SeedableRng};
 use std::{collections::BTreeMap, sync::Arc};
-use types::ids::BlockId;
+use types::{ids::BlockId, BlockNumber};
 
-pub struct KeygenTransactionSender {
-    last_keygen_mode: KeyGenMode,
-    keygen_mode_counter: u64,
+/// Number of blocks to wait for a sent Part or Acks transaction to be included,
+/// before it is sent again in case it is no longer in the transaction pool.
+static KEYGEN_TRANSACTION_RESEND_DELAY: u64 = 10;
+
+/// A Part or Acks transaction sent by this node.
+struct SentTransaction {
+    nonce: U256,
+    block: BlockNumber,
 }
 
-enum ShouldSendKeyAnswer {
-    // no, we are not in this key gen phase.
-    NoNotThisKeyGenMode,
-    // no, we are waiting to send key later.
-    NoWaiting,
-    // yes, keys should be send now.
-    Yes,
+/// The state of this node in a single key generation round.
+///
+/// The `SyncKeyGen` is created once per round, so resent Parts are identical
+/// and every Part of the other validators is handled exactly once.
+struct KeygenRound {
+    upcoming_epoch: U256,
+    round: U256,
+    validators: BTreeMap<Address, Public>,
+    synckeygen: SyncKeyGen<Public, PublicWrapper>,
+    part: Part,
+    /// Our Acks for the Parts of the validators, by validator address.
+    acks: BTreeMap<Address, Ack>,
+    part_sent: Option<SentTransaction>,
+    acks_sent: Option<SentTransaction>,
 }
 
-static KEYGEN_TRANSACTION_SEND_DELAY: u64 = 3;
-static KEYGEN_TRANSACTION_RESEND_DELAY: u64 = 10;
+pub struct KeygenTransactionSender {
+    contracts: ContractAddresses,
+    keygen_round: Option<KeygenRound>,
+    /// The source of randomness of our Parts and Acks.
+    rng: StdRng,
+}
 
 impl KeygenTransactionSender {
-    pub fn new() -> Self {
+    pub fn new(contracts: ContractAddresses) -> Self {
         KeygenTransactionSender {
-            last_keygen_mode: KeyGenMode::Other,
-            keygen_mode_counter: 0,
+            contracts,
+            keygen_round: None,
+            rng: StdRng::from_seed(rand_065::random()),
         }
     }
 
-    fn should_send(
-        &mut self,
-        client: &dyn EngineClient,
-        mining_address: &Address,
-        mode_to_check: KeyGenMode,
-    ) -> Result<ShouldSendKeyAnswer, CallError> {
-        let keygen_mode = get_pending_validator_key_generation_mode(client, mining_address)?;
-        if keygen_mode == mode_to_check {
-            if self.last_keygen_mode == mode_to_check {
-                self.keygen_mode_counter += 1;
-                if self.keygen_mode_counter == KEYGEN_TRANSACTION_SEND_DELAY {
-                    return Ok(ShouldSendKeyAnswer::Yes);
-                } else if self.keygen_mode_counter > KEYGEN_TRANSACTION_SEND_DELAY {
-                    // Part should have been sent already,
-                    // give the chain time to include the transaction before trying a re-send.
-                    if (self.keygen_mode_counter - KEYGEN_TRANSACTION_SEND_DELAY)
-                        % KEYGEN_TRANSACTION_RESEND_DELAY
-                        == 0
-                    {
-                        return Ok(ShouldSendKeyAnswer::Yes);
-                    }
-                } else {
-                    return Ok(ShouldSendKeyAnswer::NoWaiting);
-                }
-            } else {
-                self.last_keygen_mode = mode_to_check;
-                self.keygen_mode_counter = 1;
-                return Ok(ShouldSendKeyAnswer::NoWaiting);
-            }
-        }
-        return Ok(ShouldSendKeyAnswer::NoNotThisKeyGenMode);
+    /// Seeds the source of randomness, making our Parts and Acks reproducible.
+    #[cfg(test)]
+    pub fn seed_rng(&mut self, seed: [u8; 32]) {
+        self.rng = StdRng::from_seed(seed);
     }
 
-    fn should_send_part(
-        &mut self,
+    /// Returns true if the transaction was not sent yet, or if it has been dropped
+    /// from the transaction pool without taking effect.
+    fn should_send(
         client: &dyn EngineClient,
-        mining_address: &Address,
-    ) -> Result<ShouldSendKeyAnswer, CallError> {
-        self.should_send(client, mining_address, KeyGenMode::WritePart)
+        address: &Address,
+        sent: &Option<SentTransaction>,
+        latest_block: BlockNumber,
+    ) -> bool {
+        let sent = match sent {
+            Some(sent) => sent,
+            None => return true,
+        };
+        let is_queued = client
+            .queued_transactions()
+            .iter()
+            .any(|txn| txn.signed().sender() == *address && txn.signed().tx().nonce == sent.nonce);
+        !is_queued && latest_block >= sent.block + KEYGEN_TRANSACTION_RESEND_DELAY
     }
 
-    fn should_send_ack(
+    /// Drops the state of rounds that have ended and starts the current key generation round,
+    /// if we are part of the pending validator set.
+    fn update_round(
         &mut self,
         client: &dyn EngineClient,
-        mining_address: &Address,
-    ) -> Result<ShouldSendKeyAnswer, CallError> {
-        self.should_send(client, mining_address, KeyGenMode::WriteAck)
+        signer: &Arc<RwLock<Option<Box<dyn EngineSigner>>>>,
+    ) -> Result<(), CallError> {
+        let upcoming_epoch = get_posdao_epoch(client, &self.contracts, BlockId::Latest)? + 1;
+        let round = get_current_key_gen_round(client, &self.contracts)?;
+
+        let is_current = |r: &KeygenRound| r.upcoming_epoch == upcoming_epoch && r.round == round;
+        if !self.keygen_round.as_ref().map_or(true, is_current) {
+            if let Some(ended) = self.keygen_round.take() {
+                debug!(target: "engine", "Key generation round {} for epoch {} ended.", ended.round, ended.upcoming_epoch);
+            }
+        }
+
+        if self.keygen_round.is_none() {
+            let validators = get_validator_pubkeys(
+                client,
+                &self.contracts,
+                BlockId::Latest,
+                ValidatorType::Pending,
+            )?;
+            let pub_keys: BTreeMap<_, _> = validators
+                .values()
+                .map(|p| (*p, PublicWrapper { inner: p.clone() }))
+                .collect();
+
+            // if synckeygen creation fails then either signer or validator pub keys are problematic.
+            // Todo: We should expect up to f clients to write invalid pub keys. Report and re-start pending validator set selection.
+            let (synckeygen, part) =
+                engine_signer_to_synckeygen(signer, Arc::new(pub_keys), &mut self.rng)
+                    .map_err(|_| CallError::ReturnValueInvalid)?;
+
+            // If there is no part then we are not part of the pending validator set and there is nothing for us to do.
+            let part = match part {
+                Some(part) => part,
+                None => return Ok(()),
+            };
+
+            trace!(target: "engine", "Starting key generation round {} for epoch {}.", round, upcoming_epoch);
+            self.keygen_round = Some(KeygenRound {
+                upcoming_epoch,
+                round,
+                validators,
+                synckeygen,
+                part,
+                acks: BTreeMap::new(),
+                part_sent: None,
+                acks_sent: None,
+            });
+        }
+        Ok(())
     }
 
-    /// Returns a collection of transactions the pending validator has to submit in order to
+    /// Sends the Part and Acks transactions the pending validator has to submit in order to
     /// complete the keygen history contract data necessary to generate the next key and switch to the new validator set.
+    /// Transactions that got lost are sent again.
     pub fn send_keygen_transactions(
         &mut self,
         client: &dyn EngineClient,
@@ -106,10 +159,7 @@ impl KeygenTransactionSender {
         // If we have no signer there is nothing for us to send.
         let address = match signer.read().as_ref() {
             Some(signer) => signer.address(),
-            None => {
-                trace!(target: "engine", "Could not send keygen transactions, because signer module could not be retrieved");
-                return Err(CallError::ReturnValueInvalid);
-            }
+            None => return Ok(()),
         };
 
         let full_client = client.as_full_client().ok_or(CallError::NotFullClient)?;
@@ -120,40 +170,43 @@ impl KeygenTransactionSender {
             return Ok(());
         }
 
-        trace!(target:"engine", " get_validator_pubkeys...");
-
-        let vmap = get_validator_pubkeys(&*client, BlockId::Latest, ValidatorType::Pending)?;
-        let pub_keys: BTreeMap<_, _> = vmap
-            .values()
-            .map(|p| (*p, PublicWrapper { inner: p.clone() }))
-            .collect();
-
-        // if synckeygen creation fails then either signer or validator pub keys are problematic.
-        // Todo: We should expect up to f clients to write invalid pub keys. Report and re-start pending validator set selection.
-        let (mut synckeygen, part) = engine_signer_to_synckeygen(signer, Arc::new(pub_keys))
-            .map_err(|_| CallError::ReturnValueInvalid)?;
+        if !is_pending_validator(client, &self.contracts, &address)? {
+            self.keygen_round = None;
+            return Ok(());
+        }
 
-        // If there is no part then we are not part of the pending validator set and there is nothing for us to do.
-        let part_data = match part {
-            Some(part) => part,
-            None => return Err(CallError::ReturnValueInvalid),
+        let latest_block = match client.block_number(BlockId::Latest) {
+            Some(block) => block,
+            None => return Ok(()),
         };
 
-        let upcoming_epoch = get_posdao_epoch(client, BlockId::Latest)? + 1;
-        trace!(target:"engine", "preparing to send PARTS for upcoming epoch: {}", upcoming_epoch);
+        // The transactions are included in the next block at the earliest.
+        let keygen_history_address = self
+            .contracts
+            .address_at(SystemContract::KeygenHistory, latest_block + 1);
+        let keygen_mode =
+            get_pending_validator_key_generation_mode(client, &self.contracts, &address)?;
+        let contracts = self.contracts.clone();
+        self.update_round(client, signer)?;
+        let rng = &mut self.rng;
+        let keygen_round = match self.keygen_round.as_mut() {
+            Some(keygen_round) => keygen_round,
+            None => return Ok(()),
+        };
 
-        // Check if we already sent our part.
-        match self.should_send_part(client, &address)? {
-            ShouldSendKeyAnswer::Yes => {
-                let serialized_part = match bincode::serialize(&part_data) {
+        match keygen_mode {
+            KeyGenMode::WritePart => {
+                if !Self::should_send(client, &address, &keygen_round.part_sent, latest_block) {
+                    return Ok(());
+                }
+                let serialized_part = match bincode::serialize(&keygen_round.part) {
                     Ok(part) => part,
                     Err(_) => return Err(CallError::ReturnValueInvalid),
                 };
                 let serialized_part_len = serialized_part.len();
-                let current_round = get_current_key_gen_round(client)?;
                 let write_part_data = key_history_contract::functions::write_part::call(
-                    upcoming_epoch,
-                    current_round,
+                    keygen_round.upcoming_epoch,
+                    keygen_round.round,
                     serialized_part,
                 );
 
@@ -164,69 +217,72 @@ impl KeygenTransactionSender {
                 // and usually run into the gas limit problems.
                 let gas: usize = serialized_part_len * 800 + 100_000;
 
+                let nonce = full_client.next_nonce(&address);
                 let part_transaction =
-                    TransactionRequest::call(*KEYGEN_HISTORY_ADDRESS, write_part_data.0)
+                    TransactionRequest::call(keygen_history_address, write_part_data.0)
                         .gas(U256::from(gas))
-                        .nonce(full_client.nonce(&address, BlockId::Latest).unwrap())
+                        .nonce(nonce)
                         .gas_price(U256::from(10000000000u64));
                 full_client
                     .transact_silently(part_transaction)
                     .map_err(|_| CallError::ReturnValueInvalid)?;
+                keygen_round.part_sent = Some(SentTransaction {
+                    nonce,
+                    block: latest_block,
+                });
 
                 trace!(target:"engine", "PART Transaction send.");
-                return Ok(());
             }
-            ShouldSendKeyAnswer::NoWaiting => {
-                // we are waiting for parts to get written,
-                // we do not need to continue any further with current key gen history.
-                return Ok(());
-            }
-            ShouldSendKeyAnswer::NoNotThisKeyGenMode => {}
-        }
+            KeyGenMode::WriteAck => {
+                // Handle the Parts we did not acknowledge yet, each Part is handled exactly once.
+                let missing: Vec<Address> = keygen_round
+                    .validators
+                    .keys()
+                    .filter(|v| !keygen_round.acks.contains_key(v))
+                    .cloned()
+                    .collect();
+                for v in missing {
+                    match part_of_address(
+                        client,
+                        &contracts,
+                        v,
+                        &keygen_round.validators,
+                        &mut keygen_round.synckeygen,
+                        BlockId::Latest,
+                        rng,
+                    )? {
+                        Some(ack) => {
+                            keygen_round.acks.insert(v, ack);
+                        }
+                        None => {
+                            trace!(target:"engine", "could not retrieve part for {}", v);
+                            return Ok(());
+                        }
+                    }
+                }
 
-        trace!(target:"engine", "checking for acks...");
-        // Return if any Part is missing.
-        let mut acks = Vec::new();
-        for v in vmap.keys().sorted() {
-            acks.push(
-				match part_of_address(&*client, *v, &vmap, &mut synckeygen, BlockId::Latest) {
-					Ok(part_result) => {
-						match part_result {
-							    Some(ack) => ack,
-							    None => {
-							        trace!(target:"engine", "could not retrieve part for {}", *v);
-							        return Err(CallError::ReturnValueInvalid);
-							    }
-							}
-					}
-					Err(err) => {
-						error!(target:"engine", "could not retrieve part for {} call failed. Error: {:?}", *v, err);
-						return Err(err);
-					}
-				}
-            );
-        }
+                trace!(target:"engine", "has_acks_of_address_data: {:?}", has_acks_of_address_data(client, &contracts, address));
 
-        trace!(target:"engine", "has_acks_of_address_data: {:?}", has_acks_of_address_data(client, address));
+                // Now we are sure all parts are ready, let's check if we sent our Acks.
+                if !Self::should_send(client, &address, &keygen_round.acks_sent, latest_block) {
+                    return Ok(());
+                }
 
-        // Now we are sure all parts are ready, let's check if we sent our Acks.
-        match self.should_send_ack(client, &address)? {
-            ShouldSendKeyAnswer::Yes => {
                 let mut serialized_acks = Vec::new();
                 let mut total_bytes_for_acks = 0;
 
-                for ack in acks {
-                    let ack_to_push = match bincode::serialize(&ack) {
+                // Acks are sorted by validator address.
+                for ack in keygen_round.acks.values() {
+                    let ack_to_push = match bincode::serialize(ack) {
                         Ok(serialized_ack) => serialized_ack,
                         Err(_) => return Err(CallError::ReturnValueInvalid),
                     };
                     total_bytes_for_acks += ack_to_push.len();
                     serialized_acks.push(ack_to_push);
                 }
-                let current_round = get_current_key_gen_round(client)?;
                 let write_acks_data = key_history_contract::functions::write_acks::call(
-                    upcoming_epoch,
-                    current_round,
+                    keygen_round.upcoming_epoch,
+                    keygen_round.round,
                     serialized_acks,
                 );
 
@@ -236,16 +292,23 @@ impl KeygenTransactionSender {
                 let gas = total_bytes_for_acks * 850 + 200_000;
                 trace!(target: "engine","acks-len: {} gas: {}", total_bytes_for_acks, gas);
 
+                let nonce = full_client.next_nonce(&address);
                 let acks_transaction =
-                    TransactionRequest::call(*KEYGEN_HISTORY_ADDRESS, write_acks_data.0)
+                    TransactionRequest::call(keygen_history_address, write_acks_data.0)
                         .gas(U256::from(gas))
-                        .nonce(full_client.nonce(&address, BlockId::Latest).unwrap())
+                        .nonce(nonce)
                         .gas_price(U256::from(10000000000u64));
                 full_client
                     .transact_silently(acks_transaction)
                     .map_err(|_| CallError::ReturnValueInvalid)?;
+                keygen_round.acks_sent = Some(SentTransaction {
+                    nonce,
+                    block: latest_block,
+                });
+
+                trace!(target:"engine", "ACKS Transaction send.");
             }
-            _ => {}
+            KeyGenMode::Other => {}
         }
 
         Ok(())
@@ -0,0 +1,269 @@
+use client::traits::EngineClient;
+use engines::{
+    hbbft::{
+        contracts::{
+            validator_set::{report_malicious_callable, send_tx_report_malicious},
+            ContractAddresses,
+        },
+        hbbft_state::HbMessage,
+        sealing,
+        utils::bound_contract::CallError,
+    },
+    signer::EngineSigner,
+};
+use ethereum_types::Address;
+use parking_lot::RwLock;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::Arc,
+};
+use types::{ids::BlockId, BlockNumber};
+
+/// Maximum number of report transactions sent per block.
+static MAX_REPORTS_PER_BLOCK: usize = 3;
+/// Number of blocks after which faults that could not be reported are dropped.
+static REPORT_VALIDITY_BLOCKS: u64 = 100;
+
+/// The message of a validator that caused a fault.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum EvidenceMessage {
+    /// A honey badger message.
+    HoneyBadger(HbMessage),
+    /// A signature share for the seal of a block.
+    Sealing(sealing::Message),
+}
+
+/// The evidence of a fault, sent bincode encoded as the proof of the report.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FaultEvidence {
+    /// The block the fault happened in.
+    pub block: BlockNumber,
+    /// The kind of the fault, as described by the consensus algorithm.
+    pub kind: String,
+    /// The message that caused the fault, if the fault was caused by a single message.
+    pub message: Option<EvidenceMessage>,
+}
+
+/// Collects faults of validators and reports them to the validator set contract.
+pub struct MaliceReportSender {
+    contracts: ContractAddresses,
+    /// Faults waiting to be reported, by malicious mining address and block, with their evidence.
+    pending: BTreeMap<(Address, BlockNumber), FaultEvidence>,
+    /// Faults that have already been reported.
+    sent: BTreeSet<(Address, BlockNumber)>,
+    /// The latest block reports were sent at, and the number of reports sent at that block.
+    sent_at_block: (BlockNumber, usize),
+}
+
+impl MaliceReportSender {
+    pub fn new(contracts: ContractAddresses) -> Self {
+        MaliceReportSender {
+            contracts,
+            pending: BTreeMap::new(),
+            sent: BTreeSet::new(),
+            sent_at_block: (0, 0),
+        }
+    }
+
+    /// Queues the fault of a validator for reporting. Faults of a validator
+    /// are reported at most once per block.
+    /// Faults of blocks too old to be reported, or after `max_block`, are dropped.
+    pub fn queue_report(
+        &mut self,
+        malicious_address: Address,
+        evidence: FaultEvidence,
+        latest_block: BlockNumber,
+        max_block: BlockNumber,
+    ) {
+        let block = evidence.block;
+        if block + REPORT_VALIDITY_BLOCKS < latest_block || block > max_block {
+            debug!(target: "consensus", "Dropping the fault of validator {} for block {}, outside of the reporting window.", malicious_address, block);
+            return;
+        }
+        let key = (malicious_address, block);
+        if !self.sent.contains(&key) {
+            self.pending.entry(key).or_insert(evidence);
+        }
+    }
+
+    /// Drops the faults of blocks that are too old to be reported.
+    fn prune(&mut self, latest_block: BlockNumber) {
+        let is_valid =
+            |&(_, block): &(Address, BlockNumber)| block + REPORT_VALIDITY_BLOCKS >= latest_block;
+        self.pending.retain(|key, _| is_valid(key));
+        self.sent.retain(is_valid);
+    }
+
+    /// Returns the number of reports that may still be sent at the latest block.
+    fn remaining_reports(&mut self, latest_block: BlockNumber) -> usize {
+        if self.sent_at_block.0 != latest_block {
+            self.sent_at_block = (latest_block, 0);
+        }
+        MAX_REPORTS_PER_BLOCK.saturating_sub(self.sent_at_block.1)
+    }
+
+    /// Sends the queued reports for blocks that are already part of the chain,
+    /// limited to `MAX_REPORTS_PER_BLOCK` per block.
+    pub fn send_reports(
+        &mut self,
+        client: &dyn EngineClient,
+        signer: &Arc<RwLock<Option<Box<dyn EngineSigner>>>>,
+    ) -> Result<(), CallError> {
+        if self.pending.is_empty() {
+            return Ok(());
+        }
+
+        // If we have no signer we can not report anything.
+        let reporting_address = match signer.read().as_ref() {
+            Some(signer) => signer.address(),
+            None => {
+                self.pending.clear();
+                return Ok(());
+            }
+        };
+
+        let full_client = client.as_full_client().ok_or(CallError::NotFullClient)?;
+        if full_client.is_major_syncing() {
+            return Ok(());
+        }
+        let latest_block = match client.block_number(BlockId::Latest) {
+            Some(block) => block,
+            None => return Ok(()),
+        };
+
+        self.prune(latest_block);
+        let remaining = self.remaining_reports(latest_block);
+        let reportable: Vec<_> = self
+            .pending
+            .keys()
+            .filter(|&&(_, block)| block <= latest_block)
+            .take(remaining)
+            .cloned()
+            .collect();
+
+        for key in reportable {
+            let (malicious_address, block) = key;
+            let evidence = match self.pending.remove(&key) {
+                Some(evidence) => evidence,
+                None => continue,
+            };
+            let proof = match bincode::serialize(&evidence) {
+                Ok(proof) => proof,
+                Err(e) => {
+                    error!(target: "consensus", "Could not encode the evidence of the fault of validator {} for block {}: {:?}", malicious_address, block, e);
+                    continue;
+                }
+            };
+            if !report_malicious_callable(
+                client,
+                &self.contracts,
+                &reporting_address,
+                &malicious_address,
+                block,
+            )? {
+                debug!(target: "consensus", "Reporting validator {} for block {} is not callable, dropping the report.", malicious_address, block);
+                continue;
+            }
+            send_tx_report_malicious(
+                full_client,
+                &self.contracts,
+                &reporting_address,
+                &malicious_address,
+                block,
+                proof,
+            )
+            .map_err(|e| CallError::CallFailed(format!("{:?}", e)))?;
+            self.sent.insert(key);
+            self.sent_at_block.1 += 1;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn evidence(block: BlockNumber, kind: &str) -> FaultEvidence {
+        FaultEvidence {
+            block,
+            kind: kind.into(),
+            message: None,
+        }
+    }
+
+    #[test]
+    fn test_malice_reports_deduplicated_and_pruned() {
+        let mut sender = MaliceReportSender::new(ContractAddresses::default());
+        let malicious = Address::from_low_u64_be(1);
+        sender.queue_report(malicious, evidence(10, "first"), 9, 20);
+        sender.queue_report(malicious, evidence(10, "second"), 9, 20);
+        sender.queue_report(malicious, evidence(11, "other block"), 9, 20);
+        assert_eq!(sender.pending.len(), 2);
+        assert_eq!(sender.pending[&(malicious, 10)].kind, "first");
+
+        // Sent reports are not queued again.
+        sender.pending.remove(&(malicious, 10));
+        sender.sent.insert((malicious, 10));
+        sender.queue_report(malicious, evidence(10, "again"), 9, 20);
+        assert_eq!(sender.pending.len(), 1);
+
+        sender.prune(11 + REPORT_VALIDITY_BLOCKS);
+        assert!(sender.sent.is_empty());
+        assert_eq!(sender.pending.len(), 1);
+        sender.prune(12 + REPORT_VALIDITY_BLOCKS);
+        assert!(sender.pending.is_empty());
+    }
+
+    #[test]
+    fn test_malice_reports_outside_of_window_dropped() {
+        let mut sender = MaliceReportSender::new(ContractAddresses::default());
+        let malicious = Address::from_low_u64_be(1);
+        let latest = 2 * REPORT_VALIDITY_BLOCKS;
+        sender.queue_report(
+            malicious,
+            evidence(latest + 11, "too far ahead"),
+            latest,
+            latest + 10,
+        );
+        sender.queue_report(
+            malicious,
+            evidence(REPORT_VALIDITY_BLOCKS - 1, "too old"),
+            latest,
+            latest + 10,
+        );
+        assert!(sender.pending.is_empty());
+
+        sender.queue_report(
+            malicious,
+            evidence(latest + 10, "ahead"),
+            latest,
+            latest + 10,
+        );
+        sender.queue_report(
+            malicious,
+            evidence(REPORT_VALIDITY_BLOCKS, "old"),
+            latest,
+            latest + 10,
+        );
+        assert_eq!(sender.pending.len(), 2);
+    }
+
+    #[test]
+    fn test_malice_report_proof_decodes() {
+        let proof = bincode::serialize(&evidence(10, "fault")).unwrap();
+        let decoded: FaultEvidence = bincode::deserialize(&proof).unwrap();
+        assert_eq!(decoded.block, 10);
+        assert_eq!(decoded.kind, "fault");
+        assert!(decoded.message.is_none());
+    }
+
+    #[test]
+    fn test_malice_reports_rate_limited() {
+        let mut sender = MaliceReportSender::new(ContractAddresses::default());
+        assert_eq!(sender.remaining_reports(5), MAX_REPORTS_PER_BLOCK);
+        sender.sent_at_block.1 = MAX_REPORTS_PER_BLOCK;
+        assert_eq!(sender.remaining_reports(5), 0);
+        assert_eq!(sender.remaining_reports(6), MAX_REPORTS_PER_BLOCK);
+    }
+}
@@ -0,0 +1,311 @@
+use serde::{de::DeserializeOwned, Serialize};
+
+/// Wire encodings of hbbft consensus messages.
+///
+/// JSON encoded messages always start with `{`, binary encoded messages start with a
+/// version tag, which allows decoding both encodings without negotiating in advance.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum MessageEncoding {
+    /// Legacy `serde_json` encoding.
+    Json,
+    /// `bincode` encoding, prefixed with `BINCODE_V1_TAG`.
+    BincodeV1,
+}
+
+/// First byte of every JSON encoded message.
+const JSON_TAG: u8 = b'{';
+
+/// Version tag prepended to `bincode` encoded messages.
+const BINCODE_V1_TAG: u8 = 0x01;
+
+/// Serializes the message using the given encoding.
+pub(crate) fn encode<T: Serialize>(
+    message: &T,
+    encoding: MessageEncoding,
+) -> Result<Vec<u8>, String> {
+    match encoding {
+        MessageEncoding::Json => serde_json::to_vec(message).map_err(|e| e.to_string()),
+        MessageEncoding::BincodeV1 => {
+            let mut bytes = vec![BINCODE_V1_TAG];
+            bincode::serialize_into(&mut bytes, message).map_err(|e| e.to_string())?;
+            Ok(bytes)
+        }
+    }
+}
+
+/// Deserializes a message, detecting its encoding.
+///
+/// A message never decodes from more bytes than were received, so the size limit of `bincode`
+/// is set to the received bytes, which rejects lengths that claim more data than was sent.
+pub(crate) fn decode<T: DeserializeOwned>(bytes: &[u8]) -> Result<(T, MessageEncoding), String> {
+    match bytes.first() {
+        Some(&JSON_TAG) => serde_json::from_slice(bytes)
+            .map(|message| (message, MessageEncoding::Json))
+            .map_err(|e| e.to_string()),
+        Some(&BINCODE_V1_TAG) => bincode::config()
+            .limit(bytes.len() as u64 - 1)
+            .deserialize(&bytes[1..])
+            .map(|message| (message, MessageEncoding::BincodeV1))
+            .map_err(|e| e.to_string()),
+        Some(tag) => Err(format!("Unknown consensus message encoding tag: {}", tag)),
+        None => Err("Empty consensus message".into()),
+    }
+}
+
+/// A message that is serialized lazily, at most once per encoding.
+pub(crate) struct EncodedMessage<'a, T: Serialize> {
+    message: &'a T,
+    json: Option<Vec<u8>>,
+    bincode: Option<Vec<u8>>,
+}
+
+impl<'a, T: Serialize> EncodedMessage<'a, T> {
+    pub fn new(message: &'a T) -> Self {
+        EncodedMessage {
+            message,
+            json: None,
+            bincode: None,
+        }
+    }
+
+    /// Returns the message serialized with the given encoding.
+    pub fn get(&mut self, encoding: MessageEncoding) -> Result<Vec<u8>, String> {
+        let cached = match encoding {
+            MessageEncoding::Json => &mut self.json,
+            MessageEncoding::BincodeV1 => &mut self.bincode,
+        };
+        if cached.is_none() {
+            *cached = Some(encode(self.message, encoding)?);
+        }
+        Ok(cached.clone().expect("cached encoding was just set; qed"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crypto::publickey::{Generator, Random};
+    use engines::hbbft::{
+        contribution::{unix_now_secs, Contribution, DEFAULT_RANDOM_BYTES_PER_EPOCH},
+        hbbft_metrics::MessageKind,
+        hbbft_state::HbMessage,
+        sealing::Sealing,
+        test::create_transactions::create_transaction,
+        NodeId,
+    };
+    use ethereum_types::U256;
+    use hbbft::{honey_badger::HoneyBadger, NetworkInfo, Target};
+    use rand_065;
+    use std::{collections::BTreeMap, sync::Arc};
+    use types::transaction::SignedTransaction;
+
+    /// Produces the messages the first validator of a network of `num_nodes` sends
+    /// when proposing a contribution with `num_txns` transactions.
+    fn proposal_messages(num_nodes: usize, num_txns: usize) -> Vec<HbMessage> {
+        let mut rng = rand_065::thread_rng();
+        let ids: Vec<NodeId> = (0..num_nodes)
+            .map(|_| NodeId(*Random.generate().public()))
+            .collect();
+        let net_infos = NetworkInfo::generate_map(ids.clone(), &mut rng)
+            .expect("NetworkInfo generation is expected to always succeed");
+        let mut honey_badger: HoneyBadger<Contribution, NodeId> =
+            HoneyBadger::builder(Arc::new(net_infos[&ids[0]].clone())).build();
+
+        let keypair = Random.generate();
+        let txns: Vec<SignedTransaction> = (0..num_txns)
+            .map(|n| create_transaction(&keypair, &U256::from(n)))
+            .collect();
+        let step = honey_badger
+            .propose(
+                &Contribution::new(
+                    &txns,
+                    DEFAULT_RANDOM_BYTES_PER_EPOCH,
+                    unix_now_secs(),
+                    &mut rng,
+                ),
+                &mut rng,
+            )
+            .expect("Proposing a contribution must succeed");
+        step.messages.into_iter().map(|m| m.message).collect()
+    }
+
+    #[test]
+    fn test_message_roundtrip_and_detection() {
+        let messages = proposal_messages(4, 1);
+        assert!(!messages.is_empty());
+        for encoding in &[MessageEncoding::Json, MessageEncoding::BincodeV1] {
+            for message in &messages {
+                let bytes = encode(message, *encoding).expect("Encoding must succeed");
+                let (decoded, detected): (HbMessage, _) =
+                    decode(&bytes).expect("Decoding must succeed");
+                assert_eq!(detected, *encoding);
+                assert_eq!(
+                    serde_json::to_vec(&decoded).unwrap(),
+                    serde_json::to_vec(message).unwrap()
+                );
+            }
+        }
+        assert!(decode::<HbMessage>(&[0xff, 0x00]).is_err());
+        assert!(decode::<HbMessage>(&[]).is_err());
+    }
+
+    #[test]
+    fn test_decode_rejects_lengths_beyond_received_bytes() {
+        let mut bytes = vec![BINCODE_V1_TAG];
+        bytes.extend_from_slice(&u64::max_value().to_le_bytes());
+        bytes.extend_from_slice(&[0u8; 16]);
+        assert!(decode::<Vec<u8>>(&bytes).is_err());
+
+        let bytes = encode(&vec![7u8; 16], MessageEncoding::BincodeV1).unwrap();
+        assert_eq!(decode::<Vec<u8>>(&bytes).unwrap().0, vec![7u8; 16]);
+    }
+
+    /// Number and total wire size of the sent messages of one kind, in both encodings.
+    #[derive(Default)]
+    struct MessageSizes {
+        count: usize,
+        json: usize,
+        bincode: usize,
+    }
+
+    impl MessageSizes {
+        fn add<T: Serialize>(&mut self, message: &T) {
+            self.count += 1;
+            self.json += encode(message, MessageEncoding::Json)
+                .expect("Encoding must succeed")
+                .len();
+            self.bincode += encode(message, MessageEncoding::BincodeV1)
+                .expect("Encoding must succeed")
+                .len();
+        }
+    }
+
+    /// Returns the nodes other than the sender a message with the given target is sent to.
+    fn recipients(target: &Target<NodeId>, sender: &NodeId, ids: &[NodeId]) -> Vec<NodeId> {
+        ids.iter()
+            .filter(|id| *id != sender)
+            .filter(|id| match target {
+                Target::Nodes(set) => set.contains(id),
+                Target::AllExcept(set) => !set.contains(id),
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// Runs the broadcast and the agreement on a contribution with `num_txns` transactions of
+    /// the first of `num_nodes` validators, and the sealing of a block.
+    /// Returns the sizes of the sent messages by kind. Decryption shares are recorded but not
+    /// delivered, verifying them takes too long for large networks.
+    fn message_sizes(num_nodes: usize, num_txns: usize) -> BTreeMap<String, MessageSizes> {
+        let mut rng = rand_065::thread_rng();
+        let ids: Vec<NodeId> = (0..num_nodes)
+            .map(|_| NodeId(*Random.generate().public()))
+            .collect();
+        let net_infos = NetworkInfo::generate_map(ids.clone(), &mut rng)
+            .expect("NetworkInfo generation is expected to always succeed");
+        let mut nodes: BTreeMap<NodeId, HoneyBadger<Contribution, NodeId>> = ids
+            .iter()
+            .map(|id| {
+                let net_info = Arc::new(net_infos[id].clone());
+                (id.clone(), HoneyBadger::builder(net_info).build())
+            })
+            .collect();
+        let mut sizes: BTreeMap<String, MessageSizes> = BTreeMap::new();
+
+        let keypair = Random.generate();
+        let txns: Vec<SignedTransaction> = (0..num_txns)
+            .map(|n| create_transaction(&keypair, &U256::from(n)))
+            .collect();
+        let contribution = Contribution::new(
+            &txns,
+            DEFAULT_RANDOM_BYTES_PER_EPOCH,
+            unix_now_secs(),
+            &mut rng,
+        );
+        let step = nodes
+            .get_mut(&ids[0])
+            .expect("The proposer is a node")
+            .propose(&contribution, &mut rng)
+            .expect("Proposing a contribution must succeed");
+
+        let mut steps = vec![(ids[0].clone(), step)];
+        while let Some((sender, step)) = steps.pop() {
+            for m in step.messages {
+                let kind = MessageKind::of_hb_message(&m.message);
+                sizes
+                    .entry(format!("{:?}", kind))
+                    .or_default()
+                    .add(&m.message);
+                if kind == MessageKind::DecryptionShare {
+                    continue;
+                }
+                for recipient in recipients(&m.target, &sender, &ids) {
+                    let step = nodes
+                        .get_mut(&recipient)
+                        .expect("Messages are sent to nodes only")
+                        .handle_message(&sender, m.message.clone())
+                        .expect("Handling a valid message must succeed");
+                    steps.push((recipient, step));
+                }
+            }
+        }
+
+        for id in &ids {
+            let step = Sealing::new(net_infos[id].clone())
+                .sign([1u8; 32])
+                .expect("Signing must succeed");
+            for m in step.messages {
+                sizes.entry("Sealing".into()).or_default().add(&m.message);
+            }
+        }
+        sizes
+    }
+
+    #[test]
+    fn test_bincode_is_smaller_than_json() {
+        let message = proposal_messages(4, 1)
+            .into_iter()
+            .next()
+            .expect("Proposing sends a message");
+        let json = encode(&message, MessageEncoding::Json).expect("Encoding must succeed");
+        let bincode = encode(&message, MessageEncoding::BincodeV1).expect("Encoding must succeed");
+        assert!(bincode.len() < json.len());
+    }
+
+    /// Benchmarks the wire size of both encodings for the message kinds of growing networks
+    /// and prints the totals and ratios. Runs full broadcasts and agreements, run it with
+    /// `cargo test -p ethcore test_message_size_json_vs_bincode -- --ignored --nocapture`.
+    #[test]
+    #[ignore]
+    fn test_message_size_json_vs_bincode() {
+        for &num_nodes in &[4, 25, 50] {
+            let sizes = message_sizes(num_nodes, 10);
+            println!("{} validators, 10 transactions:", num_nodes);
+            for (kind, s) in &sizes {
+                println!(
+                    "  {:<16} {:>6} messages, JSON {:>10} bytes, bincode {:>10} bytes, ratio {:.2}",
+                    kind,
+                    s.count,
+                    s.json,
+                    s.bincode,
+                    s.bincode as f64 / s.json as f64
+                );
+                assert!(
+                    s.bincode < s.json,
+                    "{} validators: {} bincode messages are not smaller than JSON",
+                    num_nodes,
+                    kind
+                );
+            }
+            for kind in &[
+                "BroadcastValue",
+                "BroadcastEcho",
+                "BroadcastReady",
+                "DecryptionShare",
+                "Sealing",
+            ] {
+                assert!(sizes.contains_key(*kind), "No {} message was sent", kind);
+            }
+        }
+    }
+}
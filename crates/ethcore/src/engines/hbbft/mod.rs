@@ -1,16 +1,33 @@
 mod block_reward_hbbft;
 mod contracts;
 mod contribution;
+mod epoch_proof;
+mod future_messages_cache;
 mod hbbft_engine;
+mod hbbft_journal;
 mod hbbft_message_memorium;
+mod hbbft_metrics;
 mod hbbft_state;
+mod hbbft_status;
 mod keygen_transactions;
+mod malice_reports;
+mod message_codec;
+mod public_key_cache;
+mod random_hbbft;
 mod sealing;
+mod signer_rotation;
 #[cfg(test)]
 mod test;
 mod utils;
+mod validator_peers;
 
-pub use self::hbbft_engine::HoneyBadgerBFT;
+pub use self::{
+    hbbft_engine::HoneyBadgerBFT,
+    hbbft_status::{
+        BatchStatus, HoneyBadgerStatus, KeygenStatus, PosdaoEpochStatus, SealingStatus,
+        ValidatorPeerStatus, ValidatorScoreStatus, ValidatorStatus,
+    },
+};
 
 use crypto::publickey::Public;
 use std::fmt;
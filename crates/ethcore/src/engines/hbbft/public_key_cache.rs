@@ -0,0 +1,91 @@
+//! Persistent cache of the threshold public master keys of POSDAO epochs.
+//!
+//! Reconstructing the public master key of a past POSDAO epoch requires generating
+//! the full `SyncKeyGen` from the keygen history contract, which is too slow to do for
+//! every block when syncing. The keys are stored in the client database instead,
+//! filled whenever a key is generated or reconstructed.
+
+use client::traits::EngineClient;
+use hbbft::crypto::{PublicKey, PK_SIZE};
+use types::ids::BlockId;
+
+use super::{
+    contracts::{staking::get_posdao_epoch, ContractAddresses},
+    hbbft_state::reconstruct_public_master_key,
+};
+
+const KEY_PREFIX: &[u8] = b"hbbft-public-master-key:";
+
+fn cache_key(posdao_epoch: u64) -> Vec<u8> {
+    let mut key = KEY_PREFIX.to_vec();
+    key.extend_from_slice(&posdao_epoch.to_be_bytes());
+    key
+}
+
+/// Returns the cached public master key of the POSDAO epoch.
+pub(crate) fn cached_public_master_key(
+    client: &dyn EngineClient,
+    posdao_epoch: u64,
+) -> Option<PublicKey> {
+    let bytes = client.engine_data(&cache_key(posdao_epoch))?;
+    if bytes.len() == PK_SIZE {
+        let mut key_array = [0u8; PK_SIZE];
+        key_array.copy_from_slice(&bytes);
+        if let Ok(key) = PublicKey::from_bytes(key_array) {
+            return Some(key);
+        }
+    }
+    warn!(target: "engine", "Invalid public master key cached for POSDAO epoch {}.", posdao_epoch);
+    None
+}
+
+/// Stores the public master key of the POSDAO epoch.
+pub(crate) fn cache_public_master_key(
+    client: &dyn EngineClient,
+    posdao_epoch: u64,
+    key: &PublicKey,
+) {
+    client.set_engine_data(&cache_key(posdao_epoch), key.to_bytes().to_vec());
+}
+
+/// Returns the public master key used to seal the children of the given block,
+/// reconstructing and caching it if it is not cached yet.
+pub(crate) fn public_master_key_at(
+    client: &dyn EngineClient,
+    contracts: &ContractAddresses,
+    block_id: BlockId,
+) -> Option<PublicKey> {
+    let posdao_epoch = get_posdao_epoch(client, contracts, block_id)
+        .ok()?
+        .low_u64();
+    if let Some(key) = cached_public_master_key(client, posdao_epoch) {
+        return Some(key);
+    }
+    let key = reconstruct_public_master_key(client, contracts, block_id)?;
+    trace!(target: "engine", "Caching the public master key of POSDAO epoch {}.", posdao_epoch);
+    cache_public_master_key(client, posdao_epoch, &key);
+    Some(key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use client::TestBlockChainClient;
+    use hbbft::crypto::SecretKey;
+    use rand_065;
+
+    #[test]
+    fn test_public_master_key_cache() {
+        let client = TestBlockChainClient::new();
+        let secret_key: SecretKey = rand_065::random();
+        let key = secret_key.public_key();
+
+        assert_eq!(cached_public_master_key(&client, 3), None);
+        cache_public_master_key(&client, 3, &key);
+        assert_eq!(cached_public_master_key(&client, 3), Some(key));
+        assert_eq!(cached_public_master_key(&client, 4), None);
+
+        client.set_engine_data(&cache_key(4), vec![1, 2, 3]);
+        assert_eq!(cached_public_master_key(&client, 4), None);
+    }
+}
@@ -0,0 +1,89 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of OpenEthereum.
+
+// OpenEthereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// OpenEthereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with OpenEthereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! A client interface for feeding the random seed agreed on by the validators
+//! into the randomness contract.
+
+use engines::{SystemOrCodeCall, SystemOrCodeCallKind};
+use error::Error;
+use ethabi_contract::use_contract;
+use ethereum_types::{Address, U256};
+
+use_contract!(random_hbbft_contract, "res/contracts/random_hbbft.json");
+
+/// A client for the randomness contract.
+#[derive(PartialEq, Debug)]
+pub struct RandomnessContract {
+    kind: SystemOrCodeCallKind,
+}
+
+impl RandomnessContract {
+    /// Create a new randomness contract client targeting the contract address.
+    pub fn new_from_address(address: Address) -> RandomnessContract {
+        RandomnessContract {
+            kind: SystemOrCodeCallKind::Address(address),
+        }
+    }
+
+    /// Sets the seed of the current block, the `caller` must call as the system address.
+    pub fn set_current_seed(&self, caller: &mut SystemOrCodeCall, seed: U256) -> Result<(), Error> {
+        let input = random_hbbft_contract::functions::set_current_seed::encode_input(seed);
+
+        caller(self.kind.clone(), input)
+            .map_err(Into::into)
+            .map_err(::engines::EngineError::FailedSystemCall)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RandomnessContract;
+    use engines::SystemOrCodeCallKind;
+    use ethereum_types::{Address, U256};
+    use hash::keccak;
+
+    #[test]
+    fn test_set_current_seed_encoding() {
+        let address = Address::from_low_u64_be(0x42);
+        let seed = U256::from(0x2a) << 200;
+        let mut calls = Vec::new();
+        let mut call = |kind: SystemOrCodeCallKind, input: Vec<u8>| -> Result<Vec<u8>, String> {
+            calls.push((kind, input));
+            Ok(Vec::new())
+        };
+        RandomnessContract::new_from_address(address)
+            .set_current_seed(&mut call, seed)
+            .expect("The call succeeds");
+
+        let mut seed_bytes = [0u8; 32];
+        seed.to_big_endian(&mut seed_bytes);
+        let mut expected = keccak("setCurrentSeed(uint256)")[..4].to_vec();
+        expected.extend_from_slice(&seed_bytes);
+        assert_eq!(
+            calls,
+            vec![(SystemOrCodeCallKind::Address(address), expected)]
+        );
+
+        let mut reverting_call = |_: SystemOrCodeCallKind, _: Vec<u8>| -> Result<Vec<u8>, String> {
+            Err("reverted".into())
+        };
+        assert!(RandomnessContract::new_from_address(address)
+            .set_current_seed(&mut reverting_call, seed)
+            .is_err());
+    }
+}
@@ -0,0 +1,178 @@
+use client::traits::EngineClient;
+use engines::signer::EngineSigner;
+use ethereum_types::Address;
+use parking_lot::RwLock;
+use std::sync::Arc;
+use types::ids::BlockId;
+
+use super::{
+    contracts::{
+        keygen_history::initialize_synckeygen,
+        staking::get_posdao_epoch_start,
+        validator_set::{
+            get_validator_pubkeys, is_pending_validator, staking_by_mining_address, ValidatorType,
+        },
+        ContractAddresses,
+    },
+    utils::bound_contract::CallError,
+};
+
+/// How a change of the engine signer is applied.
+#[derive(Debug, PartialEq)]
+pub(crate) enum SignerChange {
+    /// The signer is replaced right away, since it holds no key share.
+    Immediate,
+    /// The new signer takes part in the key generation for the pending validator set
+    /// and takes over at the next POSDAO epoch.
+    /// Until then the current signer keeps the key share of the current POSDAO epoch.
+    AtNextEpoch,
+}
+
+/// The state of the current and the new mining address that decides whether the engine signer can be changed.
+#[derive(Debug)]
+pub(crate) struct SignerChangeContext {
+    current_address: Address,
+    new_address: Address,
+    /// Staking address of the pool of the current mining address, zero if there is none.
+    current_staking_address: Address,
+    /// Staking address of the pool of the new mining address, zero if there is none.
+    new_staking_address: Address,
+    /// Whether the current signer holds a key share of the current POSDAO epoch.
+    holds_key_share: bool,
+    /// Whether the current mining address takes part in the key generation for the pending validator set.
+    current_is_pending_validator: bool,
+}
+
+impl SignerChangeContext {
+    pub fn read(
+        client: &dyn EngineClient,
+        contracts: &ContractAddresses,
+        current_address: Address,
+        new_address: Address,
+        holds_key_share: bool,
+    ) -> Result<Self, CallError> {
+        Ok(SignerChangeContext {
+            current_address,
+            new_address,
+            current_staking_address: staking_by_mining_address(
+                client,
+                contracts,
+                &current_address,
+            )?,
+            new_staking_address: staking_by_mining_address(client, contracts, &new_address)?,
+            holds_key_share,
+            current_is_pending_validator: is_pending_validator(
+                client,
+                contracts,
+                &current_address,
+            )?,
+        })
+    }
+
+    /// Returns how the signer is changed, or why it can not be changed now.
+    pub fn plan(&self) -> Result<SignerChange, String> {
+        if self.current_is_pending_validator {
+            return Err(format!(
+                "The current mining address {:?} takes part in the key generation for the pending validator set. \
+                 Change the signer once the next POSDAO epoch started.",
+                self.current_address
+            ));
+        }
+        if !self.holds_key_share {
+            return Ok(SignerChange::Immediate);
+        }
+        if self.new_staking_address.is_zero() {
+            return Err(format!(
+                "The mining address {:?} is not registered with a staking pool. \
+                 It must be the mining address of the pool of the current mining address.",
+                self.new_address
+            ));
+        }
+        if !self.current_staking_address.is_zero()
+            && self.current_staking_address != self.new_staking_address
+        {
+            return Err(format!(
+                "The mining address {:?} belongs to the pool {:?}, not to the pool {:?} of the current mining address.",
+                self.new_address, self.new_staking_address, self.current_staking_address
+            ));
+        }
+        Ok(SignerChange::AtNextEpoch)
+    }
+}
+
+/// Returns whether the signer is a validator of the POSDAO epoch of the given block
+/// and holds a key share of that epoch.
+pub(crate) fn holds_key_share(
+    client: &dyn EngineClient,
+    contracts: &ContractAddresses,
+    signer: &Arc<RwLock<Option<Box<dyn EngineSigner>>>>,
+    block_id: BlockId,
+) -> Result<bool, CallError> {
+    let address = match signer.read().as_ref() {
+        Some(signer) => signer.address(),
+        None => return Ok(false),
+    };
+    let validators = get_validator_pubkeys(client, contracts, block_id, ValidatorType::Current)?;
+    if !validators.contains_key(&address) {
+        return Ok(false);
+    }
+    let posdao_epoch_start = get_posdao_epoch_start(client, contracts, block_id)?;
+    let synckeygen = initialize_synckeygen(
+        client,
+        contracts,
+        signer,
+        BlockId::Number(posdao_epoch_start.low_u64()),
+        ValidatorType::Current,
+    )?;
+    if !synckeygen.is_ready() {
+        return Ok(false);
+    }
+    let (_, key_share) = synckeygen
+        .generate()
+        .map_err(|_| CallError::ReturnValueInvalid)?;
+    Ok(key_share.is_some())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_signer_change_plan() {
+        let pool = Address::from_low_u64_be(1);
+        let context = SignerChangeContext {
+            current_address: Address::from_low_u64_be(2),
+            new_address: Address::from_low_u64_be(3),
+            current_staking_address: Address::zero(),
+            new_staking_address: pool,
+            holds_key_share: true,
+            current_is_pending_validator: false,
+        };
+        assert_eq!(context.plan(), Ok(SignerChange::AtNextEpoch));
+
+        let not_registered = SignerChangeContext {
+            new_staking_address: Address::zero(),
+            ..context
+        };
+        assert!(not_registered.plan().is_err());
+
+        let other_pool = SignerChangeContext {
+            current_staking_address: Address::from_low_u64_be(4),
+            new_staking_address: pool,
+            ..not_registered
+        };
+        assert!(other_pool.plan().is_err());
+
+        let no_key_share = SignerChangeContext {
+            holds_key_share: false,
+            ..other_pool
+        };
+        assert_eq!(no_key_share.plan(), Ok(SignerChange::Immediate));
+
+        let during_keygen = SignerChangeContext {
+            current_is_pending_validator: true,
+            ..no_key_share
+        };
+        assert!(during_keygen.plan().is_err());
+    }
+}
@@ -1,16 +1,18 @@
 use super::create_transactions::{create_call, create_transaction, create_transfer};
+use blockchain::BlockChainDB;
 use client::{
     traits::{Balance, StateOrBlock},
-    BlockChainClient, ChainSyncing, Client, ImportExportBlocks,
+    BlockChainClient, ChainSyncing, Client, ClientConfig, ImportExportBlocks,
 };
 use crypto::publickey::{Generator, KeyPair, Random};
-use engines::signer::from_keypair;
+use engines::{hbbft::contracts::ContractAddresses, signer::from_keypair};
 use ethereum_types::{Address, U256};
+use io::IoChannel;
 use miner::{Miner, MinerService};
 use parking_lot::RwLock;
 use spec::Spec;
 use std::{ops::Deref, sync::Arc};
-use test_helpers::{generate_dummy_client_with_spec, TestNotify};
+use test_helpers::{new_db, TestNotify};
 use types::{data_format::DataFormat, ids::BlockId};
 
 pub fn hbbft_spec() -> Spec {
@@ -21,6 +23,32 @@ pub fn hbbft_spec() -> Spec {
     .expect(concat!("Chain spec is invalid."))
 }
 
+/// The hbbft test chain spec, modified by `modify` before it is loaded.
+pub fn hbbft_spec_with<F: FnOnce(&mut serde_json::Value)>(modify: F) -> Spec {
+    let mut json: serde_json::Value = serde_json::from_slice(include_bytes!(
+        "../../../../res/chainspec/honey_badger_bft.json"
+    ))
+    .expect("Chain spec is valid JSON.");
+    modify(&mut json);
+    Spec::load(
+        &::std::env::temp_dir(),
+        &serde_json::to_vec(&json).expect("Chain spec serializes.") as &[u8],
+    )
+    .expect("Chain spec is invalid.")
+}
+
+/// The hbbft test chain spec with a randomness contract at `address`, which stores the
+/// seed it is called with in its first storage slot.
+pub fn hbbft_spec_with_randomness_contract(address: &Address) -> Spec {
+    hbbft_spec_with(|json| {
+        json["engine"]["hbbft"]["params"]["randomnessContractAddress"] =
+            serde_json::json!({ "0": format!("{:?}", address) });
+        // PUSH1 0x04 CALLDATALOAD PUSH1 0x00 SSTORE STOP
+        json["accounts"][format!("{:?}", address)] =
+            serde_json::json!({ "balance": "0", "code": "0x60043560005500" });
+    })
+}
+
 struct SyncProviderWrapper();
 impl ChainSyncing for SyncProviderWrapper {
     fn is_major_syncing(&self) -> bool {
@@ -28,8 +56,22 @@ impl ChainSyncing for SyncProviderWrapper {
     }
 }
 
-pub fn hbbft_client() -> std::sync::Arc<Client> {
-    let client = generate_dummy_client_with_spec(hbbft_spec);
+/// Creates a client on the given database, which may hold the chain of a previous client.
+pub fn hbbft_client(db: Arc<dyn BlockChainDB>) -> Arc<Client> {
+    hbbft_client_with_spec(hbbft_spec(), db)
+}
+
+/// Creates a client of the given chain spec on the given database.
+pub fn hbbft_client_with_spec(spec: Spec, db: Arc<dyn BlockChainDB>) -> Arc<Client> {
+    let miner = Miner::new_for_tests(&spec, None);
+    let client = Client::new(
+        ClientConfig::default(),
+        &spec,
+        db,
+        Arc::new(miner),
+        IoChannel::disconnected(),
+    )
+    .expect("The client must open the database");
     client.set_sync_provider(Box::new(SyncProviderWrapper()));
     client
 }
@@ -40,6 +82,7 @@ pub struct HbbftTestClient {
     pub notify: Arc<TestNotify>,
     pub miner: Arc<Miner>,
     pub keypair: KeyPair,
+    pub db: Arc<dyn BlockChainDB>,
 }
 
 impl HbbftTestClient {
@@ -113,6 +156,25 @@ impl HbbftTestClient {
             .unwrap();
     }
 
+    /// Returns the addresses of the system contracts used by the engine.
+    pub fn contracts(&self) -> ContractAddresses {
+        self.client
+            .engine()
+            .as_hbbft()
+            .expect("The test client must use the hbbft engine")
+            .contracts()
+            .clone()
+    }
+
+    /// Sends the keygen Part or Acks transactions, as the keygen timer of the engine does.
+    pub fn send_keygen_transactions(&self) {
+        self.client
+            .engine()
+            .as_hbbft()
+            .expect("The test client must use the hbbft engine")
+            .send_keygen_transactions();
+    }
+
     pub fn sync_transactions_to(&self, other: &mut Self) {
         let transactions = self
             .miner
@@ -129,7 +191,21 @@ impl HbbftTestClient {
 }
 
 pub fn create_hbbft_client(keypair: KeyPair) -> HbbftTestClient {
-    let client = hbbft_client();
+    create_hbbft_client_with_db(keypair, new_db())
+}
+
+/// Creates the client of a node on the given database, e.g. the database of a node that is restarted.
+pub fn create_hbbft_client_with_db(keypair: KeyPair, db: Arc<dyn BlockChainDB>) -> HbbftTestClient {
+    create_hbbft_client_with_spec(keypair, hbbft_spec(), db)
+}
+
+/// Creates the client of a node running the given chain spec.
+pub fn create_hbbft_client_with_spec(
+    keypair: KeyPair,
+    spec: Spec,
+    db: Arc<dyn BlockChainDB>,
+) -> HbbftTestClient {
+    let client = hbbft_client_with_spec(spec, db.clone());
     let miner = client.miner();
     let engine = client.engine();
     let signer = from_keypair(keypair.clone());
@@ -143,6 +219,7 @@ pub fn create_hbbft_client(keypair: KeyPair) -> HbbftTestClient {
         notify,
         miner,
         keypair,
+        db,
     }
 }
 
@@ -2,18 +2,35 @@ use super::{
     contracts::{
         staking::{
             get_posdao_epoch, start_time_of_next_phase_transition,
-            tests::{create_staker, is_pool_active},
+            tests::{create_staker, is_pool_active, register_staker},
         },
-        validator_set::{is_pending_validator, mining_by_staking_address},
+        validator_set::{
+            get_validator_pubkeys, is_pending_validator, mining_by_staking_address, ValidatorType,
+        },
+    },
+    contribution::{unix_now_secs, Contribution, DEFAULT_RANDOM_BYTES_PER_EPOCH},
+    epoch_proof::EpochProof,
+    hbbft_state::Batch,
+    public_key_cache::public_master_key_at,
+    test::{
+        create_transactions::create_transaction,
+        hbbft_test_client::{
+            create_hbbft_client, create_hbbft_clients, hbbft_spec, HbbftTestClient,
+        },
+        network_simulator::{Behaviour, NetworkFaults, NetworkSimulator},
     },
-    contribution::unix_now_secs,
-    test::hbbft_test_client::{create_hbbft_client, create_hbbft_clients, HbbftTestClient},
+    NodeId,
 };
 use client::traits::BlockInfo;
 use crypto::publickey::{Generator, KeyPair, Random, Secret};
+use engines::ConstructedVerifier;
 use ethereum_types::{Address, U256};
-use std::str::FromStr;
-use types::ids::BlockId;
+use hbbft::crypto::SecretKey;
+use rand_065;
+use snapshot::tests::helpers as snapshot_helpers;
+use std::{collections::BTreeMap, str::FromStr};
+use test_helpers;
+use types::{header::Header, ids::BlockId};
 
 pub mod create_transactions;
 pub mod hbbft_test_client;
@@ -30,7 +47,7 @@ lazy_static! {
     ///     b. Add '"isUnitTest": true' to the hbbft params section
     ///     c. Fund the new MOC address sufficiently
     /// 5. Copy the private key of the MOC to initialize the MASTER_OF_CEREMONIES_KEYPAIR variable below.
-    static ref MASTER_OF_CEREMONIES_KEYPAIR: KeyPair = KeyPair::from_secret(
+    pub static ref MASTER_OF_CEREMONIES_KEYPAIR: KeyPair = KeyPair::from_secret(
         Secret::from_str("547f6be62482a75406504a45ff530725de3f59387492296f78bd72314d00f856")
             .expect("Secret from hex string must succeed")
     )
@@ -102,14 +119,14 @@ fn test_staking_account_creation() {
     assert_eq!(block.transactions_count(), 1);
 
     assert_ne!(
-        mining_by_staking_address(moc.client.as_ref(), &staker_1.address())
+        mining_by_staking_address(moc.client.as_ref(), &moc.contracts(), &staker_1.address())
             .expect("Constant call must succeed."),
         Address::zero()
     );
 
     // Check if the staking pool is active.
     assert_eq!(
-        is_pool_active(moc.client.as_ref(), staker_1.address())
+        is_pool_active(moc.client.as_ref(), &moc.contracts(), staker_1.address())
             .expect("Pool active query must succeed."),
         true
     );
@@ -135,8 +152,10 @@ fn test_epoch_transition() {
     assert!(genesis_transition_time.as_u64() < unix_now_secs());
 
     // We should not be in the pending validator set at the genesis block.
-    assert!(!is_pending_validator(moc.client.as_ref(), &moc.address())
-        .expect("is_pending_validator call must succeed"));
+    assert!(
+        !is_pending_validator(moc.client.as_ref(), &moc.contracts(), &moc.address())
+            .expect("is_pending_validator call must succeed")
+    );
 
     // Fund the transactor.
     // Also triggers the creation of a block.
@@ -149,36 +168,37 @@ fn test_epoch_transition() {
     assert_eq!(moc.client.chain().best_block_number(), 1);
 
     // Now we should be part of the pending validator set.
-    assert!(is_pending_validator(moc.client.as_ref(), &moc.address())
-        .expect("Constant call must succeed"));
+    assert!(
+        is_pending_validator(moc.client.as_ref(), &moc.contracts(), &moc.address())
+            .expect("Constant call must succeed")
+    );
 
     // Check if we are still in the first epoch.
     assert_eq!(
-        get_posdao_epoch(moc.client.as_ref(), BlockId::Latest).expect("Constant call must succeed"),
+        get_posdao_epoch(moc.client.as_ref(), &moc.contracts(), BlockId::Latest)
+            .expect("Constant call must succeed"),
         U256::from(0)
     );
 
-    // We analyze what happens at each block if the key generation transactions are sent with a 3 block delay,
-    // and why it takes exactly 11 Blocks to complete.
+    // We analyze what happens at each block now that the key generation transactions
+    // are sent by the keygen timer instead of the "on_close" function.
+    // Sending our own transaction triggers the creation of a block right away.
     //
-    // On part writing the following steps happen (remember the transaction send call
-    // happens in "on_close", when the new block is not integrated into the chain state yet!):
-    // Block 1: Client realizes it has to write its part, sets the send delay counter to 1.
-    // Block 2: Client sets the send delay counter to 2
-    // Block 3: Client sets the send delay counter to 3 - sends its Part transaction
-    // Block 4: Part transaction is part of the new block (but not in the chain state yet in the on_close function)
-    // Block 5: Client realizes it has to write its Acks, sets the send delay counter to 1.
-    // Block 6: Client sets the send delay counter to 2
-    // Block 7: Client sets the send delay counter to 3 - sends its Acks transaction
-    // Block 8: Acks transaction is part of the new block (but not in the chain state yet in the on_close function)
-    //   Remember that the Block reward call is also done in the "on_close" function and only respects the state of the *previous* block.
-    //   No epoch transition can happen in Block 10 for that reason, even though all Parts and Acks are sent and part of a block at that point.
-    // Block 9: In the "on_close" function all Parts and Acks are now on the chain state and the block reward system call request the epoch change.
-    skip_n_blocks(9, &mut moc, &transactor);
+    // Block 2: The keygen timer realizes the client has to write its part and sends its Part transaction.
+    // Block 3: The Part is on the chain state, the keygen timer sends the Acks transaction.
+    //   Remember that the Block reward call is done in the "on_close" function and only respects the state of the *previous* block.
+    //   No epoch transition can happen in Block 3 for that reason, even though all Parts and Acks are sent and part of a block at that point.
+    // Block 4: In the "on_close" function all Parts and Acks are now on the chain state and the block reward system call request the epoch change.
+    moc.send_keygen_transactions();
+    assert_eq!(moc.client.chain().best_block_number(), 2);
+    moc.send_keygen_transactions();
+    assert_eq!(moc.client.chain().best_block_number(), 3);
+    skip_n_blocks(1, &mut moc, &transactor);
 
     // At this point we should be in the new epoch.
     assert_eq!(
-        get_posdao_epoch(moc.client.as_ref(), BlockId::Latest).expect("Constant call must succeed"),
+        get_posdao_epoch(moc.client.as_ref(), &moc.contracts(), BlockId::Latest)
+            .expect("Constant call must succeed"),
         U256::from(1)
     );
 
@@ -265,18 +285,23 @@ fn test_moc_to_first_validator() {
     moc.create_some_transaction(Some(&transactor));
 
     // Now we should be part of the pending validator set.
+    assert!(is_pending_validator(
+        moc.client.as_ref(),
+        &moc.contracts(),
+        &validator_1.address()
+    )
+    .expect("Constant call must succeed"));
+    // ..and the MOC should not be a pending validator.
     assert!(
-        is_pending_validator(moc.client.as_ref(), &validator_1.address())
+        !is_pending_validator(moc.client.as_ref(), &moc.contracts(), &moc.address())
             .expect("Constant call must succeed")
     );
-    // ..and the MOC should not be a pending validator.
-    assert!(!is_pending_validator(moc.client.as_ref(), &moc.address())
-        .expect("Constant call must succeed"));
 
     // Sync blocks from MOC to validator_1.
-    // On importing the last block validator_1 should realize he is the next
-    // validator and generate a Parts transaction.
+    // After importing the last block validator_1 should realize he is the next
+    // validator and generate a Parts transaction on the next keygen timer tick.
     moc.sync_blocks_to(&mut validator_1);
+    validator_1.send_keygen_transactions();
 
     // validator_1 created a transaction to write its part, but it is not
     // the current validator and cannot create a block.
@@ -286,7 +311,6 @@ fn test_moc_to_first_validator() {
 
     // Write another dummy block to give validator_1 the chance to realize he wrote
     // his Part already so he sends his Acks.
-    // Due to the Parts/Acks sending delay of 3 blocks we have to inject 3 blocks here
     moc.create_some_transaction(Some(&transactor));
     moc.create_some_transaction(Some(&transactor));
     moc.create_some_transaction(Some(&transactor));
@@ -294,6 +318,7 @@ fn test_moc_to_first_validator() {
     // At this point the transaction from validator_1 has written its Keygen part,
     // and we need to sync the new blocks from moc to validator_1.
     moc.sync_blocks_to(&mut validator_1);
+    validator_1.send_keygen_transactions();
 
     // At this point validator_1 realizes his Part is included on the chain and
     // generates a transaction to write it Acks.
@@ -349,3 +374,422 @@ fn test_initialize_n_validators() {
         fund_amount
     );
 }
+
+/// Number of validators of the simulated networks, tolerating one faulty validator.
+const SIMULATED_VALIDATORS: usize = 4;
+
+/// Returns the POSDAO epoch of the latest block of the node.
+fn posdao_epoch_of(sim: &NetworkSimulator, index: usize) -> U256 {
+    get_posdao_epoch(
+        sim.node(index).client.as_ref(),
+        &sim.node(index).contracts(),
+        BlockId::Latest,
+    )
+    .expect("Constant call must succeed")
+}
+
+/// Stakes `SIMULATED_VALIDATORS` validators and simulates the network until they took over
+/// from the MOC, which is node 0 of the returned simulator.
+fn simulate_validator_network(seed: u64, faults: NetworkFaults) -> NetworkSimulator {
+    let mut sim = NetworkSimulator::new(seed, faults);
+    sim.add_node(MASTER_OF_CEREMONIES_KEYPAIR.clone());
+    let transactor = sim.random_keypair();
+    sim.node_mut(0).transfer_to(
+        &transactor.address(),
+        &U256::from_dec_str("1000000000000000000000000").unwrap(),
+    );
+
+    let transaction_funds = U256::from(9000000000000000000u64);
+    for _ in 0..SIMULATED_VALIDATORS {
+        let keypair = sim.random_keypair();
+        let index = sim.add_node(keypair);
+        let validator = sim.node(index).clone();
+        let staker = sim.random_keypair();
+        let moc = sim.node_mut(0);
+        moc.transfer(&transactor, &validator.address(), &transaction_funds);
+        register_staker(moc, &transactor, &validator, &staker, transaction_funds);
+    }
+    sim.set_transaction_sender(Some(transactor));
+
+    // The validators complete the key generation and the POSDAO contracts switch to the new validator set.
+    sim.run_until(300, |sim| {
+        (0..=SIMULATED_VALIDATORS).all(|i| posdao_epoch_of(sim, i) >= U256::from(1))
+    })
+    .expect("The epoch switch must happen");
+
+    let validators = get_validator_pubkeys(
+        sim.node(0).client.as_ref(),
+        &sim.node(0).contracts(),
+        BlockId::Latest,
+        ValidatorType::Current,
+    )
+    .expect("Constant call must succeed");
+    assert_eq!(validators.len(), SIMULATED_VALIDATORS);
+    assert!(!validators.contains_key(&sim.node(0).address()));
+    sim
+}
+
+/// Runs the simulation until the network sealed the given number of additional blocks.
+fn run_for_blocks(sim: &mut NetworkSimulator, blocks: u64, max_steps: u64) {
+    let target = sim.best_block_number() + blocks;
+    sim.run_until(max_steps, |sim| sim.best_block_number() >= target)
+        .expect("The network must seal new blocks");
+}
+
+#[test]
+fn test_simulator_liveness_with_message_faults() {
+    let faults = NetworkFaults {
+        duplicate_probability: 0.1,
+        max_delay: 3,
+        reorder: true,
+        ..Default::default()
+    };
+    let mut sim = simulate_validator_network(1, faults);
+    run_for_blocks(&mut sim, 5, 200);
+    assert!(sim.stats.duplicated > 0);
+}
+
+#[test]
+fn test_simulator_liveness_across_posdao_epochs() {
+    let faults = NetworkFaults {
+        duplicate_probability: 0.1,
+        max_delay: 2,
+        reorder: true,
+        ..Default::default()
+    };
+    let mut sim = simulate_validator_network(6, faults);
+
+    // Every later POSDAO epoch requires the validators to complete another key generation.
+    let target_epoch = posdao_epoch_of(&sim, 1) + 2;
+    sim.run_until(500, |sim| {
+        (1..=SIMULATED_VALIDATORS).all(|i| posdao_epoch_of(sim, i) >= target_epoch)
+    })
+    .expect("The validators must switch to the later POSDAO epochs");
+    run_for_blocks(&mut sim, 3, 100);
+}
+
+#[test]
+fn test_simulator_is_deterministic() {
+    let faults = NetworkFaults {
+        duplicate_probability: 0.1,
+        max_delay: 2,
+        reorder: true,
+        ..Default::default()
+    };
+    let run = || {
+        let mut sim = simulate_validator_network(7, faults.clone());
+        for _ in 0..10 {
+            sim.step().expect("Sealed blocks must not conflict");
+        }
+        let chain = sim.node(0).client.chain();
+        (chain.best_block_number(), chain.best_block_hash())
+    };
+    assert_eq!(run(), run());
+}
+
+#[test]
+fn test_simulator_safety_with_dropped_messages() {
+    let mut sim = simulate_validator_network(2, NetworkFaults::default());
+    sim.set_faults(NetworkFaults {
+        drop_probability: 0.05,
+        max_delay: 2,
+        reorder: true,
+        ..Default::default()
+    });
+    // Dropped messages may stall the consensus, but must never lead to conflicting blocks.
+    for _ in 0..50 {
+        sim.step().expect("Sealed blocks must not conflict");
+    }
+    assert!(sim.stats.dropped > 0);
+}
+
+#[test]
+fn test_simulator_crash_and_restart() {
+    let mut sim = simulate_validator_network(3, NetworkFaults::default());
+
+    // One crashed validator is tolerated.
+    let crashed_at = sim
+        .node(SIMULATED_VALIDATORS)
+        .client
+        .chain()
+        .best_block_number();
+    sim.crash(SIMULATED_VALIDATORS);
+    run_for_blocks(&mut sim, 3, 100);
+
+    // The restarted validator keeps its chain, catches up and takes part in the consensus again.
+    sim.restart(SIMULATED_VALIDATORS);
+    assert_eq!(
+        sim.node(SIMULATED_VALIDATORS)
+            .client
+            .chain()
+            .best_block_number(),
+        crashed_at
+    );
+    sim.run_until(100, |sim| {
+        sim.node(SIMULATED_VALIDATORS)
+            .client
+            .chain()
+            .best_block_number()
+            == sim.best_block_number()
+    })
+    .expect("The restarted validator must sync the blocks");
+    sim.crash(1);
+    run_for_blocks(&mut sim, 3, 100);
+}
+
+#[test]
+fn test_simulator_partition() {
+    let mut sim = simulate_validator_network(4, NetworkFaults::default());
+
+    // Neither side of the partition has enough validators to seal blocks.
+    sim.partition(&[&[1, 2], &[3, 4]]);
+    // Blocks of batches that were output before the partition may still be sealed.
+    for _ in 0..5 {
+        sim.step().expect("Sealed blocks must not conflict");
+    }
+    let best_block = sim.best_block_number();
+    for _ in 0..20 {
+        sim.step().expect("Sealed blocks must not conflict");
+    }
+    assert_eq!(sim.best_block_number(), best_block);
+
+    sim.heal();
+    run_for_blocks(&mut sim, 3, 100);
+}
+
+#[test]
+fn test_simulator_malicious_validator() {
+    let mut sim = simulate_validator_network(5, NetworkFaults::default());
+
+    sim.set_behaviour(SIMULATED_VALIDATORS, Behaviour::CorruptMessages);
+    run_for_blocks(&mut sim, 3, 100);
+    assert!(sim.stats.rejected > 0);
+
+    sim.set_behaviour(SIMULATED_VALIDATORS, Behaviour::ReplayMessages);
+    run_for_blocks(&mut sim, 3, 100);
+
+    sim.set_behaviour(SIMULATED_VALIDATORS, Behaviour::Silent);
+    run_for_blocks(&mut sim, 3, 100);
+}
+
+#[test]
+fn test_simulator_honey_badger_contributors() {
+    let mut sim = simulate_validator_network(8, NetworkFaults::default());
+    let contributors_of = |sim: &NetworkSimulator, index: usize| {
+        sim.node(index)
+            .client
+            .engine()
+            .as_hbbft()
+            .expect("The test chain spec uses the HoneyBadgerBFT engine")
+            .honey_badger_status()
+            .contributors
+    };
+
+    // The contributions of an epoch are broadcast before the block is sealed.
+    sim.run_until(100, |sim| {
+        (1..=SIMULATED_VALIDATORS).any(|i| !contributors_of(sim, i).is_empty())
+    })
+    .expect("The validators must contribute to the current epoch");
+
+    let validators: Vec<Address> = (1..=SIMULATED_VALIDATORS)
+        .map(|i| sim.node(i).address())
+        .collect();
+    for i in 1..=SIMULATED_VALIDATORS {
+        for contributor in contributors_of(&sim, i) {
+            assert!(validators.contains(&contributor));
+        }
+    }
+    assert!(contributors_of(&sim, 0).is_empty());
+}
+
+#[test]
+fn test_simulator_validator_scores() {
+    let mut sim = simulate_validator_network(9, NetworkFaults::default());
+    let silent = SIMULATED_VALIDATORS;
+    sim.set_behaviour(silent, Behaviour::Silent);
+    run_for_blocks(&mut sim, 5, 200);
+
+    // Honest validators take part in every broadcast, even if their contribution is left out
+    // of the batch, only the silent validator is scored for its missing contributions.
+    for i in 1..silent {
+        let scores = sim
+            .node(i)
+            .client
+            .engine()
+            .as_hbbft()
+            .expect("The test chain spec uses the HoneyBadgerBFT engine")
+            .validator_scores();
+        for score in &scores {
+            if score.mining_address == sim.node(silent).address() {
+                assert!(score.missing_contributions > 0);
+            } else {
+                assert_eq!(score.missing_contributions, 0);
+            }
+        }
+        assert!(scores
+            .iter()
+            .any(|score| score.mining_address == sim.node(silent).address()));
+    }
+}
+
+/// Creates a batch for the given epoch with a single contribution of the client,
+/// containing a transaction of the transactor.
+fn create_batch(client: &HbbftTestClient, epoch: u64, transactor: &KeyPair, nonce: u64) -> Batch {
+    let transaction = create_transaction(transactor, &U256::from(nonce));
+    let mut contributions = BTreeMap::new();
+    contributions.insert(
+        NodeId(*client.keypair.public()),
+        Contribution::new(
+            &vec![transaction],
+            DEFAULT_RANDOM_BYTES_PER_EPOCH,
+            unix_now_secs(),
+            &mut rand_065::thread_rng(),
+        ),
+    );
+    Batch {
+        epoch,
+        contributions,
+    }
+}
+
+#[test]
+fn test_multiple_batch_outputs() {
+    let mut moc = create_hbbft_client(MASTER_OF_CEREMONIES_KEYPAIR.clone());
+    let transactor: KeyPair = Random.generate();
+    moc.transfer_to(&transactor.address(), &U256::from(9000000000000000000u64));
+    let best_block = moc.client.chain().best_block_number();
+
+    let engine = moc.client.engine();
+    let hbbft = engine.as_hbbft().expect("The hbbft engine must be used");
+    let network_info = hbbft
+        .network_info_for(best_block + 1)
+        .expect("The MOC must be a validator");
+
+    // A single step outputs the batches of three epochs, in any order.
+    let batches = (0..3)
+        .rev()
+        .map(|n| create_batch(&moc, best_block + 1 + n, &transactor, n))
+        .collect();
+    hbbft.process_output(moc.client.clone(), batches, &network_info);
+
+    // The blocks are created one after another.
+    assert_eq!(moc.client.chain().best_block_number(), best_block + 3);
+    for n in 1..=3 {
+        let block = moc
+            .client
+            .block(BlockId::Number(best_block + n))
+            .expect("Block must exist");
+        assert_eq!(block.transactions_count(), 1);
+    }
+
+    // Batches of blocks that are already part of the chain are ignored.
+    let batches = vec![create_batch(&moc, best_block + 3, &transactor, 3)];
+    hbbft.process_output(moc.client.clone(), batches, &network_info);
+    assert_eq!(moc.client.chain().best_block_number(), best_block + 3);
+}
+
+#[test]
+fn test_multiple_batch_outputs_across_posdao_epoch_switch() {
+    let mut moc = create_hbbft_client(MASTER_OF_CEREMONIES_KEYPAIR.clone());
+    let transactor: KeyPair = Random.generate();
+    moc.transfer_to(&transactor.address(), &U256::from(9000000000000000000u64));
+
+    // Write the Part and Acks of the MOC, block 4 switches to the next POSDAO epoch,
+    // see `test_epoch_transition`.
+    moc.send_keygen_transactions();
+    moc.send_keygen_transactions();
+    assert_eq!(moc.client.chain().best_block_number(), 3);
+
+    let engine = moc.client.engine();
+    let hbbft = engine.as_hbbft().expect("The hbbft engine must be used");
+    let network_info = hbbft
+        .network_info_for(4)
+        .expect("The MOC must be a validator");
+
+    // The batch of epoch 5 was output by the validator set of POSDAO epoch 0,
+    // but block 5 is in charge of the validator set of POSDAO epoch 1.
+    let batches = vec![
+        create_batch(&moc, 4, &transactor, 0),
+        create_batch(&moc, 5, &transactor, 1),
+    ];
+    hbbft.process_output(moc.client.clone(), batches, &network_info);
+
+    assert_eq!(moc.client.chain().best_block_number(), 4);
+    assert_eq!(
+        get_posdao_epoch(moc.client.as_ref(), &moc.contracts(), BlockId::Latest)
+            .expect("Constant call must succeed"),
+        U256::from(1)
+    );
+
+    // The new validator set creates block 5.
+    moc.create_some_transaction(Some(&transactor));
+    assert_eq!(moc.client.chain().best_block_number(), 5);
+}
+
+#[test]
+fn test_snapshot_round_trip() {
+    let mut moc = create_hbbft_client(MASTER_OF_CEREMONIES_KEYPAIR.clone());
+    let transactor: KeyPair = Random.generate();
+    moc.transfer_to(&transactor.address(), &U256::from(9000000000000000000u64));
+
+    // Block 4 switches to the next POSDAO epoch, see `test_epoch_transition`.
+    moc.send_keygen_transactions();
+    moc.send_keygen_transactions();
+    skip_n_blocks(2, &mut moc, &transactor);
+    assert_eq!(
+        get_posdao_epoch(moc.client.as_ref(), &moc.contracts(), BlockId::Latest)
+            .expect("Constant call must succeed"),
+        U256::from(1)
+    );
+
+    // The restoring engine verifies the first transition against its own genesis validator set,
+    // and every later transition against the key of the previous epoch.
+    let (reader, _tempdir) = snapshot_helpers::snap(&*moc.client);
+    let restoring = create_hbbft_client(Random.generate());
+    snapshot_helpers::restore(
+        test_helpers::new_db(),
+        &*restoring.client.engine(),
+        &*reader,
+        &hbbft_spec().genesis_block(),
+    )
+    .expect("Restoring the snapshot must succeed");
+}
+
+#[test]
+fn test_initial_epoch_proof_must_match_genesis_key() {
+    let moc = create_hbbft_client(MASTER_OF_CEREMONIES_KEYPAIR.clone());
+    let engine = moc.client.engine();
+    let genesis_key =
+        public_master_key_at(moc.client.as_ref(), &moc.contracts(), BlockId::Number(0))
+            .expect("The genesis key must be known");
+    let mut header = Header::new();
+    header.set_number(1);
+
+    let valid_proof = EpochProof {
+        posdao_epoch: 0,
+        public_master_key: genesis_key,
+    }
+    .encode(None);
+    match engine.epoch_verifier(&header, &valid_proof) {
+        ConstructedVerifier::Trusted(_) => {}
+        _ => panic!("The genesis key must be trusted"),
+    }
+
+    let secret_key: SecretKey = rand_065::random();
+    let forged_proof = EpochProof {
+        posdao_epoch: 0,
+        public_master_key: secret_key.public_key(),
+    }
+    .encode(None);
+    match engine.epoch_verifier(&header, &forged_proof) {
+        ConstructedVerifier::Err(_) => {}
+        _ => panic!("A key not matching the genesis validator set must be rejected"),
+    }
+
+    // Only the first transition may lack the sealed transition header.
+    header.set_number(2);
+    match engine.epoch_verifier(&header, &valid_proof) {
+        ConstructedVerifier::Err(_) => {}
+        _ => panic!("A later transition without the sealed transition header must be rejected"),
+    }
+}
@@ -1,6 +1,14 @@
-use engines::hbbft::test::hbbft_test_client::HbbftTestClient;
+use bytes::Bytes;
+use client::BlockChainClient;
+use crypto::publickey::KeyPair;
+use engines::hbbft::test::hbbft_test_client::{
+    create_hbbft_client, create_hbbft_client_with_db, HbbftTestClient,
+};
+use ethereum_types::{H256, H512};
 use parking_lot::RwLock;
-use std::collections::BTreeMap;
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+use std::collections::{BTreeMap, BTreeSet};
+use types::{ids::BlockId, BlockNumber};
 
 pub fn crank_network(clients: &Vec<RwLock<HbbftTestClient>>) {
     // sync blocks
@@ -70,3 +78,467 @@ fn sync_consensus_messages(clients: &Vec<RwLock<HbbftTestClient>>) {
         }
     }
 }
+
+/// Faults injected into the delivery of consensus messages by the `NetworkSimulator`.
+#[derive(Clone, Debug)]
+pub struct NetworkFaults {
+    /// Probability of a message being dropped.
+    pub drop_probability: f64,
+    /// Probability of a message being delivered twice.
+    pub duplicate_probability: f64,
+    /// Maximum number of steps a message is delayed.
+    pub max_delay: u64,
+    /// Whether the messages due in a step are delivered in random order.
+    pub reorder: bool,
+}
+
+impl Default for NetworkFaults {
+    fn default() -> Self {
+        NetworkFaults {
+            drop_probability: 0.0,
+            duplicate_probability: 0.0,
+            max_delay: 0,
+            reorder: false,
+        }
+    }
+}
+
+/// How a simulated node treats the consensus messages it sends.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Behaviour {
+    /// Sends its messages unmodified.
+    Honest,
+    /// Does not send any messages.
+    Silent,
+    /// Flips a random byte of every message it sends.
+    CorruptMessages,
+    /// Sends its messages and additionally replays messages it sent before to random nodes.
+    ReplayMessages,
+}
+
+/// A violation of the consensus guarantees detected by the `NetworkSimulator`.
+#[derive(Debug, PartialEq)]
+pub enum SimulationError {
+    /// Two nodes sealed different blocks with the same number.
+    ConflictingBlocks {
+        block: BlockNumber,
+        node: usize,
+        expected: H256,
+        found: H256,
+    },
+    /// The condition was not reached within the given number of steps.
+    NoProgress { steps: u64 },
+}
+
+/// Counters of the message deliveries of a simulation.
+#[derive(Debug, Default, Clone)]
+pub struct SimulationStats {
+    pub delivered: u64,
+    pub dropped: u64,
+    pub duplicated: u64,
+    /// Messages the receiving engine returned an error for.
+    pub rejected: u64,
+}
+
+struct SimulatedNode {
+    client: HbbftTestClient,
+    behaviour: Behaviour,
+    crashed: bool,
+    /// Nodes can only communicate with nodes in the same partition.
+    partition: usize,
+    /// Highest block checked for conflicts.
+    checked_block: BlockNumber,
+    /// Messages sent by the node, replayed by nodes with `Behaviour::ReplayMessages`.
+    sent: Vec<(usize, Bytes)>,
+}
+
+impl SimulatedNode {
+    fn is_live(&self) -> bool {
+        !self.crashed
+    }
+}
+
+struct InFlightMessage {
+    from: usize,
+    to: usize,
+    data: Bytes,
+    deliver_at: u64,
+}
+
+/// UNIX time in seconds at the start of a simulation. It lies in the past,
+/// so the blocks of the simulated network are never too far in the future.
+const SIMULATION_START_TIME: u64 = 1_600_000_000;
+
+/// Deterministic simulation of a network of hbbft nodes.
+///
+/// In every step the simulator emulates the keygen timer of each node, delivers the
+/// consensus messages that are due and synchronizes transactions and blocks between
+/// the nodes of the same partition. All network faults, the keys of the nodes and the
+/// randomness of their engines are derived from the seed, and the nodes use a simulated
+/// clock which advances by a second per step. So a failing run can be reproduced by
+/// running it with the same seed again.
+///
+/// After every step the sealed blocks of all nodes are checked for conflicts.
+pub struct NetworkSimulator {
+    rng: StdRng,
+    faults: NetworkFaults,
+    nodes: Vec<SimulatedNode>,
+    in_flight: Vec<InFlightMessage>,
+    step: u64,
+    /// The simulated UNIX time in seconds.
+    time: u64,
+    /// Block hashes sealed by the network, by block number.
+    sealed: BTreeMap<BlockNumber, H256>,
+    transaction_sender: Option<KeyPair>,
+    pub stats: SimulationStats,
+}
+
+impl NetworkSimulator {
+    pub fn new(seed: u64, faults: NetworkFaults) -> Self {
+        NetworkSimulator {
+            rng: StdRng::seed_from_u64(seed),
+            faults,
+            nodes: Vec::new(),
+            in_flight: Vec::new(),
+            step: 0,
+            time: SIMULATION_START_TIME,
+            sealed: BTreeMap::new(),
+            transaction_sender: None,
+            stats: SimulationStats::default(),
+        }
+    }
+
+    /// Creates a keypair derived from the seed of the simulation.
+    pub fn random_keypair(&mut self) -> KeyPair {
+        loop {
+            let secret: [u8; 32] = self.rng.gen();
+            if let Ok(keypair) = KeyPair::from_secret_slice(&secret) {
+                return keypair;
+            }
+        }
+    }
+
+    /// Adds a node with the given keypair and an empty database, returns its index.
+    pub fn add_node(&mut self, keypair: KeyPair) -> usize {
+        let client = create_hbbft_client(keypair);
+        self.start_engine(&client);
+        self.nodes.push(SimulatedNode {
+            client,
+            behaviour: Behaviour::Honest,
+            crashed: false,
+            partition: 0,
+            checked_block: 0,
+            sent: Vec::new(),
+        });
+        self.nodes.len() - 1
+    }
+
+    /// Seeds the randomness of the engine of the client and sets its clock to the simulated time.
+    fn start_engine(&mut self, client: &HbbftTestClient) {
+        let engine = client.client.engine();
+        let engine = engine
+            .as_hbbft()
+            .expect("The simulated nodes must use the hbbft engine");
+        engine.seed_rng(self.rng.gen());
+        engine.set_simulated_time(self.time);
+    }
+
+    pub fn node(&self, index: usize) -> &HbbftTestClient {
+        &self.nodes[index].client
+    }
+
+    pub fn node_mut(&mut self, index: usize) -> &mut HbbftTestClient {
+        &mut self.nodes[index].client
+    }
+
+    pub fn set_behaviour(&mut self, index: usize, behaviour: Behaviour) {
+        self.nodes[index].behaviour = behaviour;
+    }
+
+    pub fn set_faults(&mut self, faults: NetworkFaults) {
+        self.faults = faults;
+    }
+
+    /// Splits the network into the given groups of nodes.
+    /// Nodes not part of any group form a group of their own.
+    /// Messages between groups are held back until the partition is healed.
+    pub fn partition(&mut self, groups: &[&[usize]]) {
+        for node in self.nodes.iter_mut() {
+            node.partition = 0;
+        }
+        for (group, members) in groups.iter().enumerate() {
+            for index in members.iter() {
+                self.nodes[*index].partition = group + 1;
+            }
+        }
+    }
+
+    /// Reconnects all nodes.
+    pub fn heal(&mut self) {
+        for node in self.nodes.iter_mut() {
+            node.partition = 0;
+        }
+    }
+
+    /// Stops the node, messages sent to it are lost.
+    pub fn crash(&mut self, index: usize) {
+        self.nodes[index].crashed = true;
+        self.nodes[index]
+            .client
+            .notify
+            .targeted_messages
+            .write()
+            .clear();
+    }
+
+    /// Restarts a crashed node on its database. The state the node kept in memory,
+    /// e.g. its transaction queue and consensus messages, is lost.
+    /// It syncs the blocks it missed from the other nodes.
+    pub fn restart(&mut self, index: usize) {
+        let keypair = self.nodes[index].client.keypair.clone();
+        let db = self.nodes[index].client.db.clone();
+        let client = create_hbbft_client_with_db(keypair, db);
+        self.start_engine(&client);
+        let node = &mut self.nodes[index];
+        node.client = client;
+        node.crashed = false;
+        node.sent.clear();
+    }
+
+    /// Highest block number of all running nodes.
+    pub fn best_block_number(&self) -> BlockNumber {
+        self.nodes
+            .iter()
+            .filter(|n| n.is_live())
+            .map(|n| n.client.client.chain().best_block_number())
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Sets the sender of the transaction injected at a random running node in every step,
+    /// which keeps the validators creating blocks.
+    pub fn set_transaction_sender(&mut self, sender: Option<KeyPair>) {
+        self.transaction_sender = sender;
+    }
+
+    fn inject_transaction(&mut self) {
+        let sender = match self.transaction_sender {
+            Some(ref sender) => sender.clone(),
+            None => return,
+        };
+        let live: Vec<usize> = (0..self.nodes.len())
+            .filter(|i| self.nodes[*i].is_live())
+            .collect();
+        if let Some(index) = live.choose(&mut self.rng).cloned() {
+            self.nodes[index]
+                .client
+                .create_some_transaction(Some(&sender));
+        }
+    }
+
+    /// Runs a single step of the simulation.
+    pub fn step(&mut self) -> Result<(), SimulationError> {
+        self.time += 1;
+        for node in self.nodes.iter() {
+            node.client
+                .client
+                .engine()
+                .as_hbbft()
+                .expect("The simulated nodes must use the hbbft engine")
+                .set_simulated_time(self.time);
+        }
+        self.inject_transaction();
+        for node in self.nodes.iter().filter(|n| n.is_live()) {
+            node.client.send_keygen_transactions();
+        }
+        self.collect_messages();
+        self.deliver_messages();
+        self.sync_transactions();
+        self.check_safety()?;
+        self.sync_blocks();
+        self.step += 1;
+        Ok(())
+    }
+
+    /// Runs steps until the condition is met, returns the number of steps taken.
+    pub fn run_until<F>(&mut self, max_steps: u64, mut condition: F) -> Result<u64, SimulationError>
+    where
+        F: FnMut(&mut Self) -> bool,
+    {
+        for steps in 0..max_steps {
+            if condition(self) {
+                return Ok(steps);
+            }
+            self.step()?;
+        }
+        if condition(self) {
+            return Ok(max_steps);
+        }
+        Err(SimulationError::NoProgress { steps: max_steps })
+    }
+
+    fn index_of(&self, node_id: &H512) -> Option<usize> {
+        self.nodes
+            .iter()
+            .position(|n| n.client.keypair.public() == node_id)
+    }
+
+    fn collect_messages(&mut self) {
+        for from in 0..self.nodes.len() {
+            let messages: Vec<_> = self.nodes[from]
+                .client
+                .notify
+                .targeted_messages
+                .write()
+                .drain(..)
+                .collect();
+            if !self.nodes[from].is_live() {
+                continue;
+            }
+            for (mut data, target) in messages {
+                let to = match target.and_then(|t| self.index_of(&t)) {
+                    Some(to) => to,
+                    None => continue,
+                };
+                match self.nodes[from].behaviour {
+                    Behaviour::Honest => {}
+                    Behaviour::Silent => continue,
+                    Behaviour::CorruptMessages => {
+                        if !data.is_empty() {
+                            let pos = self.rng.gen_range(0, data.len());
+                            data[pos] ^= 0xff;
+                        }
+                    }
+                    Behaviour::ReplayMessages => {
+                        if let Some((to, replayed)) = self.nodes[from].sent.choose(&mut self.rng) {
+                            let (to, replayed) = (*to, replayed.clone());
+                            self.send(from, to, replayed);
+                        }
+                        self.nodes[from].sent.push((to, data.clone()));
+                    }
+                }
+                self.send(from, to, data);
+            }
+        }
+    }
+
+    fn send(&mut self, from: usize, to: usize, data: Bytes) {
+        if self.rng.gen_bool(self.faults.drop_probability) {
+            self.stats.dropped += 1;
+            return;
+        }
+        if self.rng.gen_bool(self.faults.duplicate_probability) {
+            self.stats.duplicated += 1;
+            let deliver_at = self.step + self.rng.gen_range(0, self.faults.max_delay + 1);
+            self.in_flight.push(InFlightMessage {
+                from,
+                to,
+                data: data.clone(),
+                deliver_at,
+            });
+        }
+        let deliver_at = self.step + self.rng.gen_range(0, self.faults.max_delay + 1);
+        self.in_flight.push(InFlightMessage {
+            from,
+            to,
+            data,
+            deliver_at,
+        });
+    }
+
+    fn deliver_messages(&mut self) {
+        let step = self.step;
+        let nodes = &self.nodes;
+        let (mut due, held): (Vec<_>, Vec<_>) = self.in_flight.drain(..).partition(|m| {
+            m.deliver_at <= step && nodes[m.from].partition == nodes[m.to].partition
+        });
+        self.in_flight = held;
+        if self.faults.reorder {
+            due.shuffle(&mut self.rng);
+        }
+
+        for message in due {
+            let target = &self.nodes[message.to];
+            if !target.is_live() {
+                self.stats.dropped += 1;
+                continue;
+            }
+            let from = *self.nodes[message.from].client.keypair.public();
+            match target
+                .client
+                .client
+                .engine()
+                .handle_message(&message.data, Some(from))
+            {
+                Ok(()) => self.stats.delivered += 1,
+                Err(e) => {
+                    trace!(target: "engine", "Simulated node {} rejected message from {}: {:?}", message.to, message.from, e);
+                    self.stats.rejected += 1;
+                }
+            }
+        }
+    }
+
+    fn sync_transactions(&self) {
+        for (n1, sharer) in self.nodes.iter().enumerate() {
+            for (n2, target) in self.nodes.iter().enumerate() {
+                if n1 != n2
+                    && sharer.is_live()
+                    && target.is_live()
+                    && sharer.partition == target.partition
+                {
+                    sharer
+                        .client
+                        .sync_transactions_to(&mut target.client.clone());
+                }
+            }
+        }
+    }
+
+    /// Verifies that no two nodes sealed different blocks with the same number.
+    fn check_safety(&mut self) -> Result<(), SimulationError> {
+        for (index, node) in self.nodes.iter_mut().enumerate() {
+            if !node.is_live() {
+                continue;
+            }
+            let best = node.client.client.chain().best_block_number();
+            for block in (node.checked_block + 1)..=best {
+                let found = node
+                    .client
+                    .client
+                    .block_hash(BlockId::Number(block))
+                    .expect("Blocks up to the best block must exist");
+                let expected = *self.sealed.entry(block).or_insert(found);
+                if expected != found {
+                    return Err(SimulationError::ConflictingBlocks {
+                        block,
+                        node: index,
+                        expected,
+                        found,
+                    });
+                }
+            }
+            node.checked_block = best;
+        }
+        Ok(())
+    }
+
+    /// Syncs the blocks of the best node of each partition to the other nodes of the partition.
+    fn sync_blocks(&mut self) {
+        let partitions: BTreeSet<usize> = self.nodes.iter().map(|n| n.partition).collect();
+        for partition in partitions {
+            let members: Vec<usize> = (0..self.nodes.len())
+                .filter(|i| self.nodes[*i].is_live() && self.nodes[*i].partition == partition)
+                .collect();
+            let best = members
+                .iter()
+                .max_by_key(|i| self.nodes[**i].client.client.chain().best_block_number())
+                .map(|i| self.nodes[*i].client.clone());
+            if let Some(best) = best {
+                for index in members {
+                    best.sync_blocks_to(&mut self.nodes[index].client);
+                }
+            }
+        }
+    }
+}
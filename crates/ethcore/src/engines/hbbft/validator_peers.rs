@@ -0,0 +1,349 @@
+use client::traits::EngineClient;
+use crypto::publickey::Public;
+use ethereum_types::{Address, U256};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    net::SocketAddr,
+    time::{Duration, Instant},
+};
+use types::ids::BlockId;
+
+use super::{
+    contracts::{
+        staking::{get_pool_internet_address, get_posdao_epoch},
+        validator_set::{
+            get_pending_validators, get_validator_pubkeys, staking_by_mining_address, ValidatorType,
+        },
+        ContractAddresses,
+    },
+    hbbft_status::ValidatorPeerStatus,
+    utils::bound_contract::CallError,
+};
+
+/// Default devp2p port of validators reached at the internet address registered in the staking contract.
+pub(crate) const DEFAULT_VALIDATOR_PORT: u16 = 30303;
+
+/// Time a validator may stay disconnected after it was reserved, before it is reported as unreachable.
+const UNREACHABLE_GRACE_PERIOD: Duration = Duration::from_secs(120);
+
+/// Time after which the endpoints of the validators are looked up again,
+/// even if the validator sets did not change.
+const ENDPOINT_REFRESH_INTERVAL: Duration = Duration::from_secs(600);
+
+/// A validator of the current or the pending validator set.
+struct ValidatorPeer {
+    mining_address: Address,
+    /// The enode URL of the validator, if its endpoint is known.
+    enode: Option<String>,
+    /// The time the validator was reserved.
+    since: Instant,
+}
+
+/// Keeps the validators of the current and the pending validator set connected as reserved peers,
+/// and tracks which of them are connected.
+pub(crate) struct ValidatorPeers {
+    contracts: ContractAddresses,
+    /// Configured endpoints, by mining address.
+    endpoints: BTreeMap<Address, String>,
+    /// Port of the validators whose internet address is read from the staking contract.
+    port: u16,
+    /// The POSDAO epoch and the pending validators the reserved peers were last updated for.
+    updated_for: Option<(U256, Vec<Address>)>,
+    /// The time the endpoints of the validators were last looked up.
+    endpoints_updated: Option<Instant>,
+    /// The validators, by node id.
+    validators: BTreeMap<Public, ValidatorPeer>,
+    /// Node ids of the connected peers.
+    connected: BTreeSet<Public>,
+    /// The validators that were last reported as unreachable.
+    reported_unreachable: BTreeSet<Address>,
+    /// Time a validator may stay disconnected after it was reserved, before it is reported as unreachable.
+    grace_period: Duration,
+}
+
+impl ValidatorPeers {
+    pub fn new(
+        contracts: ContractAddresses,
+        endpoints: BTreeMap<Address, String>,
+        port: u16,
+    ) -> Self {
+        ValidatorPeers {
+            contracts,
+            endpoints,
+            port,
+            updated_for: None,
+            endpoints_updated: None,
+            validators: BTreeMap::new(),
+            connected: BTreeSet::new(),
+            reported_unreachable: BTreeSet::new(),
+            grace_period: UNREACHABLE_GRACE_PERIOD,
+        }
+    }
+
+    pub fn on_peer_connected(&mut self, node_id: Public) {
+        self.connected.insert(node_id);
+    }
+
+    pub fn on_peer_disconnected(&mut self, node_id: Public) {
+        self.connected.remove(&node_id);
+    }
+
+    /// Returns the enode URL of the validator, using the configured endpoint
+    /// or the internet address registered in the staking contract.
+    fn enode(
+        &self,
+        client: &dyn EngineClient,
+        mining_address: &Address,
+        public: &Public,
+    ) -> Result<Option<String>, CallError> {
+        if let Some(endpoint) = self.endpoints.get(mining_address) {
+            return Ok(Some(format!("enode://{:x}@{}", public, endpoint)));
+        }
+        let staking_address = staking_by_mining_address(client, &self.contracts, mining_address)?;
+        Ok(
+            get_pool_internet_address(client, &self.contracts, &staking_address)?
+                .map(|ip| format!("enode://{:x}@{}", public, SocketAddr::new(ip, self.port))),
+        )
+    }
+
+    /// Reserves the validators of the current and the pending validator set as peers,
+    /// and releases the validators that left both sets.
+    /// The validator sets are only read again if the POSDAO epoch or the pending validators changed,
+    /// or if the endpoints were not looked up for the refresh interval.
+    pub fn update(
+        &mut self,
+        client: &dyn EngineClient,
+        our_address: Option<Address>,
+    ) -> Result<(), CallError> {
+        let updated_for = (
+            get_posdao_epoch(client, &self.contracts, BlockId::Latest)?,
+            get_pending_validators(client, &self.contracts)?,
+        );
+        let is_refresh_due = self.endpoints_updated.map_or(true, |updated| {
+            updated.elapsed() >= ENDPOINT_REFRESH_INTERVAL
+        });
+        if self.updated_for.as_ref() == Some(&updated_for) && !is_refresh_due {
+            return Ok(());
+        }
+
+        let mut validators = get_validator_pubkeys(
+            client,
+            &self.contracts,
+            BlockId::Latest,
+            ValidatorType::Current,
+        )?;
+        validators.extend(get_validator_pubkeys(
+            client,
+            &self.contracts,
+            BlockId::Latest,
+            ValidatorType::Pending,
+        )?);
+
+        // Look up all endpoints first, so a failing contract call leaves the reserved peers unchanged.
+        let mut endpoints = BTreeMap::new();
+        for (mining_address, public) in validators {
+            if Some(mining_address) == our_address {
+                continue;
+            }
+            let enode = self.enode(client, &mining_address, &public)?;
+            if enode.is_none() {
+                warn!(target: "engine", "No endpoint known for validator {}, it can not be reserved as peer.", mining_address);
+            }
+            endpoints.insert(public, (mining_address, enode));
+        }
+
+        self.reserve_validators(client, endpoints);
+        debug!(target: "engine", "Reserved {} validators as peers for POSDAO epoch {}.", self.validators.len(), updated_for.0);
+        self.updated_for = Some(updated_for);
+        self.endpoints_updated = Some(Instant::now());
+        Ok(())
+    }
+
+    /// Reserves the given validators as peers, by node id, and releases the other validators.
+    /// A validator whose endpoint changed is released and reserved again with the new endpoint.
+    fn reserve_validators(
+        &mut self,
+        client: &dyn EngineClient,
+        endpoints: BTreeMap<Public, (Address, Option<String>)>,
+    ) {
+        let mut updated = BTreeMap::new();
+        for (public, (mining_address, enode)) in endpoints {
+            let since = match self.validators.remove(&public) {
+                Some(ref peer) if peer.enode == enode => peer.since,
+                Some(peer) => {
+                    debug!(target: "engine", "The endpoint of validator {} changed to {:?}.", mining_address, enode);
+                    self.release(client, &peer);
+                    self.reserve(client, &enode);
+                    Instant::now()
+                }
+                None => {
+                    self.reserve(client, &enode);
+                    Instant::now()
+                }
+            };
+            updated.insert(
+                public,
+                ValidatorPeer {
+                    mining_address,
+                    enode,
+                    since,
+                },
+            );
+        }
+        for peer in self.validators.values() {
+            self.release(client, peer);
+        }
+        self.validators = updated;
+    }
+
+    fn reserve(&self, client: &dyn EngineClient, enode: &Option<String>) {
+        if let Some(enode) = enode {
+            client.add_reserved_peer(enode.clone());
+        }
+    }
+
+    fn release(&self, client: &dyn EngineClient, peer: &ValidatorPeer) {
+        if let Some(ref enode) = peer.enode {
+            client.remove_reserved_peer(enode.clone());
+        }
+    }
+
+    /// Returns the mining addresses of the validators that are not connected,
+    /// although they were reserved longer than the grace period.
+    pub fn unreachable_validators(&self) -> BTreeSet<Address> {
+        self.validators
+            .iter()
+            .filter(|(node_id, peer)| {
+                !self.connected.contains(node_id) && peer.since.elapsed() >= self.grace_period
+            })
+            .map(|(_, peer)| peer.mining_address)
+            .collect()
+    }
+
+    /// Logs the validators that became unreachable or reachable since the last report.
+    pub fn report_unreachable(&mut self) {
+        let unreachable = self.unreachable_validators();
+        if unreachable == self.reported_unreachable {
+            return;
+        }
+        if unreachable.is_empty() {
+            info!(target: "engine", "All validators are connected.");
+        } else {
+            warn!(target: "engine", "Validators not connected: {:?}", unreachable);
+        }
+        self.reported_unreachable = unreachable;
+    }
+
+    /// Returns the connection status of the reserved validators.
+    pub fn status(&self) -> Vec<ValidatorPeerStatus> {
+        self.validators
+            .iter()
+            .map(|(node_id, peer)| ValidatorPeerStatus {
+                mining_address: peer.mining_address,
+                enode: peer.enode.clone(),
+                is_connected: self.connected.contains(node_id),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use client::TestBlockChainClient;
+    use crypto::publickey::{Generator, KeyPair, Random};
+
+    fn enode(keypair: &KeyPair, port: u16) -> String {
+        format!("enode://{:x}@127.0.0.1:{}", keypair.public(), port)
+    }
+
+    fn endpoint(keypair: &KeyPair, port: u16) -> (Public, (Address, Option<String>)) {
+        (
+            *keypair.public(),
+            (keypair.address(), Some(enode(keypair, port))),
+        )
+    }
+
+    fn reserved_peers(client: &TestBlockChainClient) -> Vec<String> {
+        client.reserved_peers.read().iter().cloned().collect()
+    }
+
+    #[test]
+    fn test_validator_peers_reserve_validators() {
+        let client = TestBlockChainClient::new();
+        let mut peers = ValidatorPeers::new(
+            ContractAddresses::default(),
+            BTreeMap::new(),
+            DEFAULT_VALIDATOR_PORT,
+        );
+        let leaving = Random.generate();
+        let moving = Random.generate();
+        let joining = Random.generate();
+
+        peers.reserve_validators(
+            &client,
+            vec![endpoint(&leaving, 30303), endpoint(&moving, 30303)]
+                .into_iter()
+                .collect(),
+        );
+        let mut expected = vec![enode(&leaving, 30303), enode(&moving, 30303)];
+        expected.sort();
+        assert_eq!(reserved_peers(&client), expected);
+
+        // A validator leaves, one changes its endpoint and a new one joins.
+        peers.reserve_validators(
+            &client,
+            vec![endpoint(&moving, 30304), endpoint(&joining, 30303)]
+                .into_iter()
+                .collect(),
+        );
+        let mut expected = vec![enode(&moving, 30304), enode(&joining, 30303)];
+        expected.sort();
+        assert_eq!(reserved_peers(&client), expected);
+        assert_eq!(peers.status().len(), 2);
+    }
+
+    #[test]
+    fn test_validator_peers_reachability() {
+        let mut peers = ValidatorPeers::new(
+            ContractAddresses::default(),
+            BTreeMap::new(),
+            DEFAULT_VALIDATOR_PORT,
+        );
+        let connected = Random.generate();
+        let disconnected = Random.generate();
+        peers.grace_period = Duration::from_secs(0);
+        for keypair in &[&connected, &disconnected] {
+            peers.validators.insert(
+                *keypair.public(),
+                ValidatorPeer {
+                    mining_address: keypair.address(),
+                    enode: None,
+                    since: Instant::now(),
+                },
+            );
+        }
+        peers.on_peer_connected(*connected.public());
+        assert_eq!(
+            peers.unreachable_validators(),
+            vec![disconnected.address()].into_iter().collect()
+        );
+        peers.report_unreachable();
+        assert_eq!(peers.reported_unreachable.len(), 1);
+
+        peers.on_peer_connected(*disconnected.public());
+        peers.on_peer_disconnected(*connected.public());
+        assert_eq!(
+            peers.unreachable_validators(),
+            vec![connected.address()].into_iter().collect()
+        );
+        assert_eq!(
+            peers
+                .status()
+                .iter()
+                .filter(|status| status.is_connected)
+                .count(),
+            1
+        );
+    }
+}
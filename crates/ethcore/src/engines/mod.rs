@@ -31,7 +31,10 @@ pub use self::{
     authority_round::AuthorityRound,
     basic_authority::BasicAuthority,
     clique::Clique,
-    hbbft::HoneyBadgerBFT,
+    hbbft::{
+        BatchStatus, HoneyBadgerBFT, HoneyBadgerStatus, KeygenStatus, PosdaoEpochStatus,
+        SealingStatus, ValidatorPeerStatus, ValidatorScoreStatus, ValidatorStatus,
+    },
     instant_seal::{InstantSeal, InstantSealParams},
     null_engine::NullEngine,
     signer::EngineSigner,
@@ -53,6 +56,7 @@ use builtin::Builtin;
 use error::Error;
 use snapshot::SnapshotComponents;
 use spec::CommonParams;
+use stats::PrometheusRegistry;
 use types::{
     header::{ExtendedHeader, Header},
     transaction::{self, SignedTransaction, UnverifiedTransaction},
@@ -334,6 +338,9 @@ pub trait Engine<M: Machine>: Sync + Send {
     /// New transactions were imported to the transaction queue
     fn on_transactions_imported(&self) {}
 
+    /// A new best block was imported to the chain
+    fn on_best_block_imported(&self) {}
+
     /// Block transformation functions, before the transactions.
     /// `epoch_begin` set to true if this block kicks off an epoch.
     fn on_new_block(
@@ -472,9 +479,21 @@ pub trait Engine<M: Machine>: Sync + Send {
         Err(EngineError::UnexpectedMessage)
     }
 
+    /// Called when a peer with the given node id connected.
+    fn on_peer_connected(&self, _node_id: H512) {}
+
+    /// Called when a peer with the given node id disconnected.
+    fn on_peer_disconnected(&self, _node_id: H512) {}
+
     /// Register a component which signs consensus messages.
     fn set_signer(&self, _signer: Option<Box<dyn EngineSigner>>) {}
 
+    /// Checks whether the engine signer can be changed to the given address now.
+    /// Engines that derive key material from the signer reject changes in phases that would lose it.
+    fn check_signer_change(&self, _address: &Address) -> Result<(), EngineError> {
+        Ok(())
+    }
+
     /// Returns whether the current node is a validator and
     /// actually may seal a block if AuRa engine is used.
     ///
@@ -565,6 +584,15 @@ pub trait Engine<M: Machine>: Sync + Send {
     fn use_block_author(&self) -> bool {
         true
     }
+
+    /// Returns the engine as HoneyBadgerBFT engine, if it is one.
+    /// Used to expose hbbft specific information via RPC.
+    fn as_hbbft(&self) -> Option<&HoneyBadgerBFT> {
+        None
+    }
+
+    /// Adds the metrics of the engine to the prometheus registry.
+    fn prometheus_metrics(&self, _registry: &mut PrometheusRegistry) {}
 }
 
 /// t_nb 9.3 Check whether a given block is the best block based on the default total difficulty rule.
@@ -290,6 +290,23 @@ pub fn new_berlin_to_london_test() -> Spec {
     )
 }
 
+/// Create a new Foundation Shanghai era spec.
+pub fn new_shanghai_test() -> Spec {
+    load(
+        None,
+        include_bytes!("../../res/chainspec/test/shanghai_test.json"),
+    )
+}
+
+/// Create a new Foundation Shanghai era spec with the transient storage (EIP-1153), MCOPY (EIP-5656)
+/// and SELFDESTRUCT (EIP-6780) changes of Cancun.
+pub fn new_shanghai_eip1153_eip5656_eip6780_test() -> Spec {
+    load(
+        None,
+        include_bytes!("../../res/chainspec/test/shanghai_eip1153_eip5656_eip6780_test.json"),
+    )
+}
+
 /// Create a new Musicoin-MCIP3-era spec.
 pub fn new_mcip3_test() -> Spec {
     load(
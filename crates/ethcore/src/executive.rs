@@ -29,6 +29,7 @@ use std::{cmp, convert::TryFrom, sync::Arc};
 use trace::{self, Tracer, VMTracer};
 use transaction_ext::Transaction;
 use types::transaction::{Action, SignedTransaction, TypedTransaction};
+use vm::schedule::EIP3860_MAX_INITCODE_SIZE;
 use vm::{
     self, AccessList, ActionParams, ActionValue, CleanDustMode, CreateContractAddress, EnvInfo,
     ResumeCall, ResumeCreate, ReturnData, Schedule, TrapError,
@@ -322,7 +323,8 @@ impl<'a> CallCreateExecutive<'a> {
 
         let gas = params.gas;
 
-        let substate = Self::new_substate(&params, schedule);
+        let mut substate = Self::new_substate(&params, schedule);
+        substate.created.insert(params.address);
         let kind = CallCreateExecutiveKind::ExecCreate(params, substate);
 
         Self {
@@ -1144,6 +1146,18 @@ impl<'a, B: 'a + StateBackend> Executive<'a, B> {
             TypedTransaction::Legacy(_) => (), //legacy transactions are allways valid
         };
 
+        // EIP-3860: initcode of a contract creation transaction is limited in size
+        if schedule.eip3860
+            && t.tx().action == Action::Create
+            && t.tx().data.len() > EIP3860_MAX_INITCODE_SIZE
+        {
+            return Err(ExecutionError::TransactionMalformed(format!(
+                "Initcode size {} exceeds the limit of {}",
+                t.tx().data.len(),
+                EIP3860_MAX_INITCODE_SIZE
+            )));
+        }
+
         let sender = t.sender();
         let nonce = self.state.nonce(&sender)?;
 
@@ -1153,6 +1167,9 @@ impl<'a, B: 'a + StateBackend> Executive<'a, B> {
 
         if schedule.eip2929 {
             access_list.insert_address(sender);
+            if schedule.eip3651 {
+                access_list.insert_address(self.info.author);
+            }
             for (address, builtin) in self.machine.builtins() {
                 if builtin.is_active(self.info.number) {
                     access_list.insert_address(*address);
@@ -1381,6 +1398,7 @@ impl<'a, B: 'a + StateBackend> Executive<'a, B> {
     }
 
     /// Calls contract function with given contract params.
+    /// Starts with an empty transient storage, like every transaction.
     pub fn call<T, V>(
         &mut self,
         params: ActionParams,
@@ -1392,6 +1410,7 @@ impl<'a, B: 'a + StateBackend> Executive<'a, B> {
         T: Tracer,
         V: VMTracer,
     {
+        self.state.clear_transient_storage();
         self.call_with_stack_depth(params, substate, 0, tracer, vm_tracer)
     }
 
@@ -1451,6 +1470,7 @@ impl<'a, B: 'a + StateBackend> Executive<'a, B> {
     }
 
     /// Creates contract with given contract params.
+    /// Starts with an empty transient storage, like every transaction.
     pub fn create<T, V>(
         &mut self,
         params: ActionParams,
@@ -1462,6 +1482,7 @@ impl<'a, B: 'a + StateBackend> Executive<'a, B> {
         T: Tracer,
         V: VMTracer,
     {
+        self.state.clear_transient_storage();
         self.create_with_stack_depth(params, substate, 0, tracer, vm_tracer)
     }
 
@@ -1566,8 +1587,8 @@ impl<'a, B: 'a + StateBackend> Executive<'a, B> {
         };
 
         // perform suicides
-        for address in &substate.suicides {
-            self.state.kill_account(address);
+        for address in substate.accounts_to_kill(&schedule) {
+            self.state.kill_account(&address);
         }
 
         // perform garbage-collection
@@ -1637,8 +1658,9 @@ mod tests {
     use std::{str::FromStr, sync::Arc};
     use test_helpers::{get_temp_state, get_temp_state_with_factory};
     use trace::{
-        trace, ExecutiveTracer, ExecutiveVMTracer, FlatTrace, MemoryDiff, NoopTracer, NoopVMTracer,
-        StorageDiff, Tracer, VMExecutedOperation, VMOperation, VMTrace, VMTracer,
+        trace, AccessListTracer, ExecutiveTracer, ExecutiveVMTracer, FlatTrace, MemoryDiff,
+        NoopTracer, NoopVMTracer, StorageDiff, Tracer, VMExecutedOperation, VMOperation, VMTrace,
+        VMTracer,
     };
     use types::transaction::{
         AccessListTx, Action, EIP1559TransactionTx, Transaction, TypedTransaction,
@@ -2881,6 +2903,142 @@ mod tests {
         }
     }
 
+    evm_test! {test_initcode_size_limit: test_initcode_size_limit_int}
+    fn test_initcode_size_limit(factory: Factory) {
+        let keypair = Random.generate();
+        let t = TypedTransaction::Legacy(Transaction {
+            action: Action::Create,
+            value: U256::zero(),
+            data: vec![0; 2 * 24576 + 1],
+            gas: U256::from(1_000_000),
+            gas_price: U256::zero(),
+            nonce: U256::zero(),
+        })
+        .sign(keypair.secret(), None);
+
+        let mut state = get_temp_state_with_factory(factory);
+        let mut info = EnvInfo::default();
+        info.gas_limit = U256::from(1_000_000);
+        let mut machine = ::ethereum::new_london_test_machine();
+        // The initcode limit does not depend on the maximum code size.
+        machine.set_schedule_creation_rules(Box::new(|s, _| {
+            s.eip3860 = true;
+            s.create_data_limit = usize::max_value();
+        }));
+        let schedule = machine.schedule(info.number);
+
+        let res = {
+            let mut ex = Executive::new(&mut state, &info, &machine, &schedule);
+            let opts = TransactOptions::with_no_tracing();
+            ex.transact(&t, opts)
+        };
+
+        match res {
+            Err(ExecutionError::TransactionMalformed(err)) if err.contains("Initcode size") => (),
+            _ => assert!(false, "Expected initcode size error. {:?}", res),
+        }
+    }
+
+    evm_test! {test_selfdestruct_eip6780: test_selfdestruct_eip6780_int}
+    fn test_selfdestruct_eip6780(factory: Factory) {
+        // PUSH1 0x0a SELFDESTRUCT
+        let code = "600aff".from_hex().unwrap();
+        let beneficiary = Address::from_low_u64_be(10);
+        let contract = Address::from_low_u64_be(0x1000);
+        let keypair = Random.generate();
+        let call = TypedTransaction::Legacy(Transaction {
+            action: Action::Call(contract),
+            value: U256::zero(),
+            data: vec![],
+            gas: U256::from(100_000),
+            gas_price: U256::zero(),
+            nonce: U256::zero(),
+        })
+        .sign(keypair.secret(), None);
+        let create = TypedTransaction::Legacy(Transaction {
+            action: Action::Create,
+            value: U256::from(100),
+            data: code.clone(),
+            gas: U256::from(100_000),
+            gas_price: U256::zero(),
+            nonce: U256::one(),
+        })
+        .sign(keypair.secret(), None);
+        let created = contract_address(
+            CreateContractAddress::FromSenderAndNonce,
+            &keypair.address(),
+            &U256::one(),
+            &[],
+        )
+        .0;
+
+        let mut state = get_temp_state_with_factory(factory);
+        state
+            .add_balance(&keypair.address(), &U256::from(100), CleanupMode::NoEmpty)
+            .unwrap();
+        state
+            .new_contract(&contract, U256::from(1000), U256::zero())
+            .unwrap();
+        state.init_code(&contract, code.clone()).unwrap();
+        let mut info = EnvInfo::default();
+        info.gas_limit = U256::from(100_000);
+        let mut machine = ::ethereum::new_london_test_machine();
+        machine.set_schedule_creation_rules(Box::new(|s, _| s.eip6780 = true));
+        let schedule = machine.schedule(info.number);
+
+        for t in &[call, create] {
+            let mut ex = Executive::new(&mut state, &info, &machine, &schedule);
+            let opts = TransactOptions::with_no_tracing();
+            ex.transact(t, opts).unwrap();
+        }
+
+        // The existing contract only loses its balance.
+        assert_eq!(state.code(&contract).unwrap(), Some(Arc::new(code)));
+        assert_eq!(state.balance(&contract).unwrap(), U256::zero());
+        // The contract created in the same transaction is removed.
+        assert!(!state.exists(&created).unwrap());
+        assert_eq!(state.balance(&beneficiary).unwrap(), U256::from(1100));
+    }
+
+    evm_test! {test_access_list_tracer: test_access_list_tracer_int}
+    fn test_access_list_tracer(factory: Factory) {
+        // PUSH1 0x01 SLOAD POP PUSH20 0x0a BALANCE POP STOP
+        let code = format!("6001545073{:040x}315000", 10).from_hex().unwrap();
+        let contract = Address::from_low_u64_be(0x1000);
+        let keypair = Random.generate();
+        let t = TypedTransaction::Legacy(Transaction {
+            action: Action::Call(contract),
+            value: U256::zero(),
+            data: vec![],
+            gas: U256::from(100_000),
+            gas_price: U256::zero(),
+            nonce: U256::zero(),
+        })
+        .sign(keypair.secret(), None);
+
+        let mut state = get_temp_state_with_factory(factory);
+        state.init_code(&contract, code).unwrap();
+        let mut info = EnvInfo::default();
+        info.gas_limit = U256::from(100_000);
+        let machine = make_london_machine(0);
+        let schedule = machine.schedule(info.number);
+
+        let excluded = vec![keypair.address(), contract].into_iter().collect();
+        let executed = {
+            let mut ex = Executive::new(&mut state, &info, &machine, &schedule);
+            let opts = TransactOptions::new(AccessListTracer::new(excluded), NoopVMTracer);
+            ex.transact(&t, opts).unwrap()
+        };
+
+        assert_eq!(
+            executed.trace,
+            vec![
+                (Address::from_low_u64_be(10), vec![]),
+                (contract, vec![H256::from_low_u64_be(1)]),
+            ]
+        );
+    }
+
     evm_test! {test_keccak: test_keccak_int}
     fn test_keccak(factory: Factory) {
         let code = "6064640fffffffff20600055".from_hex().unwrap();
@@ -150,6 +150,22 @@ where
         }
     }
 
+    fn transient_storage_at(&self, key: &H256) -> vm::Result<H256> {
+        Ok(self
+            .state
+            .transient_storage_at(&self.origin_info.address, key))
+    }
+
+    fn set_transient_storage(&mut self, key: H256, value: H256) -> vm::Result<()> {
+        if self.static_flag {
+            Err(vm::Error::MutableCallInStaticContext)
+        } else {
+            self.state
+                .set_transient_storage(&self.origin_info.address, key, value);
+            Ok(())
+        }
+    }
+
     fn is_static(&self) -> bool {
         return self.static_flag;
     }
@@ -468,9 +484,12 @@ where
         let address = self.origin_info.address.clone();
         let balance = self.balance(&address)?;
         if &address == refund_address {
-            // TODO [todr] To be consistent with CPP client we set balance to 0 in that case.
-            self.state
-                .sub_balance(&address, &balance, &mut CleanupMode::NoEmpty)?;
+            // Since EIP-6780 the balance is only burnt if the account is killed in finalize.
+            if !self.schedule.eip6780 {
+                // TODO [todr] To be consistent with CPP client we set balance to 0 in that case.
+                self.state
+                    .sub_balance(&address, &balance, &mut CleanupMode::NoEmpty)?;
+            }
         } else {
             trace!(target: "ext", "Suiciding {} -> {} (xfer: {})", address, refund_address, balance);
             self.state.transfer_balance(
@@ -546,6 +565,7 @@ where
     }
 
     fn al_insert_storage_key(&mut self, address: Address, key: H256) {
+        self.tracer.trace_access(address, Some(key));
         self.substate.access_list.insert_storage_key(address, key)
     }
 
@@ -554,6 +574,7 @@ where
     }
 
     fn al_insert_address(&mut self, address: Address) {
+        self.tracer.trace_access(address, None);
         self.substate.access_list.insert_address(address)
     }
 }
@@ -67,7 +67,8 @@ pub fn json_state_test<H: FnMut(&str, HookType)>(
             let pre: PodState = test.pre_state.into();
 
             for (spec_name, states) in test.post_states {
-                let spec = match EvmTestClient::spec_from_json(&spec_name) {
+                let rules = state_test.fork_rules.get(&spec_name).unwrap_or(&spec_name);
+                let spec = match EvmTestClient::spec_from_json(rules) {
                     Some(spec) => spec,
                     None => {
                         panic!(
@@ -31,6 +31,7 @@ use types::{
     },
     BlockNumber,
 };
+use vm::schedule::EIP3860_MAX_INITCODE_SIZE;
 use vm::{
     AccessList, ActionParams, ActionValue, CallType, CreateContractAddress, EnvInfo, ParamsType,
     Schedule,
@@ -459,6 +460,17 @@ impl EthereumMachine {
             _ => (),
         };
 
+        if schedule.eip3860
+            && tx.tx().action == transaction::Action::Create
+            && tx.tx().data.len() > EIP3860_MAX_INITCODE_SIZE
+        {
+            debug!(
+                "Rejected contract creation with {} bytes of initcode",
+                tx.tx().data.len()
+            );
+            return Err(transaction::Error::TooBig);
+        }
+
         Ok(tx)
     }
 
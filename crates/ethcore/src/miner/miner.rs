@@ -61,7 +61,7 @@ use client::{
     BlockChain, BlockId, BlockProducer, ChainInfo, ClientIoMessage, Nonce, SealedBlockImporter,
     TransactionId, TransactionInfo,
 };
-use engines::{EngineSigner, EthEngine, Seal, SealingState};
+use engines::{EngineError, EngineSigner, EthEngine, Seal, SealingState};
 use error::{Error, ErrorKind};
 use executed::ExecutionError;
 use executive::contract_address;
@@ -1149,6 +1149,10 @@ impl miner::MinerService for Miner {
         }
     }
 
+    fn check_engine_signer(&self, address: &Address) -> Result<(), EngineError> {
+        self.engine.check_signer_change(address)
+    }
+
     fn sensible_gas_price(&self) -> U256 {
         // 10% above our minimum.
         self.transaction_queue.current_worst_gas_price() * 110u32 / 100
@@ -1647,6 +1651,7 @@ impl miner::MinerService for Miner {
             // Clear nonce cache
             self.nonce_cache.clear();
             self.balance_cache.clear();
+            self.engine.on_best_block_imported();
         }
 
         // t_nb 10.1 First update gas limit in transaction queue and minimal gas price.
@@ -54,6 +54,7 @@ use client::{
     traits::ForceUpdateSealing, AccountData, BlockChain, BlockProducer, Nonce, ScheduleInfo,
     SealedBlockImporter,
 };
+use engines::EngineError;
 use error::Error;
 use state::StateInfo;
 
@@ -155,6 +156,11 @@ pub trait MinerService: Send + Sync {
     /// On chains where sealing is done externally (e.g. PoW) we provide only reward beneficiary.
     fn set_author<T: Into<Option<Author>>>(&self, author: T);
 
+    /// Checks whether the engine accepts a signer with the given address at this point.
+    fn check_engine_signer(&self, _address: &Address) -> Result<(), EngineError> {
+        Ok(())
+    }
+
     // Transaction Pool
 
     /// Imports transactions to transaction queue.
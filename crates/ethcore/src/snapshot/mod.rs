@@ -75,7 +75,7 @@ mod error;
 mod watcher;
 
 #[cfg(test)]
-mod tests;
+pub(crate) mod tests;
 
 mod traits;
 
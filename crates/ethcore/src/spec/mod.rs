@@ -24,3 +24,4 @@ pub use self::{
     genesis::Genesis,
     spec::{CommonParams, OptimizeFor, Spec, SpecParams},
 };
+pub use ethjson::spec::HbbftUnavailabilityAction;
@@ -149,6 +149,18 @@ pub struct CommonParams {
     pub eip3541_transition: BlockNumber,
     /// Number of first block where EIP-3607 rule begins.
     pub eip3607_transition: BlockNumber,
+    /// Number of first block where EIP-3651 rules begin. Warm COINBASE.
+    pub eip3651_transition: BlockNumber,
+    /// Number of first block where EIP-3855 rules begin. PUSH0 opcode.
+    pub eip3855_transition: BlockNumber,
+    /// Number of first block where EIP-3860 rules begin. Initcode size limit and metering.
+    pub eip3860_transition: BlockNumber,
+    /// Number of first block where EIP-1153 rules begin. Transient storage opcodes.
+    pub eip1153_transition: BlockNumber,
+    /// Number of first block where EIP-5656 rules begin. MCOPY opcode.
+    pub eip5656_transition: BlockNumber,
+    /// Number of first block where EIP-6780 rules begin. SELFDESTRUCT only deletes accounts created in the same transaction.
+    pub eip6780_transition: BlockNumber,
     /// Number of first block where dust cleanup rules (EIP-168 and EIP169) begin.
     pub dust_protection_transition: BlockNumber,
     /// Nonce cap increase per block. Nonce cap is only checked if dust protection is enabled.
@@ -225,6 +237,18 @@ impl CommonParams {
         }
     }
 
+    /// Checks that the enabled EIPs do not conflict with each other.
+    fn validate(&self) -> Result<(), String> {
+        // EIP-1153 and EIP-5656 assign the opcodes of the EIP-2315 subroutine instructions.
+        if self.eip2315_transition != BlockNumber::max_value()
+            && (self.eip1153_transition != BlockNumber::max_value()
+                || self.eip5656_transition != BlockNumber::max_value())
+        {
+            return Err("eip2315Transition can not be combined with eip1153Transition or eip5656Transition, their opcodes overlap".into());
+        }
+        Ok(())
+    }
+
     /// Apply common spec config parameters to the schedule.
     pub fn update_schedule(&self, block_number: u64, schedule: &mut ::vm::Schedule) {
         schedule.have_create2 = block_number >= self.eip1014_transition;
@@ -244,6 +268,12 @@ impl CommonParams {
         schedule.eip3541 = block_number >= self.eip3541_transition;
         schedule.eip1559 = block_number >= self.eip1559_transition;
         schedule.eip3198 = block_number >= self.eip3198_transition;
+        schedule.eip3651 = block_number >= self.eip3651_transition;
+        schedule.eip3855 = block_number >= self.eip3855_transition;
+        schedule.eip3860 = block_number >= self.eip3860_transition;
+        schedule.eip1153 = block_number >= self.eip1153_transition;
+        schedule.eip5656 = block_number >= self.eip5656_transition;
+        schedule.eip6780 = block_number >= self.eip6780_transition;
         if schedule.eip1559 {
             schedule.eip1559_elasticity_multiplier = self.eip1559_elasticity_multiplier.as_usize();
 
@@ -437,6 +467,24 @@ impl From<ethjson::spec::Params> for CommonParams {
                 .dust_protection_transition
                 .map_or_else(BlockNumber::max_value, Into::into),
             eip3607_transition: p.eip3607_transition.map_or(0, Into::into),
+            eip3651_transition: p
+                .eip3651_transition
+                .map_or_else(BlockNumber::max_value, Into::into),
+            eip3855_transition: p
+                .eip3855_transition
+                .map_or_else(BlockNumber::max_value, Into::into),
+            eip3860_transition: p
+                .eip3860_transition
+                .map_or_else(BlockNumber::max_value, Into::into),
+            eip1153_transition: p
+                .eip1153_transition
+                .map_or_else(BlockNumber::max_value, Into::into),
+            eip5656_transition: p
+                .eip5656_transition
+                .map_or_else(BlockNumber::max_value, Into::into),
+            eip6780_transition: p
+                .eip6780_transition
+                .map_or_else(BlockNumber::max_value, Into::into),
             nonce_cap_increment: p.nonce_cap_increment.map_or(64, Into::into),
             remove_dust_contracts: p.remove_dust_contracts.unwrap_or(false),
             gas_limit_bound_divisor: p.gas_limit_bound_divisor.into(),
@@ -498,6 +546,9 @@ pub struct SpecParams<'a> {
     /// memory. This may get more fine-grained in the future but for now is simply a binary
     /// option.
     pub optimization_setting: Option<OptimizeFor>,
+    /// Overrides the action the hbbft engine takes if this node is a staked validator
+    /// flagged as unavailable.
+    pub hbbft_unavailability_action: Option<ethjson::spec::HbbftUnavailabilityAction>,
 }
 
 impl<'a> SpecParams<'a> {
@@ -506,6 +557,7 @@ impl<'a> SpecParams<'a> {
         SpecParams {
             cache_dir: path,
             optimization_setting: None,
+            hbbft_unavailability_action: None,
         }
     }
 
@@ -514,6 +566,7 @@ impl<'a> SpecParams<'a> {
         SpecParams {
             cache_dir: path,
             optimization_setting: Some(optimization),
+            hbbft_unavailability_action: None,
         }
     }
 }
@@ -600,7 +653,7 @@ impl Clone for Spec {
     }
 }
 
-fn load_machine_from(s: ethjson::spec::Spec) -> EthereumMachine {
+fn load_machine_from(s: ethjson::spec::Spec) -> Result<EthereumMachine, String> {
     let builtins = s
         .accounts
         .builtins()
@@ -613,8 +666,9 @@ fn load_machine_from(s: ethjson::spec::Spec) -> EthereumMachine {
         })
         .collect();
     let params = CommonParams::from(s.params);
+    params.validate()?;
 
-    Spec::machine(&s.engine, params, builtins)
+    Ok(Spec::machine(&s.engine, params, builtins))
 }
 
 fn convert_json_to_spec(
@@ -636,6 +690,7 @@ fn load_from(spec_params: SpecParams, s: ethjson::spec::Spec) -> Result<Spec, Er
     let g = Genesis::from(s.genesis);
     let GenericSeal(seal_rlp) = g.seal.into();
     let params = CommonParams::from(s.params);
+    params.validate()?;
 
     let (engine, hard_forks) = Spec::engine(spec_params, s.engine, params, builtins);
 
@@ -751,6 +806,12 @@ impl Spec {
             params.eip3198_transition,
             params.eip3529_transition,
             params.eip3541_transition,
+            params.eip3651_transition,
+            params.eip3855_transition,
+            params.eip3860_transition,
+            params.eip1153_transition,
+            params.eip5656_transition,
+            params.eip6780_transition,
             params.dust_protection_transition,
             params.wasm_activation_transition,
             params.wasm_disable_transition,
@@ -814,7 +875,11 @@ impl Spec {
                     .expect("Failed to start AuthorityRound consensus engine.")
             }
             ethjson::spec::Engine::Hbbft(hbbft) => {
-                HoneyBadgerBFT::new(hbbft.params.into(), machine)
+                let mut params = hbbft.params;
+                if spec_params.hbbft_unavailability_action.is_some() {
+                    params.unavailability_action = spec_params.hbbft_unavailability_action;
+                }
+                HoneyBadgerBFT::new(params, machine)
                     .expect("Failed to start AuthorityRound consensus engine.")
             }
         };
@@ -1043,7 +1108,7 @@ impl Spec {
     pub fn load_machine<R: Read>(reader: R) -> Result<EthereumMachine, String> {
         ethjson::spec::Spec::load(reader)
             .map_err(fmt_err)
-            .map(load_machine_from)
+            .and_then(|x| load_machine_from(x).map_err(fmt_err))
     }
 
     /// Loads spec from json file. Provide factories for executing contracts and ensuring
@@ -1242,6 +1307,24 @@ mod tests {
         assert!(Spec::load(&tempdir.path(), &[] as &[u8]).is_err());
     }
 
+    #[test]
+    fn test_load_conflicting_opcodes() {
+        let tempdir = TempDir::new("").unwrap();
+        let json: &[u8] =
+            include_bytes!("../../res/chainspec/test/shanghai_eip1153_eip5656_eip6780_test.json");
+        assert!(Spec::load(&tempdir.path(), json).is_ok());
+
+        // Subroutines use the opcodes of TLOAD, TSTORE and MCOPY.
+        let conflicting = String::from_utf8_lossy(json).replace(
+            "\"eip1153Transition\"",
+            "\"eip2315Transition\": \"0x0\", \"eip1153Transition\"",
+        );
+        let err = Spec::load(&tempdir.path(), conflicting.as_bytes())
+            .err()
+            .expect("The conflicting transitions must be rejected");
+        assert!(err.contains("eip2315Transition"));
+    }
+
     #[test]
     fn test_chain() {
         let test_spec = Spec::new_test();
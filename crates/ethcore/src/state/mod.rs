@@ -312,6 +312,10 @@ pub struct State<B> {
     cache: RefCell<HashMap<Address, AccountEntry>>,
     // The original account is preserved in
     checkpoints: RefCell<Vec<HashMap<Address, Option<AccountEntry>>>>,
+    // Transient storage of the current transaction (EIP-1153), never committed.
+    transient_storage: HashMap<(Address, H256), H256>,
+    // The original transient storage values, one map for each checkpoint.
+    transient_checkpoints: Vec<HashMap<(Address, H256), H256>>,
     account_start_nonce: U256,
     factories: Factories,
 }
@@ -334,6 +338,21 @@ pub enum CleanupMode<'a> {
     TrackTouched(&'a mut HashSet<Address>),
 }
 
+/// Fields of an account to override before a call, like the state overrides of `eth_call`.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct AccountOverride {
+    /// Replaces the balance.
+    pub balance: Option<U256>,
+    /// Replaces the nonce.
+    pub nonce: Option<U256>,
+    /// Replaces the code.
+    pub code: Option<Bytes>,
+    /// Replaces the whole storage.
+    pub storage: Option<HashMap<H256, H256>>,
+    /// Replaces the given storage slots, leaving the others untouched.
+    pub storage_diff: HashMap<H256, H256>,
+}
+
 /// Provides subset of `State` methods to query state information
 pub trait StateInfo {
     /// Get the nonce of account `a`.
@@ -349,6 +368,13 @@ pub trait StateInfo {
     fn code(&self, a: &Address) -> TrieResult<Option<Arc<Bytes>>>;
 }
 
+/// A state whose accounts can be overridden. Only used on throwaway states, before a call.
+pub trait Overridable {
+    /// Override fields of account `a`.
+    fn apply_override(&mut self, a: &Address, account_override: &AccountOverride)
+        -> TrieResult<()>;
+}
+
 impl<B: Backend> StateInfo for State<B> {
     fn nonce(&self, a: &Address) -> TrieResult<U256> {
         State::nonce(self, a)
@@ -364,6 +390,16 @@ impl<B: Backend> StateInfo for State<B> {
     }
 }
 
+impl<B: Backend> Overridable for State<B> {
+    fn apply_override(
+        &mut self,
+        address: &Address,
+        account_override: &AccountOverride,
+    ) -> TrieResult<()> {
+        State::apply_override(self, address, account_override)
+    }
+}
+
 const SEC_TRIE_DB_UNWRAP_STR: &'static str = "A state can only be created with valid root. Creating a SecTrieDB with a valid root will not fail. \
 			 Therefore creating a SecTrieDB with this state's root will not fail.";
 
@@ -382,6 +418,8 @@ impl<B: Backend> State<B> {
             root: root,
             cache: RefCell::new(HashMap::new()),
             checkpoints: RefCell::new(Vec::new()),
+            transient_storage: HashMap::new(),
+            transient_checkpoints: Vec::new(),
             account_start_nonce: account_start_nonce,
             factories: factories,
         }
@@ -403,6 +441,8 @@ impl<B: Backend> State<B> {
             root: root,
             cache: RefCell::new(HashMap::new()),
             checkpoints: RefCell::new(Vec::new()),
+            transient_storage: HashMap::new(),
+            transient_checkpoints: Vec::new(),
             account_start_nonce: account_start_nonce,
             factories: factories,
         };
@@ -420,11 +460,19 @@ impl<B: Backend> State<B> {
         let checkpoints = self.checkpoints.get_mut();
         let index = checkpoints.len();
         checkpoints.push(HashMap::new());
+        self.transient_checkpoints.push(HashMap::new());
         index
     }
 
     /// Merge last checkpoint with previous.
     pub fn discard_checkpoint(&mut self) {
+        if let Some(checkpoint) = self.transient_checkpoints.pop() {
+            if let Some(prev) = self.transient_checkpoints.last_mut() {
+                for (slot, value) in checkpoint {
+                    prev.entry(slot).or_insert(value);
+                }
+            }
+        }
         // merge with previous checkpoint
         let last = self.checkpoints.get_mut().pop();
         if let Some(mut checkpoint) = last {
@@ -442,6 +490,11 @@ impl<B: Backend> State<B> {
 
     /// Revert to the last checkpoint and discard it.
     pub fn revert_to_checkpoint(&mut self) {
+        if let Some(checkpoint) = self.transient_checkpoints.pop() {
+            for (slot, value) in checkpoint {
+                self.write_transient_storage(slot, value);
+            }
+        }
         if let Some(mut checkpoint) = self.checkpoints.get_mut().pop() {
             for (k, v) in checkpoint.drain() {
                 match v {
@@ -611,6 +664,38 @@ impl<B: Backend> State<B> {
             .unwrap_or(KECCAK_NULL_RLP))
     }
 
+    /// Get the transient storage (EIP-1153) of account `address` at `key`.
+    pub fn transient_storage_at(&self, address: &Address, key: &H256) -> H256 {
+        self.transient_storage
+            .get(&(*address, *key))
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Mutate the transient storage (EIP-1153) of account `address` so that it is `value` for `key`.
+    /// The change is undone when the current checkpoint is reverted.
+    pub fn set_transient_storage(&mut self, address: &Address, key: H256, value: H256) {
+        let slot = (*address, key);
+        let old_value = self.write_transient_storage(slot, value);
+        if let Some(checkpoint) = self.transient_checkpoints.last_mut() {
+            checkpoint.entry(slot).or_insert(old_value);
+        }
+    }
+
+    /// Clear the transient storage (EIP-1153). It lives for the duration of a transaction.
+    pub fn clear_transient_storage(&mut self) {
+        self.transient_storage.clear();
+    }
+
+    fn write_transient_storage(&mut self, slot: (Address, H256), value: H256) -> H256 {
+        let old_value = if value.is_zero() {
+            self.transient_storage.remove(&slot)
+        } else {
+            self.transient_storage.insert(slot, value)
+        };
+        old_value.unwrap_or_default()
+    }
+
     /// Get the value of storage at a specific checkpoint.
     pub fn checkpoint_storage_at(
         &self,
@@ -1467,6 +1552,36 @@ impl<B: Backend> State<B> {
             .require(a, false)?
             .reset_code_and_storage(code, storage))
     }
+
+    /// Override the given fields of account `a`. Creates account if it does not exist.
+    /// The storage diff is applied after a replaced storage.
+    pub fn apply_override(
+        &mut self,
+        a: &Address,
+        account_override: &AccountOverride,
+    ) -> TrieResult<()> {
+        if let Some(balance) = account_override.balance {
+            self.require(a, false)?.set_balance(balance);
+        }
+        if let Some(nonce) = account_override.nonce {
+            self.require(a, false)?.set_nonce(nonce);
+        }
+        match (&account_override.code, &account_override.storage) {
+            (code, Some(storage)) => {
+                let code = match code {
+                    Some(code) => Arc::new(code.clone()),
+                    None => self.code(a)?.unwrap_or_default(),
+                };
+                self.patch_account(a, code, storage.clone())?;
+            }
+            (Some(code), None) => self.reset_code(a, code.clone())?,
+            (None, None) => (),
+        }
+        for (key, value) in &account_override.storage_diff {
+            self.set_storage(a, *key, *value)?;
+        }
+        Ok(())
+    }
 }
 
 // State proof implementations; useful for light client protocols.
@@ -1565,6 +1680,8 @@ impl Clone for State<StateDB> {
             root: self.root.clone(),
             cache: RefCell::new(cache),
             checkpoints: RefCell::new(Vec::new()),
+            transient_storage: self.transient_storage.clone(),
+            transient_checkpoints: Vec::new(),
             account_start_nonce: self.account_start_nonce.clone(),
             factories: self.factories.clone(),
         }
@@ -2597,6 +2714,78 @@ mod tests {
         assert_eq!(result.trace, expected_trace);
     }
 
+    #[test]
+    fn should_trace_and_diff_suicide_eip6780() {
+        use pod_account;
+
+        let _ = env_logger::try_init();
+
+        let mut state = get_temp_state();
+
+        let mut info = EnvInfo::default();
+        info.gas_limit = 1_000_000.into();
+        let mut machine = ::ethereum::new_london_test_machine();
+        machine.set_schedule_creation_rules(Box::new(|s, _| s.eip6780 = true));
+
+        let t = TypedTransaction::Legacy(Transaction {
+            nonce: 0.into(),
+            gas_price: 0.into(),
+            gas: 100_000.into(),
+            action: Action::Call(Address::from_low_u64_be(0xa)),
+            value: 100.into(),
+            data: vec![],
+        })
+        .sign(&secret(), None);
+
+        let code: Bytes =
+            FromHex::from_hex("73000000000000000000000000000000000000000bff").unwrap();
+        state
+            .init_code(&Address::from_low_u64_be(0xa), code.clone())
+            .unwrap();
+        state
+            .add_balance(
+                &Address::from_low_u64_be(0xa),
+                &50.into(),
+                CleanupMode::NoEmpty,
+            )
+            .unwrap();
+        state
+            .add_balance(&t.sender(), &100.into(), CleanupMode::NoEmpty)
+            .unwrap();
+        state.commit().unwrap();
+        let original = state.clone();
+
+        let result = state.apply(&info, &machine, &t, true).unwrap();
+        // The suicide is traced with the balance moved to the refund address.
+        assert_eq!(result.trace.len(), 2);
+        assert_eq!(
+            result.trace[1].action,
+            trace::Action::Suicide(trace::Suicide {
+                address: Address::from_low_u64_be(0xa),
+                refund_address: Address::from_low_u64_be(0xb),
+                balance: 150.into(),
+            })
+        );
+
+        // The contract was not created in the transaction, so it is only emptied of its balance.
+        let diff = state.diff_from(original).unwrap();
+        let contract = |balance: U256| PodAccount {
+            balance,
+            nonce: U256::zero(),
+            code: Some(code.clone()),
+            storage: Default::default(),
+        };
+        assert_eq!(
+            diff.get().get(&Address::from_low_u64_be(0xa)),
+            pod_account::diff_pod(Some(&contract(50.into())), Some(&contract(U256::zero())))
+                .as_ref()
+        );
+        assert_eq!(
+            state.balance(&Address::from_low_u64_be(0xb)).unwrap(),
+            150.into()
+        );
+    }
+
     #[test]
     fn code_from_database() {
         let a = Address::zero();
@@ -2846,6 +3035,34 @@ mod tests {
         assert_eq!(state.balance(&a).unwrap(), U256::from(0));
     }
 
+    #[test]
+    fn checkpoint_transient_storage() {
+        let mut state = get_temp_state();
+        let a = Address::zero();
+        let k = H256::from_low_u64_be(1);
+        let v1 = H256::from_low_u64_be(69);
+        let v2 = H256::from_low_u64_be(70);
+
+        state.checkpoint();
+        state.set_transient_storage(&a, k, v1);
+        state.checkpoint();
+        state.set_transient_storage(&a, k, v2);
+        assert_eq!(state.transient_storage_at(&a, &k), v2);
+        state.revert_to_checkpoint();
+        assert_eq!(state.transient_storage_at(&a, &k), v1);
+
+        state.checkpoint();
+        state.set_transient_storage(&a, k, v2);
+        state.discard_checkpoint();
+        assert_eq!(state.transient_storage_at(&a, &k), v2);
+        state.revert_to_checkpoint();
+        assert_eq!(state.transient_storage_at(&a, &k), H256::zero());
+
+        state.set_transient_storage(&a, k, v1);
+        state.clear_transient_storage();
+        assert_eq!(state.transient_storage_at(&a, &k), H256::zero());
+    }
+
     #[test]
     fn checkpoint_revert_to_get_storage_at() {
         let mut state = get_temp_state();
@@ -3541,4 +3758,38 @@ mod tests {
             BigEndianHash::from_uint(&U256::from(0u64))
         );
     }
+
+    #[test]
+    fn apply_account_override() {
+        let mut state = get_temp_state();
+        let a = Address::from_low_u64_be(1);
+        let k1 = H256::from_low_u64_be(1);
+        let k2 = H256::from_low_u64_be(2);
+        let v = H256::from_low_u64_be(3);
+        state.set_storage(&a, k1, v).unwrap();
+        state.commit().unwrap();
+
+        let diff = AccountOverride {
+            balance: Some(U256::from(100)),
+            nonce: Some(U256::from(7)),
+            storage_diff: vec![(k2, v)].into_iter().collect(),
+            ..Default::default()
+        };
+        state.apply_override(&a, &diff).unwrap();
+        assert_eq!(state.balance(&a).unwrap(), U256::from(100));
+        assert_eq!(state.nonce(&a).unwrap(), U256::from(7));
+        assert_eq!(state.storage_at(&a, &k1).unwrap(), v);
+        assert_eq!(state.storage_at(&a, &k2).unwrap(), v);
+
+        let replace = AccountOverride {
+            code: Some(vec![0x60, 0x00]),
+            storage: Some(vec![(k2, v)].into_iter().collect()),
+            ..Default::default()
+        };
+        state.apply_override(&a, &replace).unwrap();
+        assert_eq!(state.code(&a).unwrap(), Some(Arc::new(vec![0x60, 0x00])));
+        assert_eq!(state.storage_at(&a, &k1).unwrap(), H256::zero());
+        assert_eq!(state.storage_at(&a, &k2).unwrap(), v);
+        assert_eq!(state.balance(&a).unwrap(), U256::from(100));
+    }
 }
@@ -41,6 +41,9 @@ pub struct Substate {
     /// Created contracts.
     pub contracts_created: Vec<Address>,
 
+    /// Accounts created in this transaction, including the one of a contract creation transaction.
+    pub created: HashSet<Address>,
+
     /// List of accesses addresses and slots
     pub access_list: AccessList,
 }
@@ -58,6 +61,7 @@ impl Substate {
             logs: Vec::default(),
             sstore_clears_refund: 0,
             contracts_created: Vec::default(),
+            created: HashSet::default(),
             access_list: access_list.clone(),
         }
     }
@@ -69,6 +73,17 @@ impl Substate {
         self.logs.extend(s.logs);
         self.sstore_clears_refund += s.sstore_clears_refund;
         self.contracts_created.extend(s.contracts_created);
+        self.created.extend(s.created);
+    }
+
+    /// Accounts to kill when the transaction is finalized. Since EIP-6780 only suicided accounts
+    /// that were created in the same transaction are killed, the others only lost their balance.
+    pub fn accounts_to_kill(&self, schedule: &Schedule) -> Vec<Address> {
+        self.suicides
+            .iter()
+            .filter(|address| !schedule.eip6780 || self.created.contains(address))
+            .cloned()
+            .collect()
     }
 
     /// Get the cleanup mode object from this.
@@ -89,6 +104,7 @@ impl Substate {
 mod tests {
     use super::Substate;
     use ethereum_types::Address;
+    use evm::Schedule;
     use types::log_entry::LogEntry;
 
     #[test]
@@ -127,4 +143,22 @@ mod tests {
         assert_eq!(sub_state.sstore_clears_refund, (15000 * 12).into());
         assert_eq!(sub_state.suicides.len(), 1);
     }
+
+    #[test]
+    fn accounts_to_kill() {
+        let created = Address::from_low_u64_be(1u64);
+        let existing = Address::from_low_u64_be(2u64);
+        let mut sub_state = Substate::new();
+        sub_state.created.insert(created);
+        sub_state.suicides.insert(created);
+        sub_state.suicides.insert(existing);
+
+        let mut schedule = Schedule::new_london();
+        let mut killed = sub_state.accounts_to_kill(&schedule);
+        killed.sort();
+        assert_eq!(killed, vec![created, existing]);
+
+        schedule.eip6780 = true;
+        assert_eq!(sub_state.accounts_to_kill(&schedule), vec![created]);
+    }
 }
@@ -21,35 +21,40 @@ use std::{
 
 use client::{
     traits::{
-        BlockChainClient, BlockChainReset, BlockInfo, ChainInfo, ImportBlock, ImportExportBlocks,
+        BlockChainClient, BlockChainReset, BlockInfo, Call, ChainInfo, ImportBlock,
+        ImportExportBlocks,
     },
     Client, ClientConfig, ImportSealedBlock, PrepareOpenBlock,
 };
 use crypto::publickey::KeyPair;
 use ethereum;
 use ethereum_types::{Address, U256};
+use executed::CallError;
 use executive::{Executive, TransactOptions};
 use hash::keccak;
 use io::IoChannel;
 use miner::{Miner, MinerService, PendingOrdering};
-use rustc_hex::ToHex;
+use rustc_hex::{FromHex, ToHex};
 use spec::Spec;
 use state::{self, CleanupMode, State, StateInfo};
 use tempdir::TempDir;
 use test_helpers::{
-    self, generate_dummy_client, generate_dummy_client_with_data, get_bad_state_dummy_block,
-    get_good_dummy_block, get_good_dummy_block_seq, get_test_client_with_blocks,
-    push_blocks_to_client,
+    self, generate_dummy_client, generate_dummy_client_with_data, generate_dummy_client_with_spec,
+    get_bad_state_dummy_block, get_good_dummy_block, get_good_dummy_block_seq,
+    get_test_client_with_blocks, push_blocks_to_client,
 };
 use types::{
     data_format::DataFormat,
     filter::Filter,
     ids::BlockId,
-    transaction::{Action, Condition, PendingTransaction, Transaction, TypedTransaction},
+    transaction::{
+        AccessList, Action, Condition, PendingTransaction, Transaction, TypedTransaction,
+    },
     view,
     views::BlockView,
 };
 use verification::queue::kind::blocks::Unverified;
+use vm::Error as VmError;
 
 #[test]
 fn imports_from_empty() {
@@ -487,6 +492,84 @@ fn transaction_proof() {
     assert_eq!(state.balance(&address).unwrap(), 95.into());
 }
 
+/// Creates the access list of a call with 100000 gas to a contract with the given code.
+fn create_access_list_for_code<F>(
+    spec: F,
+    code: &str,
+) -> Result<(AccessList, U256, Option<VmError>), CallError>
+where
+    F: Fn() -> Spec,
+{
+    let client = generate_dummy_client_with_spec(spec);
+    let (mut state, header) = client.latest_state_and_header();
+    let contract = Address::from_low_u64_be(0xc0de);
+    state
+        .init_code(&contract, code.from_hex().unwrap())
+        .unwrap();
+    state.commit().unwrap();
+
+    let transaction = TypedTransaction::Legacy(Transaction {
+        nonce: 0.into(),
+        gas_price: 0.into(),
+        gas: 100_000.into(),
+        action: Action::Call(contract),
+        value: 0.into(),
+        data: Vec::new(),
+    })
+    .fake_sign(Address::from_low_u64_be(0x5e4d));
+
+    client.create_access_list(&transaction, &state, &header)
+}
+
+#[test]
+fn create_access_list_converges() {
+    // GAS BALANCE STOP: the accessed account is the gas left, which depends on the access list.
+    let (access_list, gas_used, error) =
+        create_access_list_for_code(ethereum::new_london_test, "5a3100").unwrap();
+
+    // Once the list holds one account, the gas left and thereby the list stay the same.
+    let accessed = Address::from_low_u64_be(100_000 - 21_000 - 2_400 - 2);
+    assert_eq!(access_list, vec![(accessed, vec![])]);
+    assert_eq!(gas_used, U256::from(21_000 + 2_400 + 2 + 100));
+    assert_eq!(error, None);
+}
+
+#[test]
+fn create_access_list_of_reverting_call() {
+    // PUSH1 0x42 BALANCE POP PUSH1 0 PUSH1 0 REVERT
+    let (access_list, gas_used, error) =
+        create_access_list_for_code(ethereum::new_london_test, "6042315060006000fd").unwrap();
+
+    // The accesses before the revert are listed, with the gas used up to the revert.
+    assert_eq!(access_list, vec![(Address::from_low_u64_be(0x42), vec![])]);
+    assert_eq!(gas_used, U256::from(21_000 + 2_400 + 3 + 100 + 2 + 3 + 3));
+    assert_eq!(error, Some(VmError::Reverted));
+}
+
+#[test]
+fn create_access_list_fails_if_not_converging() {
+    // Reads the storage slots 0 and 1 only if more than 76000 gas is left, so an empty list
+    // leads to a list with both slots, which leaves too little gas to read them.
+    let result = create_access_list_for_code(
+        ethereum::new_london_test,
+        "5a620128e010600a57005b600054506001545000",
+    );
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn create_access_list_leaves_out_warm_coinbase() {
+    // COINBASE BALANCE STOP
+    let (access_list, _, _) =
+        create_access_list_for_code(ethereum::new_london_test, "413100").unwrap();
+    assert_eq!(access_list, vec![(Address::zero(), vec![])]);
+
+    let (access_list, _, _) =
+        create_access_list_for_code(ethereum::new_shanghai_test, "413100").unwrap();
+    assert!(access_list.is_empty());
+}
+
 #[test]
 fn reset_blockchain() {
     let client = get_test_client_with_blocks(get_good_dummy_block_seq(19));
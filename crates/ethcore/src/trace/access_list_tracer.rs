@@ -0,0 +1,103 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of OpenEthereum.
+
+// OpenEthereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// OpenEthereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with OpenEthereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Access list tracer.
+
+use ethereum_types::{Address, H256, U256};
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use trace::{trace::RewardType, Tracer};
+use types::transaction::AccessListItem;
+use vm::{ActionParams, Error as VmError};
+
+/// Records the accounts and storage slots accessed by a transaction, to build its EIP-2930 access list.
+/// Contracts created by the transaction are left out, and so are the excluded accounts,
+/// unless their storage is accessed.
+pub struct AccessListTracer {
+    excluded: HashSet<Address>,
+    created: HashSet<Address>,
+    accessed: BTreeMap<Address, BTreeSet<H256>>,
+}
+
+impl AccessListTracer {
+    /// Creates a tracer leaving out the given accounts, usually the ones warm from the start.
+    pub fn new(excluded: HashSet<Address>) -> Self {
+        AccessListTracer {
+            excluded,
+            created: HashSet::new(),
+            accessed: BTreeMap::new(),
+        }
+    }
+}
+
+impl Tracer for AccessListTracer {
+    type Output = AccessListItem;
+
+    fn prepare_trace_call(&mut self, _: &ActionParams, _: usize, _: bool) {}
+
+    fn prepare_trace_create(&mut self, params: &ActionParams) {
+        self.created.insert(params.address);
+    }
+
+    fn done_trace_call(&mut self, _: U256, _: &[u8]) {}
+    fn done_trace_create(&mut self, _: U256, _: &[u8], _: Address) {}
+    fn done_trace_failed(&mut self, _: &VmError) {}
+    fn trace_suicide(&mut self, _: Address, _: U256, _: Address) {}
+    fn trace_reward(&mut self, _: Address, _: U256, _: RewardType) {}
+
+    fn trace_access(&mut self, address: Address, key: Option<H256>) {
+        let keys = self.accessed.entry(address).or_default();
+        if let Some(key) = key {
+            keys.insert(key);
+        }
+    }
+
+    fn drain(self) -> Vec<AccessListItem> {
+        let excluded = self.excluded;
+        let created = self.created;
+        self.accessed
+            .into_iter()
+            .filter(|(address, keys)| {
+                !keys.is_empty() || !(excluded.contains(address) || created.contains(address))
+            })
+            .map(|(address, keys)| (address, keys.into_iter().collect()))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_leave_out_excluded_accounts_without_storage() {
+        let sender = Address::from_low_u64_be(1);
+        let recipient = Address::from_low_u64_be(2);
+        let other = Address::from_low_u64_be(3);
+        let key = H256::from_low_u64_be(4);
+
+        let mut tracer = AccessListTracer::new(vec![sender, recipient].into_iter().collect());
+        tracer.trace_access(sender, None);
+        tracer.trace_access(recipient, None);
+        tracer.trace_access(recipient, Some(key));
+        tracer.trace_access(other, None);
+        tracer.trace_access(recipient, Some(key));
+
+        assert_eq!(
+            tracer.drain(),
+            vec![(recipient, vec![key]), (other, vec![])]
+        );
+    }
+}
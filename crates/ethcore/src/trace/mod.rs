@@ -16,6 +16,7 @@
 
 //! Tracing
 
+mod access_list_tracer;
 mod config;
 mod db;
 mod executive_tracer;
@@ -24,6 +25,7 @@ mod noop_tracer;
 mod types;
 
 pub use self::{
+    access_list_tracer::AccessListTracer,
     config::Config,
     db::TraceDB,
     executive_tracer::{ExecutiveTracer, ExecutiveVMTracer},
@@ -74,6 +76,9 @@ pub trait Tracer: Send {
     /// Stores reward info.
     fn trace_reward(&mut self, author: Address, value: U256, reward_type: RewardType);
 
+    /// Stores an access to an account, or to one of its storage slots, as accounted by EIP-2929.
+    fn trace_access(&mut self, _address: Address, _key: Option<H256>) {}
+
     /// Consumes self and returns all traces.
     fn drain(self) -> Vec<Self::Output>;
 }
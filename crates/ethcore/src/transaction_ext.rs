@@ -18,6 +18,7 @@
 
 use evm::Schedule;
 use types::transaction::{self, Action};
+use vm::schedule::EIP3860_INITCODE_WORD_GAS;
 
 /// Extends transaction with gas verification method.
 pub trait Transaction {
@@ -40,7 +41,7 @@ impl Transaction for transaction::Transaction {
 
 /// Get the transaction cost in gas for the given params.
 fn gas_required_for(is_create: bool, data: &[u8], schedule: &Schedule) -> u64 {
-    data.iter().fold(
+    let gas = data.iter().fold(
         (if is_create {
             schedule.tx_create_gas
         } else {
@@ -52,5 +53,11 @@ fn gas_required_for(is_create: bool, data: &[u8], schedule: &Schedule) -> u64 {
                 _ => schedule.tx_data_non_zero_gas,
             }) as u64
         },
-    )
+    );
+    // EIP-3860: initcode of a contract creation is charged per 32 byte word
+    if is_create && schedule.eip3860 {
+        gas + (EIP3860_INITCODE_WORD_GAS * ((data.len() + 31) / 32)) as u64
+    } else {
+        gas
+    }
 }
@@ -245,6 +245,9 @@ pub struct EthSync {
     priority_tasks: Mutex<mpsc::Sender<PriorityTask>>,
     /// New incoming transactions notification channel
     new_transaction_hashes: crossbeam_channel::Sender<H256>,
+    /// Node ids of the reserved peers of the network configuration,
+    /// which stay reserved when the engine releases them.
+    static_reserved_peers: BTreeSet<NodeId>,
 }
 
 impl EthSync {
@@ -268,6 +271,12 @@ impl EthSync {
             params.network_config.clone().into_basic()?,
             connection_filter,
         )?;
+        let static_reserved_peers = params
+            .network_config
+            .reserved_nodes
+            .iter()
+            .filter_map(|enode| enode_node_id(enode))
+            .collect();
 
         let sync = Arc::new(EthSync {
             network: service,
@@ -281,6 +290,7 @@ impl EthSync {
             subprotocol_name: params.config.subprotocol_name,
             priority_tasks: Mutex::new(priority_tasks_tx),
             new_transaction_hashes: new_transaction_hashes_tx,
+            static_reserved_peers,
         });
 
         Ok(sync)
@@ -555,6 +565,9 @@ impl NetworkProtocolHandler for SyncProtocolHandler {
         if io.is_reserved_peer(*peer) {
             trace!(target: "sync", "Connected to reserved peer {:?}", node_id);
         }
+        if let Some(node_id) = node_id {
+            self.chain.peer_connected(node_id);
+        }
         // If warp protocol is supported only allow warp handshake
         let warp_protocol = io.protocol_version(PAR_PROTOCOL, *peer).unwrap_or(0) != 0;
         let warp_context = io.subprotocol_name() == PAR_PROTOCOL;
@@ -571,6 +584,9 @@ impl NetworkProtocolHandler for SyncProtocolHandler {
         if io.is_reserved_peer(*peer) {
             trace!(target: "sync", "Disconnected from reserved peer {:?}", io.session_info(*peer).expect("").id);
         }
+        if let Some(node_id) = io.session_info(*peer).and_then(|info| info.id) {
+            self.chain.peer_disconnected(node_id);
+        }
         if io.subprotocol_name() != PAR_PROTOCOL {
             self.sync.write().on_peer_aborting(
                 &mut NetSyncIo::new(io, &*self.chain, &*self.snapshot_service, &self.overlay),
@@ -725,6 +741,30 @@ impl ChainNotify for EthSync {
         let mut sync = self.eth_handler.sync.write();
         sync.transactions_received(txs, peer_id);
     }
+
+    fn add_reserved_peer(&self, enode: &str) {
+        if let Err(e) = self.network.add_reserved_peer(enode) {
+            warn!(target: "sync", "Could not add reserved peer {}: {:?}", enode, e);
+        }
+    }
+
+    fn remove_reserved_peer(&self, enode: &str) {
+        if enode_node_id(enode).map_or(false, |id| self.static_reserved_peers.contains(&id)) {
+            trace!(target: "sync", "Keeping the configured reserved peer {}", enode);
+            return;
+        }
+        if let Err(e) = self.network.remove_reserved_peer(enode) {
+            warn!(target: "sync", "Could not remove reserved peer {}: {:?}", enode, e);
+        }
+    }
+}
+
+/// Returns the node id of the enode URL, if it is valid.
+fn enode_node_id(enode: &str) -> Option<NodeId> {
+    if !enode.starts_with("enode://") || enode.get(136..137) != Some("@") {
+        return None;
+    }
+    enode.get(8..136)?.parse().ok()
 }
 
 /// Trait for managing network
@@ -16,7 +16,9 @@
 
 //! Hbbft parameter deserialization.
 
-use ethereum_types::Address;
+use crate::uint::Uint;
+use ethereum_types::{Address, U256};
+use std::collections::BTreeMap;
 
 /// Skip block reward parameter.
 /// Defines one (potential open) range about skips
@@ -32,6 +34,18 @@ pub struct HbbftParamsSkipBlockReward {
     pub to_block: Option<u64>,
 }
 
+/// Action taken by a staked validator that detects it has been flagged as unavailable.
+#[derive(Debug, PartialEq, Clone, Copy, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum HbbftUnavailabilityAction {
+    /// Shut down the node gracefully, so a supervisor can restart it.
+    Shutdown,
+    /// Only log a warning.
+    Warn,
+    /// Announce the availability of the validator again.
+    Reannounce,
+}
+
 /// Hbbft parameters.
 #[derive(Debug, PartialEq, Deserialize)]
 #[serde(deny_unknown_fields)]
@@ -47,13 +61,54 @@ pub struct HbbftParams {
     pub is_unit_test: Option<bool>,
     /// Block reward contract address.
     pub block_reward_contract_address: Option<Address>,
+    /// The validator set contract's address, mapped by the block from which on it is used.
+    /// Defaults to the address of the contract in the genesis block of the DMD networks.
+    pub validator_set_contract_address: Option<BTreeMap<Uint, Address>>,
+    /// The staking contract's address, mapped by the block from which on it is used.
+    /// Defaults to the address of the contract in the genesis block of the DMD networks.
+    pub staking_contract_address: Option<BTreeMap<Uint, Address>>,
+    /// The key history contract's address, mapped by the block from which on it is used.
+    /// Defaults to the address of the contract in the genesis block of the DMD networks.
+    pub keygen_history_contract_address: Option<BTreeMap<Uint, Address>>,
     /// Block reward skips at different blocks.
     pub block_reward_skips: Option<Vec<HbbftParamsSkipBlockReward>>,
     /// Number of consensus messages to store on the disk. 0 means zero blocks get stored.
     pub blocks_to_keep_on_disk: Option<u64>,
-    /// Directory where to store the Hbbft Messages.
+    /// Directory where to store the Hbbft Messages, and the journal of the in-flight consensus state
+    /// that is restored after a restart.
     /// Usually only the latest HBBFT messages are interesting for Debug, Analytics or Evidence.
     pub blocks_to_keep_directory: Option<String>,
+    /// Block from which on consensus messages are sent in the compact binary encoding.
+    /// Messages are always decoded in both the binary and the legacy JSON encoding,
+    /// and peers still sending JSON keep receiving JSON.
+    pub binary_consensus_messages_transition: Option<u64>,
+    /// The randomness contract's address, mapped by the block from which on it is used.
+    /// The seed combined from the random data of all contributions is fed into it for every block.
+    pub randomness_contract_address: Option<BTreeMap<Uint, Address>>,
+    /// Number of random bytes every validator contributes per honey badger epoch.
+    /// At least 32 bytes are required, defaults to 80.
+    pub random_bytes_per_epoch: Option<usize>,
+    /// Maximum size of the transactions a validator proposes per honey badger epoch, in bytes.
+    /// Defaults to 1 MiB.
+    pub max_contribution_bytes: Option<usize>,
+    /// Action taken if this node is a staked validator and flagged as unavailable.
+    /// Defaults to shutting down the node.
+    pub unavailability_action: Option<HbbftUnavailabilityAction>,
+    /// The devp2p endpoints ("host:port") of validator nodes, by mining address.
+    /// Validators without a configured endpoint are reached at the internet address
+    /// registered in the staking contract.
+    pub validator_endpoints: Option<BTreeMap<Address, String>>,
+    /// The devp2p port of validators reached at the internet address registered in the
+    /// staking contract. Defaults to 30303.
+    pub validator_port: Option<u16>,
+    /// Maximum number of honey badger epochs a consensus message may be ahead of the current
+    /// epoch to be cached until its epoch becomes current. Defaults to 3.
+    pub max_future_epochs: Option<u64>,
+    /// Maximum number of cached consensus messages of future epochs per sender. Defaults to 1000.
+    pub max_future_messages_per_sender: Option<usize>,
+    /// Maximum total size of the cached consensus messages of future epochs, in bytes.
+    /// Defaults to 32 MiB.
+    pub max_future_messages_bytes: Option<usize>,
 }
 
 /// Hbbft engine config.
@@ -84,12 +139,21 @@ impl HbbftParams {
 
         true
     }
+
+    /// Returns the randomness contract that has to be called for the given block, if any.
+    pub fn randomness_contract_address_at(&self, block_number: u64) -> Option<Address> {
+        self.randomness_contract_address
+            .as_ref()
+            .and_then(|transitions| transitions.range(..=Uint(U256::from(block_number))).last())
+            .map(|(_, address)| *address)
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::Hbbft;
-    use ethereum_types::Address;
+    use super::{Hbbft, HbbftUnavailabilityAction};
+    use crate::uint::Uint;
+    use ethereum_types::{Address, U256};
     use std::str::FromStr;
 
     #[test]
@@ -100,7 +164,11 @@ mod tests {
 				"maximumBlockTime": 600,
 				"transactionQueueSizeTrigger": 1,
 				"isUnitTest": true,
-				"blockRewardContractAddress": "0x2000000000000000000000000000000000000002"
+				"blockRewardContractAddress": "0x2000000000000000000000000000000000000002",
+				"validatorSetContractAddress": {
+					"0": "0x1000000000000000000000000000000000000001",
+					"1000": "0x1000000000000000000000000000000000000002"
+				}
 			}
 		}"#;
 
@@ -113,6 +181,160 @@ mod tests {
             deserialized.params.block_reward_contract_address,
             Address::from_str("2000000000000000000000000000000000000002").ok()
         );
+        assert_eq!(
+            deserialized
+                .params
+                .validator_set_contract_address
+                .as_ref()
+                .unwrap()[&Uint(U256::from(1000))],
+            Address::from_str("1000000000000000000000000000000000000002").unwrap()
+        );
+        assert_eq!(deserialized.params.staking_contract_address, None);
+        assert_eq!(
+            deserialized.params.binary_consensus_messages_transition,
+            None
+        );
+    }
+
+    #[test]
+    fn hbbft_deserialization_hex_contract_transitions() {
+        let s = r#"{
+			"params": {
+				"minimumBlockTime": 0,
+				"maximumBlockTime": 600,
+				"transactionQueueSizeTrigger": 1,
+				"stakingContractAddress": {
+					"0x0": "0x1100000000000000000000000000000000000001",
+					"0x10": "0x1100000000000000000000000000000000000002"
+				},
+				"keygenHistoryContractAddress": {
+					"0x10": "0x7000000000000000000000000000000000000002"
+				}
+			}
+		}"#;
+
+        let deserialized: Hbbft = serde_json::from_str(s).unwrap();
+        let staking = deserialized.params.staking_contract_address.unwrap();
+        assert_eq!(
+            staking[&Uint(U256::from(0))],
+            Address::from_str("1100000000000000000000000000000000000001").unwrap()
+        );
+        assert_eq!(
+            staking[&Uint(U256::from(16))],
+            Address::from_str("1100000000000000000000000000000000000002").unwrap()
+        );
+        assert_eq!(
+            deserialized
+                .params
+                .keygen_history_contract_address
+                .unwrap()
+                .keys()
+                .collect::<Vec<_>>(),
+            vec![&Uint(U256::from(16))]
+        );
+    }
+
+    #[test]
+    fn hbbft_deserialization_binary_consensus_messages() {
+        let s = r#"{
+			"params": {
+				"minimumBlockTime": 0,
+				"maximumBlockTime": 600,
+				"transactionQueueSizeTrigger": 1,
+				"binaryConsensusMessagesTransition": 1000
+			}
+		}"#;
+
+        let deserialized: Hbbft = serde_json::from_str(s).unwrap();
+        assert_eq!(
+            deserialized.params.binary_consensus_messages_transition,
+            Some(1000)
+        );
+    }
+
+    #[test]
+    fn hbbft_deserialization_randomness_contract() {
+        let s = r#"{
+			"params": {
+				"minimumBlockTime": 0,
+				"maximumBlockTime": 600,
+				"transactionQueueSizeTrigger": 1,
+				"randomnessContractAddress": {
+					"10": "0x3000000000000000000000000000000000000001",
+					"20": "0x3000000000000000000000000000000000000002"
+				},
+				"randomBytesPerEpoch": 64,
+				"maxContributionBytes": 500000,
+				"validatorEndpoints": {
+					"0xbbcaa8d48289bb1ffcf9808d9aa4b1d215054c78": "192.168.0.101:30301"
+				},
+				"validatorPort": 30300
+			}
+		}"#;
+
+        let deserialized: Hbbft = serde_json::from_str(s).unwrap();
+        assert_eq!(deserialized.params.random_bytes_per_epoch, Some(64));
+        assert_eq!(deserialized.params.max_contribution_bytes, Some(500000));
+        assert_eq!(deserialized.params.unavailability_action, None);
+        assert_eq!(deserialized.params.validator_port, Some(30300));
+        assert_eq!(
+            deserialized.params.validator_endpoints.as_ref().unwrap()
+                [&Address::from_str("bbcaa8d48289bb1ffcf9808d9aa4b1d215054c78").unwrap()],
+            "192.168.0.101:30301"
+        );
+        assert_eq!(deserialized.params.randomness_contract_address_at(9), None);
+        assert_eq!(
+            deserialized.params.randomness_contract_address_at(10),
+            Address::from_str("3000000000000000000000000000000000000001").ok()
+        );
+        assert_eq!(
+            deserialized.params.randomness_contract_address_at(19),
+            Address::from_str("3000000000000000000000000000000000000001").ok()
+        );
+        assert_eq!(
+            deserialized.params.randomness_contract_address_at(100),
+            Address::from_str("3000000000000000000000000000000000000002").ok()
+        );
+    }
+
+    #[test]
+    fn hbbft_deserialization_future_messages_limits() {
+        let s = r#"{
+			"params": {
+				"minimumBlockTime": 0,
+				"maximumBlockTime": 600,
+				"transactionQueueSizeTrigger": 1,
+				"maxFutureEpochs": 5,
+				"maxFutureMessagesPerSender": 200,
+				"maxFutureMessagesBytes": 1048576
+			}
+		}"#;
+
+        let deserialized: Hbbft = serde_json::from_str(s).unwrap();
+        assert_eq!(deserialized.params.max_future_epochs, Some(5));
+        assert_eq!(
+            deserialized.params.max_future_messages_per_sender,
+            Some(200)
+        );
+        assert_eq!(deserialized.params.max_future_messages_bytes, Some(1048576));
+    }
+
+    #[test]
+    fn hbbft_deserialization_unavailability_action() {
+        let s = r#"{
+			"params": {
+				"minimumBlockTime": 0,
+				"maximumBlockTime": 600,
+				"transactionQueueSizeTrigger": 1,
+				"unavailabilityAction": "reannounce"
+			}
+		}"#;
+
+        let deserialized: Hbbft = serde_json::from_str(s).unwrap();
+        assert_eq!(
+            deserialized.params.unavailability_action,
+            Some(HbbftUnavailabilityAction::Reannounce)
+        );
     }
 
     #[test]
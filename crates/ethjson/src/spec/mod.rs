@@ -43,7 +43,7 @@ pub use self::{
     engine::Engine,
     ethash::{BlockReward, Ethash, EthashParams},
     genesis::Genesis,
-    hbbft::{Hbbft, HbbftParams},
+    hbbft::{Hbbft, HbbftParams, HbbftUnavailabilityAction},
     instant_seal::{InstantSeal, InstantSealParams},
     null_engine::{NullEngine, NullEngineParams},
     params::Params,
@@ -122,6 +122,18 @@ pub struct Params {
     /// See `CommonParams` docs.
     pub eip3607_transition: Option<Uint>,
     /// See `CommonParams` docs.
+    pub eip3651_transition: Option<Uint>,
+    /// See `CommonParams` docs.
+    pub eip3855_transition: Option<Uint>,
+    /// See `CommonParams` docs.
+    pub eip3860_transition: Option<Uint>,
+    /// See `CommonParams` docs.
+    pub eip1153_transition: Option<Uint>,
+    /// See `CommonParams` docs.
+    pub eip5656_transition: Option<Uint>,
+    /// See `CommonParams` docs.
+    pub eip6780_transition: Option<Uint>,
+    /// See `CommonParams` docs.
     pub dust_protection_transition: Option<Uint>,
     /// See `CommonParams` docs.
     pub nonce_cap_increment: Option<Uint>,
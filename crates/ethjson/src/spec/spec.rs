@@ -40,6 +40,10 @@ pub enum ForkSpec {
     Berlin,
     London,
     BerlinToLondonAt5,
+    Shanghai,
+    /// Shanghai with the EIP-1153, EIP-5656 and EIP-6780 changes of Cancun.
+    ShanghaiEIP1153EIP5656EIP6780,
+    Cancun,
 }
 
 /// Spec deserialization.
@@ -16,7 +16,7 @@
 
 //! Additional test structures deserialization.
 
-use crate::{hash::H256, uint::Uint};
+use crate::{hash::H256, spec::ForkSpec, uint::Uint};
 use serde_json::{self, Error};
 use std::{collections::BTreeMap, io::Read, path::PathBuf};
 
@@ -196,6 +196,10 @@ pub struct StateTests {
     pub path: PathBuf,
     /// Tests to skip
     pub skip: Vec<StateTestSkip>,
+    /// Forks whose post states are checked with the rules of another fork,
+    /// for forks that are only partly implemented.
+    #[serde(default)]
+    pub fork_rules: BTreeMap<ForkSpec, ForkSpec>,
 }
 
 /// State test to skip
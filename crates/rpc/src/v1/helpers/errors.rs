@@ -163,6 +163,14 @@ pub fn exceptional<T: fmt::Display>(data: T) -> Error {
     }
 }
 
+pub fn engine_signer_rejected<T: fmt::Display>(error: T) -> Error {
+    Error {
+        code: ErrorCode::ServerError(codes::NO_AUTHOR),
+        message: "The engine signer can not be changed now.".into(),
+        data: Some(Value::String(error.to_string())),
+    }
+}
+
 pub fn no_work() -> Error {
     Error {
         code: ErrorCode::ServerError(codes::NO_WORK),
@@ -33,6 +33,7 @@ use ethcore::{
     },
     miner::{self, MinerService},
     snapshot::SnapshotService,
+    state::Overridable,
 };
 use hash::keccak;
 use miner::external::ExternalMinerService;
@@ -58,9 +59,9 @@ use v1::{
     metadata::Metadata,
     traits::Eth,
     types::{
-        block_number_to_id, Block, BlockNumber, BlockTransactions, Bytes, CallRequest, EthAccount,
-        EthFeeHistory, Filter, Index, Log, Receipt, RichBlock, StorageProof, SyncInfo, SyncStatus,
-        Transaction, Work,
+        block_number_to_id, AccessListWithGasUsed, Block, BlockNumber, BlockOverride,
+        BlockTransactions, Bytes, CallRequest, EthAccount, EthFeeHistory, Filter, Index, Log,
+        Receipt, RichBlock, StateOverride, StorageProof, SyncInfo, SyncStatus, Transaction, Work,
     },
 };
 
@@ -596,7 +597,8 @@ where
 
 const MAX_QUEUE_SIZE_TO_MINE_ON: usize = 4; // because uncles go back 6.
 
-impl<C, SN: ?Sized, S: ?Sized, M, EM, T: StateInfo + 'static> Eth for EthClient<C, SN, S, M, EM>
+impl<C, SN: ?Sized, S: ?Sized, M, EM, T: StateInfo + Overridable + 'static> Eth
+    for EthClient<C, SN, S, M, EM>
 where
     C: miner::BlockChainClient
         + StateClient<State = T>
@@ -1350,13 +1352,19 @@ where
         self.send_raw_transaction(raw)
     }
 
-    fn call(&self, request: CallRequest, num: Option<BlockNumber>) -> BoxFuture<Bytes> {
+    fn call(
+        &self,
+        request: CallRequest,
+        num: Option<BlockNumber>,
+        state_override: Option<StateOverride>,
+        block_override: Option<BlockOverride>,
+    ) -> BoxFuture<Bytes> {
         let request = CallRequest::into(request);
         let signed = try_bf!(fake_sign::sign_call(request));
 
         let num = num.unwrap_or_default();
 
-        let (mut state, header) = if num == BlockNumber::Pending {
+        let (mut state, mut header) = if num == BlockNumber::Pending {
             self.pending_state_and_header_with_fallback()
         } else {
             let id = match num {
@@ -1379,6 +1387,13 @@ where
             (state, header)
         };
 
+        if let Some(state_override) = state_override {
+            try_bf!(state_override.apply(&mut state));
+        }
+        if let Some(block_override) = block_override {
+            try_bf!(block_override.apply(&mut header));
+        }
+
         let result = self
             .client
             .call(&signed, Default::default(), &mut state, &header);
@@ -1394,12 +1409,18 @@ where
         ))
     }
 
-    fn estimate_gas(&self, request: CallRequest, num: Option<BlockNumber>) -> BoxFuture<U256> {
+    fn estimate_gas(
+        &self,
+        request: CallRequest,
+        num: Option<BlockNumber>,
+        state_override: Option<StateOverride>,
+        block_override: Option<BlockOverride>,
+    ) -> BoxFuture<U256> {
         let request = CallRequest::into(request);
         let signed = try_bf!(fake_sign::sign_call(request));
         let num = num.unwrap_or_default();
 
-        let (state, header) = if num == BlockNumber::Pending {
+        let (mut state, mut header) = if num == BlockNumber::Pending {
             self.pending_state_and_header_with_fallback()
         } else {
             let id = match num {
@@ -1421,6 +1442,13 @@ where
             (state, header)
         };
 
+        if let Some(state_override) = state_override {
+            try_bf!(state_override.apply(&mut state));
+        }
+        if let Some(block_override) = block_override {
+            try_bf!(block_override.apply(&mut header));
+        }
+
         Box::new(future::done(
             self.client
                 .estimate_gas(&signed, &state, &header)
@@ -1428,6 +1456,49 @@ where
         ))
     }
 
+    fn create_access_list(
+        &self,
+        request: CallRequest,
+        num: Option<BlockNumber>,
+    ) -> BoxFuture<AccessListWithGasUsed> {
+        let request = CallRequest::into(request);
+        let signed = try_bf!(fake_sign::sign_call(request));
+        let num = num.unwrap_or_default();
+
+        let (state, header) = if num == BlockNumber::Pending {
+            self.pending_state_and_header_with_fallback()
+        } else {
+            let id = match num {
+                BlockNumber::Hash { hash, .. } => BlockId::Hash(hash),
+                BlockNumber::Num(num) => BlockId::Number(num),
+                BlockNumber::Earliest => BlockId::Earliest,
+                BlockNumber::Latest => BlockId::Latest,
+                BlockNumber::Pending => unreachable!(), // Already covered
+            };
+
+            let state = try_bf!(self.client.state_at(id).ok_or_else(errors::state_pruned));
+            let header = try_bf!(self
+                .client
+                .block_header(id)
+                .ok_or_else(errors::state_pruned)
+                .and_then(|h| h
+                    .decode(self.client.engine().params().eip1559_transition)
+                    .map_err(errors::decode)));
+            (state, header)
+        };
+
+        Box::new(future::done(
+            self.client
+                .create_access_list(&signed, &state, &header)
+                .map(|(access_list, gas_used, error)| AccessListWithGasUsed {
+                    access_list: access_list.into_iter().map(Into::into).collect(),
+                    gas_used,
+                    error: error.map(|error| error.to_string()),
+                })
+                .map_err(errors::call),
+        ))
+    }
+
     fn compile_lll(&self, _: String) -> Result<Bytes> {
         Err(errors::deprecated(
             "Compilation of LLL via RPC is deprecated".to_string(),
@@ -0,0 +1,104 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of OpenEthereum.
+
+// OpenEthereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// OpenEthereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with OpenEthereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! HoneyBadgerBFT RPC implementation.
+
+use std::sync::Arc;
+
+use ethcore::{client::EngineInfo, engines::HoneyBadgerBFT};
+
+use jsonrpc_core::Result;
+use v1::{
+    helpers::errors,
+    traits::Hbbft,
+    types::{
+        HbbftKeygenStatus, HbbftSealing, HbbftValidatorPeer, HbbftValidatorScore,
+        HbbftValidatorStatus, HoneyBadgerStatus, PosdaoEpoch,
+    },
+};
+
+/// HoneyBadgerBFT rpc implementation.
+pub struct HbbftClient<C> {
+    client: Arc<C>,
+}
+
+impl<C> HbbftClient<C> {
+    /// Creates new hbbft client.
+    pub fn new(client: Arc<C>) -> Self {
+        HbbftClient { client }
+    }
+}
+
+impl<C: EngineInfo> HbbftClient<C> {
+    fn engine(&self) -> Result<&HoneyBadgerBFT> {
+        self.client.engine().as_hbbft().ok_or_else(|| {
+            errors::unsupported("The hbbft API requires the HoneyBadgerBFT engine.", None)
+        })
+    }
+}
+
+impl<C: EngineInfo + Send + Sync + 'static> Hbbft for HbbftClient<C> {
+    fn posdao_epoch(&self) -> Result<PosdaoEpoch> {
+        self.engine()?
+            .posdao_epoch_status()
+            .map(Into::into)
+            .map_err(|e| errors::internal("Could not read POSDAO epoch", e))
+    }
+
+    fn validator_status(&self) -> Result<HbbftValidatorStatus> {
+        self.engine()?
+            .validator_status()
+            .map(Into::into)
+            .map_err(|e| errors::internal("Could not read validator status", e))
+    }
+
+    fn keygen_status(&self) -> Result<HbbftKeygenStatus> {
+        self.engine()?
+            .keygen_status()
+            .map(Into::into)
+            .map_err(|e| errors::internal("Could not read key generation status", e))
+    }
+
+    fn honey_badger_status(&self) -> Result<HoneyBadgerStatus> {
+        Ok(self.engine()?.honey_badger_status().into())
+    }
+
+    fn sealing_status(&self) -> Result<HbbftSealing> {
+        Ok(self
+            .engine()?
+            .sealing_status()
+            .into_iter()
+            .map(|(block_num, status)| (block_num.into(), status.into()))
+            .collect())
+    }
+
+    fn validator_peers(&self) -> Result<Vec<HbbftValidatorPeer>> {
+        Ok(self
+            .engine()?
+            .validator_peers_status()
+            .into_iter()
+            .map(Into::into)
+            .collect())
+    }
+    fn validator_scores(&self) -> Result<Vec<HbbftValidatorScore>> {
+        Ok(self
+            .engine()?
+            .validator_scores()
+            .into_iter()
+            .map(Into::into)
+            .collect())
+    }
+}
@@ -20,6 +20,7 @@ mod debug;
 mod eth;
 mod eth_filter;
 mod eth_pubsub;
+mod hbbft;
 mod net;
 mod parity;
 #[cfg(any(test, feature = "accounts"))]
@@ -50,6 +51,7 @@ pub use self::{
     eth::{EthClient, EthClientOptions},
     eth_filter::EthFilterClient,
     eth_pubsub::EthPubSubClient,
+    hbbft::HbbftClient,
     net::NetClient,
     parity::ParityClient,
     parity_set::ParitySetClient,
@@ -67,6 +67,9 @@ pub mod accounts {
                 "parity_setEngineSigner",
                 "use `parity_setEngineSignerSecret` instead. See #9997 for context.",
             );
+            self.miner
+                .check_engine_signer(&address)
+                .map_err(errors::engine_signer_rejected)?;
 
             let signer = Box::new(EngineSigner::new(
                 self.accounts.clone(),
@@ -151,6 +154,9 @@ where
     fn set_engine_signer_secret(&self, secret: H256) -> Result<bool> {
         let keypair = crypto::publickey::KeyPair::from_secret(secret.into())
             .map_err(|e| errors::account("Invalid secret", e))?;
+        self.miner
+            .check_engine_signer(&keypair.address())
+            .map_err(errors::engine_signer_rejected)?;
         self.miner.set_author(miner::Author::Sealer(
             ethcore::engines::signer::from_keypair(keypair),
         ));
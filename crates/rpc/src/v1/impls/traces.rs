@@ -18,9 +18,12 @@
 
 use std::sync::Arc;
 
-use ethcore::client::{
-    BlockChainClient, BlockId, Call, CallAnalytics, EngineInfo, StateClient, StateInfo, TraceId,
-    TransactionId,
+use ethcore::{
+    client::{
+        BlockChainClient, BlockId, Call, CallAnalytics, EngineInfo, StateClient, StateInfo,
+        TraceId, TransactionId,
+    },
+    state::Overridable,
 };
 use ethereum_types::H256;
 use types::transaction::{SignedTransaction, TypedTransaction};
@@ -30,8 +33,8 @@ use v1::{
     helpers::{errors, fake_sign},
     traits::Traces,
     types::{
-        block_number_to_id, BlockNumber, Bytes, CallRequest, Index, LocalizedTrace, TraceFilter,
-        TraceOptions, TraceResults, TraceResultsWithTransactionHash,
+        block_number_to_id, BlockNumber, BlockOverride, Bytes, CallRequest, Index, LocalizedTrace,
+        StateOverride, TraceFilter, TraceOptions, TraceResults, TraceResultsWithTransactionHash,
     },
     Metadata,
 };
@@ -60,7 +63,7 @@ impl<C> TracesClient<C> {
 
 impl<C, S> Traces for TracesClient<C>
 where
-    S: StateInfo + 'static,
+    S: StateInfo + Overridable + 'static,
     C: BlockChainClient + StateClient<State = S> + Call<State = S> + EngineInfo + 'static,
 {
     type Metadata = Metadata;
@@ -105,6 +108,8 @@ where
         request: CallRequest,
         flags: TraceOptions,
         block: Option<BlockNumber>,
+        state_override: Option<StateOverride>,
+        block_override: Option<BlockOverride>,
     ) -> Result<TraceResults> {
         let block = block.unwrap_or_default();
 
@@ -126,20 +131,22 @@ where
         };
 
         let mut state = self.client.state_at(id).ok_or_else(errors::state_pruned)?;
-        let header = self
+        let mut header = self
             .client
             .block_header(id)
-            .ok_or_else(errors::state_pruned)?;
+            .ok_or_else(errors::state_pruned)?
+            .decode(self.client.engine().params().eip1559_transition)
+            .map_err(errors::decode)?;
+
+        if let Some(state_override) = state_override {
+            state_override.apply(&mut state)?;
+        }
+        if let Some(block_override) = block_override {
+            block_override.apply(&mut header)?;
+        }
 
         self.client
-            .call(
-                &signed,
-                to_call_analytics(flags),
-                &mut state,
-                &header
-                    .decode(self.client.engine().params().eip1559_transition)
-                    .map_err(errors::decode)?,
-            )
+            .call(&signed, to_call_analytics(flags), &mut state, &header)
             .map(TraceResults::from)
             .map_err(errors::call)
     }
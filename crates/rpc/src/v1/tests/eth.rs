@@ -40,10 +40,10 @@ use v1::{
         dispatch::{self, FullDispatcher},
         nonce,
     },
-    impls::{EthClient, EthClientOptions, SigningUnsafeClient},
+    impls::{EthClient, EthClientOptions, SigningUnsafeClient, TracesClient},
     metadata::Metadata,
     tests::helpers::{Config, TestSnapshotService, TestSyncProvider},
-    traits::{Eth, EthSigning},
+    traits::{Eth, EthSigning, Traces},
 };
 
 fn account_provider() -> Arc<AccountProvider> {
@@ -157,10 +157,12 @@ impl EthTester {
             FullDispatcher::new(client.clone(), miner_service.clone(), reservations, 50);
         let signer = Arc::new(dispatch::Signer::new(account_provider.clone())) as _;
         let eth_sign = SigningUnsafeClient::new(&signer, dispatcher);
+        let traces_client = TracesClient::new(&client);
 
         let mut handler = IoHandler::default();
         handler.extend_with(eth_client.to_delegate());
         handler.extend_with(eth_sign.to_delegate());
+        handler.extend_with(traces_client.to_delegate());
 
         EthTester {
             _miner: miner_service,
@@ -613,6 +615,126 @@ fn starting_nonce_test() {
     assert_eq!(r#"{"jsonrpc":"2.0","result":"0x100","id":15}"#, &sample);
 }
 
+// Gives the account code returning the value of storage slot 0, and sets that slot to 0x2a.
+const SLOT_0_OVERRIDE: &'static str = r#"{
+	"0x0000000000000000000000000000000000000100": {
+		"code": "0x60005460005260206000f3",
+		"stateDiff": {
+			"0x0000000000000000000000000000000000000000000000000000000000000000": "0x000000000000000000000000000000000000000000000000000000000000002a"
+		}
+	}
+}"#;
+
+#[test]
+fn eth_call_with_overrides() {
+    let tester = EthTester::from_spec(
+        Spec::load(&env::temp_dir(), TRANSACTION_COUNT_SPEC).expect("invalid chain spec"),
+    );
+
+    let request = format!(
+        r#"{{
+		"jsonrpc": "2.0",
+		"method": "eth_call",
+		"params": [{{"to": "0x0000000000000000000000000000000000000100"}}, "latest", {}],
+		"id": 1
+	}}"#,
+        SLOT_0_OVERRIDE
+    );
+    let response = r#"{"jsonrpc":"2.0","result":"0x000000000000000000000000000000000000000000000000000000000000002a","id":1}"#;
+    assert_eq!(
+        tester.handler.handle_request_sync(&request).unwrap(),
+        response
+    );
+
+    // Without the overrides the account has no code.
+    let request = r#"{
+		"jsonrpc": "2.0",
+		"method": "eth_call",
+		"params": [{"to": "0x0000000000000000000000000000000000000100"}, "latest"],
+		"id": 1
+	}"#;
+    let response = r#"{"jsonrpc":"2.0","result":"0x","id":1}"#;
+    assert_eq!(
+        tester.handler.handle_request_sync(request).unwrap(),
+        response
+    );
+
+    // Code returning the block number.
+    let request = r#"{
+		"jsonrpc": "2.0",
+		"method": "eth_call",
+		"params": [
+			{"to": "0x0000000000000000000000000000000000000100"},
+			"latest",
+			{"0x0000000000000000000000000000000000000100": {"code": "0x4360005260206000f3"}},
+			{"number": "0x10"}
+		],
+		"id": 1
+	}"#;
+    let response = r#"{"jsonrpc":"2.0","result":"0x0000000000000000000000000000000000000000000000000000000000000010","id":1}"#;
+    assert_eq!(
+        tester.handler.handle_request_sync(request).unwrap(),
+        response
+    );
+}
+
+#[test]
+fn eth_estimate_gas_with_overrides() {
+    let tester = EthTester::from_spec(
+        Spec::load(&env::temp_dir(), TRANSACTION_COUNT_SPEC).expect("invalid chain spec"),
+    );
+
+    let request = r#"{
+		"jsonrpc": "2.0",
+		"method": "eth_estimateGas",
+		"params": [{"to": "0x0000000000000000000000000000000000000100"}, "latest"],
+		"id": 1
+	}"#;
+    let response = r#"{"jsonrpc":"2.0","result":"0x5208","id":1}"#;
+    assert_eq!(
+        tester.handler.handle_request_sync(request).unwrap(),
+        response
+    );
+
+    // Code storing 0x2a in slot 0, costing 6 gas for the pushes and 20000 for the store.
+    let request = r#"{
+		"jsonrpc": "2.0",
+		"method": "eth_estimateGas",
+		"params": [
+			{"to": "0x0000000000000000000000000000000000000100"},
+			"latest",
+			{"0x0000000000000000000000000000000000000100": {"code": "0x602a600055"}}
+		],
+		"id": 1
+	}"#;
+    let response = r#"{"jsonrpc":"2.0","result":"0xa02e","id":1}"#;
+    assert_eq!(
+        tester.handler.handle_request_sync(request).unwrap(),
+        response
+    );
+}
+
+#[test]
+fn trace_call_with_overrides() {
+    let tester = EthTester::from_spec(
+        Spec::load(&env::temp_dir(), TRANSACTION_COUNT_SPEC).expect("invalid chain spec"),
+    );
+
+    let request = format!(
+        r#"{{
+		"jsonrpc": "2.0",
+		"method": "trace_call",
+		"params": [{{"to": "0x0000000000000000000000000000000000000100"}}, ["trace"], "latest", {}],
+		"id": 1
+	}}"#,
+        SLOT_0_OVERRIDE
+    );
+    let response = tester.handler.handle_request_sync(&request).unwrap();
+    assert!(response.starts_with(
+        r#"{"jsonrpc":"2.0","result":{"output":"0x000000000000000000000000000000000000000000000000000000000000002a","#
+    ));
+}
+
 register_test!(
     eth_transaction_count_1,
     verify_transaction_counts,
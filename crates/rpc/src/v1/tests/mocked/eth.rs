@@ -987,6 +987,96 @@ fn rpc_eth_call_default_block() {
     );
 }
 
+#[test]
+fn rpc_eth_call_with_overrides() {
+    let tester = EthTester::default();
+    tester.client.set_execution_result(Ok(Executed {
+        exception: None,
+        gas: U256::zero(),
+        gas_used: U256::from(0xff30),
+        refunded: U256::from(0x5),
+        cumulative_gas_used: U256::zero(),
+        logs: vec![],
+        contracts_created: vec![],
+        output: vec![0x12, 0x34, 0xff],
+        trace: vec![],
+        vm_trace: None,
+        state_diff: None,
+    }));
+
+    let request = r#"{
+		"jsonrpc": "2.0",
+		"method": "eth_call",
+		"params": [{
+			"from": "0xb60e8dd61c5d32be8058bb8eb970870f07233155",
+			"to": "0xd46e8dd67c5d32be8058bb8eb970870f07244567"
+		},
+		"latest",
+		{
+			"0xd46e8dd67c5d32be8058bb8eb970870f07244567": {
+				"balance": "0x9184e72a",
+				"code": "0x600160005260206000f3"
+			}
+		},
+		{
+			"number": "0x10",
+			"time": "0x5f5e100"
+		}],
+		"id": 1
+	}"#;
+    let response = r#"{"jsonrpc":"2.0","result":"0x1234ff","id":1}"#;
+
+    assert_eq!(
+        tester.io.handle_request_sync(request),
+        Some(response.to_owned())
+    );
+
+    let request = r#"{
+		"jsonrpc": "2.0",
+		"method": "eth_call",
+		"params": [{
+			"to": "0xd46e8dd67c5d32be8058bb8eb970870f07244567"
+		},
+		"latest",
+		{
+			"0xd46e8dd67c5d32be8058bb8eb970870f07244567": {
+				"state": {},
+				"stateDiff": {}
+			}
+		}],
+		"id": 1
+	}"#;
+    let response = r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Couldn't parse parameters: stateDiff","data":"\"state and stateDiff can not be overridden at the same time\""},"id":1}"#;
+
+    assert_eq!(
+        tester.io.handle_request_sync(request),
+        Some(response.to_owned())
+    );
+}
+
+#[test]
+fn rpc_eth_create_access_list() {
+    let tester = EthTester::default();
+
+    let request = r#"{
+		"jsonrpc": "2.0",
+		"method": "eth_createAccessList",
+		"params": [{
+			"from": "0xb60e8dd61c5d32be8058bb8eb970870f07233155",
+			"to": "0xd46e8dd67c5d32be8058bb8eb970870f07244567",
+			"data": "0xd46e8dd67c5d32be8d46e8dd67c5d32be8058bb8eb970870f072445675058bb8eb970870f072445675"
+		},
+		"latest"],
+		"id": 1
+	}"#;
+    let response = r#"{"jsonrpc":"2.0","result":{"accessList":[],"gasUsed":"0x5208"},"id":1}"#;
+
+    assert_eq!(
+        tester.io.handle_request_sync(request),
+        Some(response.to_owned())
+    );
+}
+
 #[test]
 fn rpc_eth_estimate_gas() {
     let tester = EthTester::default();
@@ -0,0 +1,79 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of OpenEthereum.
+
+// OpenEthereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// OpenEthereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with OpenEthereum.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::{env, sync::Arc};
+
+use ethcore::{client::TestBlockChainClient, spec::Spec};
+
+use jsonrpc_core::IoHandler;
+use v1::{Hbbft, HbbftClient};
+
+fn io_with_client(client: TestBlockChainClient) -> IoHandler {
+    let mut io = IoHandler::new();
+    io.extend_with(HbbftClient::new(Arc::new(client)).to_delegate());
+    io
+}
+
+fn io() -> IoHandler {
+    io_with_client(TestBlockChainClient::new())
+}
+
+fn hbbft_io() -> IoHandler {
+    let spec = Spec::load(
+        &env::temp_dir(),
+        include_bytes!("../../../../../ethcore/res/chainspec/honey_badger_bft.json") as &[u8],
+    )
+    .expect("invalid chain spec");
+    io_with_client(TestBlockChainClient::new_with_spec(spec))
+}
+
+#[test]
+fn rpc_hbbft_unsupported_engine() {
+    let io = io();
+    for method in &[
+        "hbbft_posdaoEpoch",
+        "hbbft_validatorStatus",
+        "hbbft_keygenStatus",
+        "hbbft_honeyBadgerStatus",
+        "hbbft_sealingStatus",
+        "hbbft_validatorPeers",
+        "hbbft_validatorScores",
+    ] {
+        let request = format!(
+            r#"{{"jsonrpc": "2.0", "method": "{}", "params": [], "id": 1}}"#,
+            method
+        );
+        let response = r#"{"jsonrpc":"2.0","error":{"code":-32000,"message":"The hbbft API requires the HoneyBadgerBFT engine."},"id":1}"#;
+        assert_eq!(io.handle_request_sync(&request), Some(response.to_owned()));
+    }
+}
+
+#[test]
+fn rpc_hbbft_validator_scores() {
+    let io = hbbft_io();
+    let request = r#"{"jsonrpc": "2.0", "method": "hbbft_validatorScores", "params": [], "id": 1}"#;
+    let response = r#"{"jsonrpc":"2.0","result":[],"id":1}"#;
+    assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_hbbft_honey_badger_status() {
+    let io = hbbft_io();
+    let request =
+        r#"{"jsonrpc": "2.0", "method": "hbbft_honeyBadgerStatus", "params": [], "id": 1}"#;
+    let response = r#"{"jsonrpc":"2.0","result":{"epoch":null,"hasContributed":false,"contributors":[],"lastBatch":null},"id":1}"#;
+    assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
@@ -20,8 +20,9 @@ use jsonrpc_core::{BoxFuture, Result};
 use jsonrpc_derive::rpc;
 
 use v1::types::{
-    BlockNumber, Bytes, CallRequest, EthAccount, EthFeeHistory, Filter, FilterChanges, Index, Log,
-    Receipt, RichBlock, SyncStatus, Transaction, Work,
+    AccessListWithGasUsed, BlockNumber, BlockOverride, Bytes, CallRequest, EthAccount,
+    EthFeeHistory, Filter, FilterChanges, Index, Log, Receipt, RichBlock, StateOverride,
+    SyncStatus, Transaction, Work,
 };
 
 /// Eth rpc interface.
@@ -130,12 +131,34 @@ pub trait Eth {
     fn submit_transaction(&self, _: Bytes) -> Result<H256>;
 
     /// Call contract, returning the output data.
+    /// The state and block overrides are applied to a throwaway state before the call.
     #[rpc(name = "eth_call")]
-    fn call(&self, _: CallRequest, _: Option<BlockNumber>) -> BoxFuture<Bytes>;
+    fn call(
+        &self,
+        _: CallRequest,
+        _: Option<BlockNumber>,
+        _: Option<StateOverride>,
+        _: Option<BlockOverride>,
+    ) -> BoxFuture<Bytes>;
 
     /// Estimate gas needed for execution of given contract.
+    /// The state and block overrides are applied to a throwaway state before the estimation.
     #[rpc(name = "eth_estimateGas")]
-    fn estimate_gas(&self, _: CallRequest, _: Option<BlockNumber>) -> BoxFuture<U256>;
+    fn estimate_gas(
+        &self,
+        _: CallRequest,
+        _: Option<BlockNumber>,
+        _: Option<StateOverride>,
+        _: Option<BlockOverride>,
+    ) -> BoxFuture<U256>;
+
+    /// Creates an EIP-2930 access list for the given call, along with the gas used by the call with it.
+    #[rpc(name = "eth_createAccessList")]
+    fn create_access_list(
+        &self,
+        _: CallRequest,
+        _: Option<BlockNumber>,
+    ) -> BoxFuture<AccessListWithGasUsed>;
 
     /// Get transaction by its hash.
     #[rpc(name = "eth_getTransactionByHash")]
@@ -0,0 +1,57 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of OpenEthereum.
+
+// OpenEthereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// OpenEthereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with OpenEthereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! HoneyBadgerBFT RPC interface.
+
+use jsonrpc_core::Result;
+use jsonrpc_derive::rpc;
+
+use v1::types::{
+    HbbftKeygenStatus, HbbftSealing, HbbftValidatorPeer, HbbftValidatorScore, HbbftValidatorStatus,
+    HoneyBadgerStatus, PosdaoEpoch,
+};
+
+/// HoneyBadgerBFT RPC interface, giving validator operators insight into the consensus.
+#[rpc(server)]
+pub trait Hbbft {
+    /// Returns the POSDAO epoch of the latest block.
+    #[rpc(name = "hbbft_posdaoEpoch")]
+    fn posdao_epoch(&self) -> Result<PosdaoEpoch>;
+
+    /// Returns the role of this node in the current and pending validator set.
+    #[rpc(name = "hbbft_validatorStatus")]
+    fn validator_status(&self) -> Result<HbbftValidatorStatus>;
+
+    /// Returns the progress of the key generation for the pending validator set.
+    #[rpc(name = "hbbft_keygenStatus")]
+    fn keygen_status(&self) -> Result<HbbftKeygenStatus>;
+
+    /// Returns the state of the honey badger consensus.
+    #[rpc(name = "hbbft_honeyBadgerStatus")]
+    fn honey_badger_status(&self) -> Result<HoneyBadgerStatus>;
+
+    /// Returns the sealing state of the blocks currently being signed.
+    #[rpc(name = "hbbft_sealingStatus")]
+    fn sealing_status(&self) -> Result<HbbftSealing>;
+
+    /// Returns the connection status of the validators reserved as peers.
+    #[rpc(name = "hbbft_validatorPeers")]
+    fn validator_peers(&self) -> Result<Vec<HbbftValidatorPeer>>;
+
+    /// Returns the misbehaviour of the validators within the recent history, as seen by this node.
+    #[rpc(name = "hbbft_validatorScores")]
+    fn validator_scores(&self) -> Result<Vec<HbbftValidatorScore>>;
+}
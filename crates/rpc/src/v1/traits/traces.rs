@@ -20,8 +20,8 @@ use ethereum_types::H256;
 use jsonrpc_core::Result;
 use jsonrpc_derive::rpc;
 use v1::types::{
-    BlockNumber, Bytes, CallRequest, Index, LocalizedTrace, TraceFilter, TraceOptions,
-    TraceResults, TraceResultsWithTransactionHash,
+    BlockNumber, BlockOverride, Bytes, CallRequest, Index, LocalizedTrace, StateOverride,
+    TraceFilter, TraceOptions, TraceResults, TraceResultsWithTransactionHash,
 };
 
 /// Traces specific rpc interface.
@@ -47,9 +47,16 @@ pub trait Traces {
     fn block_traces(&self, _: BlockNumber) -> Result<Option<Vec<LocalizedTrace>>>;
 
     /// Executes the given call and returns a number of possible traces for it.
+    /// The state and block overrides are applied to a throwaway state before the call.
     #[rpc(name = "trace_call")]
-    fn call(&self, _: CallRequest, _: TraceOptions, _: Option<BlockNumber>)
-        -> Result<TraceResults>;
+    fn call(
+        &self,
+        _: CallRequest,
+        _: TraceOptions,
+        _: Option<BlockNumber>,
+        _: Option<StateOverride>,
+        _: Option<BlockOverride>,
+    ) -> Result<TraceResults>;
 
     /// Executes all given calls and returns a number of possible traces for each of it.
     #[rpc(name = "trace_callMany")]
@@ -0,0 +1,223 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of OpenEthereum.
+
+// OpenEthereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// OpenEthereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with OpenEthereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! HoneyBadgerBFT engine status.
+
+use std::collections::BTreeMap;
+
+use ethcore::engines;
+use ethereum_types::{Address, U64};
+
+/// POSDAO epoch of the latest block.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PosdaoEpoch {
+    /// POSDAO epoch number.
+    pub epoch: U64,
+    /// Block the POSDAO epoch started at.
+    pub start_block: U64,
+}
+
+impl From<engines::PosdaoEpochStatus> for PosdaoEpoch {
+    fn from(s: engines::PosdaoEpochStatus) -> Self {
+        PosdaoEpoch {
+            epoch: s.epoch.into(),
+            start_block: s.start_block.into(),
+        }
+    }
+}
+
+/// Role of the engine signer in the validator set.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HbbftValidatorStatus {
+    /// Mining address of the engine signer.
+    pub mining_address: Option<Address>,
+    /// Mining address of the engine signer taking over at the next POSDAO epoch.
+    pub next_mining_address: Option<Address>,
+    /// Staking address of the pool of the mining address.
+    pub staking_address: Option<Address>,
+    /// Whether the node is a validator of the current POSDAO epoch.
+    pub is_current_validator: bool,
+    /// Whether the node is a validator of the next POSDAO epoch.
+    pub is_pending_validator: bool,
+    /// Whether the pool of the node is staked.
+    pub is_staked: bool,
+    /// Whether the node is flagged as available.
+    pub is_available: bool,
+}
+
+impl From<engines::ValidatorStatus> for HbbftValidatorStatus {
+    fn from(s: engines::ValidatorStatus) -> Self {
+        HbbftValidatorStatus {
+            mining_address: s.mining_address,
+            next_mining_address: s.next_mining_address,
+            staking_address: s.staking_address,
+            is_current_validator: s.is_current_validator,
+            is_pending_validator: s.is_pending_validator,
+            is_staked: s.is_staked,
+            is_available: s.is_available,
+        }
+    }
+}
+
+/// Progress of the key generation for the pending validator set.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HbbftKeygenStatus {
+    /// Key generation round.
+    pub round: U64,
+    /// Number of pending validators.
+    pub pending_validators: U64,
+    /// Number of Parts written.
+    pub parts: U64,
+    /// Number of Acks written.
+    pub acks: U64,
+    /// Whether the Parts and Acks of all pending validators are available.
+    pub all_parts_acks_available: bool,
+}
+
+impl From<engines::KeygenStatus> for HbbftKeygenStatus {
+    fn from(s: engines::KeygenStatus) -> Self {
+        HbbftKeygenStatus {
+            round: s.round.into(),
+            pending_validators: (s.num_pending_validators as u64).into(),
+            parts: s.num_parts.into(),
+            acks: s.num_acks.into(),
+            all_parts_acks_available: s.all_parts_acks_available,
+        }
+    }
+}
+
+/// Contributions to a honey badger epoch.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HbbftBatch {
+    /// Honey badger epoch.
+    pub epoch: U64,
+    /// Mining addresses of the contributing validators.
+    pub contributors: Vec<Address>,
+}
+
+impl From<engines::BatchStatus> for HbbftBatch {
+    fn from(s: engines::BatchStatus) -> Self {
+        HbbftBatch {
+            epoch: s.epoch.into(),
+            contributors: s.contributors,
+        }
+    }
+}
+
+/// State of the honey badger consensus.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HoneyBadgerStatus {
+    /// Current honey badger epoch, if the node is a validator.
+    pub epoch: Option<U64>,
+    /// Whether the node has contributed to the current epoch.
+    pub has_contributed: bool,
+    /// Mining addresses of the validators that have contributed to the current epoch.
+    pub contributors: Vec<Address>,
+    /// Last batch output by the consensus.
+    pub last_batch: Option<HbbftBatch>,
+}
+
+impl From<engines::HoneyBadgerStatus> for HoneyBadgerStatus {
+    fn from(s: engines::HoneyBadgerStatus) -> Self {
+        HoneyBadgerStatus {
+            epoch: s.epoch.map(Into::into),
+            has_contributed: s.has_contributed,
+            contributors: s.contributors,
+            last_batch: s.last_batch.map(Into::into),
+        }
+    }
+}
+
+/// Connection status of a validator reserved as peer.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HbbftValidatorPeer {
+    /// Mining address of the validator.
+    pub mining_address: Address,
+    /// Enode URL of the validator, if its endpoint is known.
+    pub enode: Option<String>,
+    /// Whether the validator is connected.
+    pub is_connected: bool,
+}
+
+impl From<engines::ValidatorPeerStatus> for HbbftValidatorPeer {
+    fn from(s: engines::ValidatorPeerStatus) -> Self {
+        HbbftValidatorPeer {
+            mining_address: s.mining_address,
+            enode: s.enode,
+            is_connected: s.is_connected,
+        }
+    }
+}
+
+/// Misbehaviour of a validator within the recent history, as recorded by this node.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HbbftValidatorScore {
+    /// Mining address of the validator.
+    pub mining_address: Address,
+    /// Number of messages for epochs or blocks that were decided before the grace window.
+    pub late_messages: U64,
+    /// Number of epochs the validator did not take part in the broadcasts of.
+    pub missing_contributions: U64,
+    /// Number of messages that could not be decoded or handled.
+    pub invalid_messages: U64,
+    /// Number of faults reported by the consensus algorithm.
+    pub faults: U64,
+    /// Total number of misbehaviours, regardless of their kind.
+    pub total: U64,
+}
+
+impl From<engines::ValidatorScoreStatus> for HbbftValidatorScore {
+    fn from(s: engines::ValidatorScoreStatus) -> Self {
+        HbbftValidatorScore {
+            mining_address: s.mining_address,
+            late_messages: s.late_messages.into(),
+            missing_contributions: s.missing_contributions.into(),
+            invalid_messages: s.invalid_messages.into(),
+            faults: s.faults.into(),
+            total: (s.late_messages + s.missing_contributions + s.invalid_messages + s.faults)
+                .into(),
+        }
+    }
+}
+
+/// State of threshold signing a block.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum HbbftSealingStatus {
+    /// Signature shares are still being collected.
+    Ongoing,
+    /// The block seal is complete.
+    Complete,
+}
+
+impl From<engines::SealingStatus> for HbbftSealingStatus {
+    fn from(s: engines::SealingStatus) -> Self {
+        match s {
+            engines::SealingStatus::Ongoing => HbbftSealingStatus::Ongoing,
+            engines::SealingStatus::Complete => HbbftSealingStatus::Complete,
+        }
+    }
+}
+
+/// Sealing state of the blocks currently being signed, by block number.
+pub type HbbftSealing = BTreeMap<U64, HbbftSealingStatus>;
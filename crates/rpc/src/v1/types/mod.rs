@@ -31,6 +31,10 @@ pub use self::{
     eip191::{EIP191Version, PresignedTransaction},
     fee_history::EthFeeHistory,
     filter::{Filter, FilterChanges},
+    hbbft::{
+        HbbftBatch, HbbftKeygenStatus, HbbftSealing, HbbftSealingStatus, HbbftValidatorPeer,
+        HbbftValidatorScore, HbbftValidatorStatus, HoneyBadgerStatus, PosdaoEpoch,
+    },
     histogram::Histogram,
     index::Index,
     log::Log,
@@ -39,6 +43,7 @@ pub use self::{
     receipt::Receipt,
     rpc_settings::RpcSettings,
     secretstore::EncryptedDocumentKey,
+    state_override::{AccountOverride, BlockOverride, StateOverride},
     sync::{
         ChainStatus, EthProtocolInfo, PeerInfo, PeerNetworkInfo, PeerProtocolsInfo, Peers,
         SyncInfo, SyncStatus, TransactionStats,
@@ -46,7 +51,7 @@ pub use self::{
     trace::{LocalizedTrace, TraceResults, TraceResultsWithTransactionHash},
     trace_filter::TraceFilter,
     transaction::{LocalTransactionStatus, RichRawTransaction, Transaction},
-    transaction_access_list::{AccessList, AccessListItem},
+    transaction_access_list::{AccessList, AccessListItem, AccessListWithGasUsed},
     transaction_condition::TransactionCondition,
     transaction_request::TransactionRequest,
     work::Work,
@@ -65,6 +70,7 @@ mod derivation;
 mod eip191;
 mod fee_history;
 mod filter;
+mod hbbft;
 mod histogram;
 mod index;
 mod log;
@@ -73,6 +79,7 @@ mod provenance;
 mod receipt;
 mod rpc_settings;
 mod secretstore;
+mod state_override;
 mod sync;
 mod trace;
 mod trace_filter;
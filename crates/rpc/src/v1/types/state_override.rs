@@ -0,0 +1,253 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of OpenEthereum.
+
+// OpenEthereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// OpenEthereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with OpenEthereum.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::{collections::BTreeMap, convert::TryFrom};
+
+use ethcore::state::{AccountOverride as EthAccountOverride, Overridable};
+use ethereum_types::{H160, H256, U256, U64};
+use jsonrpc_core::Error as RpcError;
+use types::header::Header;
+use v1::{
+    helpers::errors::{invalid_params, state_pruned, unsupported},
+    types::Bytes,
+};
+
+/// State overrides of a call, by account address.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+pub struct StateOverride(pub BTreeMap<H160, AccountOverride>);
+
+impl StateOverride {
+    /// Applies the overrides to the throwaway state the call is executed on.
+    pub fn apply<T: Overridable>(self, state: &mut T) -> Result<(), RpcError> {
+        for (address, account) in self.0 {
+            state
+                .apply_override(&address, &EthAccountOverride::try_from(account)?)
+                .map_err(|_| state_pruned())?;
+        }
+        Ok(())
+    }
+}
+
+/// Account fields to override before a call.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountOverride {
+    /// Balance
+    pub balance: Option<U256>,
+    /// Nonce
+    pub nonce: Option<U256>,
+    /// Code
+    pub code: Option<Bytes>,
+    /// Storage, replacing the whole storage of the account
+    pub state: Option<BTreeMap<H256, H256>>,
+    /// Storage slots to change
+    pub state_diff: Option<BTreeMap<H256, H256>>,
+}
+
+impl TryFrom<AccountOverride> for EthAccountOverride {
+    type Error = RpcError;
+
+    fn try_from(account: AccountOverride) -> Result<Self, Self::Error> {
+        if account.state.is_some() && account.state_diff.is_some() {
+            return Err(invalid_params(
+                "stateDiff",
+                "state and stateDiff can not be overridden at the same time",
+            ));
+        }
+
+        Ok(EthAccountOverride {
+            balance: account.balance,
+            nonce: account.nonce,
+            code: account.code.map(Into::into),
+            storage: account.state.map(|state| state.into_iter().collect()),
+            storage_diff: account
+                .state_diff
+                .map(|diff| diff.into_iter().collect())
+                .unwrap_or_default(),
+        })
+    }
+}
+
+/// Block fields to override before a call.
+///
+/// All fields of geth's block overrides are accepted, the ones without an effect on the
+/// calls of this client are rejected when they are set.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockOverride {
+    /// Number
+    pub number: Option<U64>,
+    /// Timestamp
+    pub time: Option<U64>,
+    /// Base fee
+    #[serde(alias = "baseFeePerGas")]
+    pub base_fee: Option<U256>,
+    /// Author
+    #[serde(alias = "feeRecipient")]
+    pub coinbase: Option<H160>,
+    /// Difficulty
+    pub difficulty: Option<U256>,
+    /// Gas limit, unsupported since calls are not bounded by the block gas limit
+    pub gas_limit: Option<U64>,
+    /// Randomness beacon output, unsupported
+    #[serde(alias = "prevRandao")]
+    pub random: Option<H256>,
+    /// Blob base fee, unsupported
+    pub blob_base_fee: Option<U256>,
+}
+
+impl BlockOverride {
+    /// Applies the overrides to the header the call is executed on.
+    pub fn apply(&self, header: &mut Header) -> Result<(), RpcError> {
+        let unsupported_field = if self.gas_limit.is_some() {
+            Some("gasLimit")
+        } else if self.random.is_some() {
+            Some("random")
+        } else if self.blob_base_fee.is_some() {
+            Some("blobBaseFee")
+        } else {
+            None
+        };
+        if let Some(field) = unsupported_field {
+            return Err(unsupported(
+                format!("Unsupported block override field: {}", field),
+                None,
+            ));
+        }
+
+        if let Some(number) = self.number {
+            header.set_number(number.as_u64());
+        }
+        if let Some(time) = self.time {
+            header.set_timestamp(time.as_u64());
+        }
+        if let Some(base_fee) = self.base_fee {
+            header.set_base_fee(Some(base_fee));
+        }
+        if let Some(coinbase) = self.coinbase {
+            header.set_author(coinbase);
+        }
+        if let Some(difficulty) = self.difficulty {
+            header.set_difficulty(difficulty);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AccountOverride, BlockOverride, StateOverride};
+    use ethcore::state::AccountOverride as EthAccountOverride;
+    use ethereum_types::{H160, H256, U256, U64};
+    use serde_json;
+    use std::convert::TryFrom;
+    use types::header::Header;
+
+    #[test]
+    fn state_override_deserialize() {
+        let s = r#"{
+			"0x0000000000000000000000000000000000000001": {
+				"balance": "0x10",
+				"nonce": "0x2",
+				"code": "0x6000",
+				"stateDiff": {
+					"0x0000000000000000000000000000000000000000000000000000000000000001": "0x0000000000000000000000000000000000000000000000000000000000000002"
+				}
+			}
+		}"#;
+        let deserialized: StateOverride = serde_json::from_str(s).unwrap();
+        let account = deserialized.0[&H160::from_low_u64_be(1)].clone();
+        assert_eq!(account.balance, Some(U256::from(0x10)));
+        assert_eq!(account.nonce, Some(U256::from(2)));
+        assert_eq!(account.state, None);
+
+        let account = EthAccountOverride::try_from(account).unwrap();
+        assert_eq!(account.code, Some(vec![0x60, 0x00]));
+        assert_eq!(
+            account.storage_diff[&H256::from_low_u64_be(1)],
+            H256::from_low_u64_be(2)
+        );
+    }
+
+    #[test]
+    fn state_and_state_diff_are_exclusive() {
+        let account = AccountOverride {
+            state: Some(Default::default()),
+            state_diff: Some(Default::default()),
+            ..Default::default()
+        };
+        assert!(EthAccountOverride::try_from(account).is_err());
+    }
+
+    #[test]
+    fn block_override_deserialize() {
+        let s = r#"{"number": "0x10", "time": "0x20", "baseFee": "0x7"}"#;
+        let deserialized: BlockOverride = serde_json::from_str(s).unwrap();
+        assert_eq!(
+            deserialized,
+            BlockOverride {
+                number: Some(U64::from(0x10)),
+                time: Some(U64::from(0x20)),
+                base_fee: Some(U256::from(7)),
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn block_override_deserialize_geth_fields() {
+        // Block overrides as sent by Foundry and viem.
+        let s = r#"{
+			"number": "0x10",
+			"time": "0x20",
+			"baseFeePerGas": "0x7",
+			"feeRecipient": "0x0000000000000000000000000000000000000002",
+			"difficulty": "0x3",
+			"gasLimit": "0x1c9c380",
+			"prevRandao": "0x0000000000000000000000000000000000000000000000000000000000000004",
+			"blobBaseFee": "0x1"
+		}"#;
+        let deserialized: BlockOverride = serde_json::from_str(s).unwrap();
+        assert_eq!(
+            deserialized,
+            BlockOverride {
+                number: Some(U64::from(0x10)),
+                time: Some(U64::from(0x20)),
+                base_fee: Some(U256::from(7)),
+                coinbase: Some(H160::from_low_u64_be(2)),
+                difficulty: Some(U256::from(3)),
+                gas_limit: Some(U64::from(30_000_000)),
+                random: Some(H256::from_low_u64_be(4)),
+                blob_base_fee: Some(U256::from(1)),
+            }
+        );
+
+        let mut header = Header::default();
+        let error = deserialized.apply(&mut header).unwrap_err();
+        assert_eq!(error.message, "Unsupported block override field: gasLimit");
+
+        let s =
+            r#"{"coinbase": "0x0000000000000000000000000000000000000002", "difficulty": "0x3"}"#;
+        let deserialized: BlockOverride = serde_json::from_str(s).unwrap();
+        deserialized.apply(&mut header).unwrap();
+        assert_eq!(*header.author(), H160::from_low_u64_be(2));
+        assert_eq!(*header.difficulty(), U256::from(3));
+
+        assert!(serde_json::from_str::<BlockOverride>(r#"{"gasLimits": "0x1"}"#).is_err());
+    }
+}
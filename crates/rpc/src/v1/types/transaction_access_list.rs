@@ -1,4 +1,4 @@
-use ethereum_types::{H160, H256};
+use ethereum_types::{H160, H256, U256};
 use serde::Serialize;
 use std::vec::Vec;
 use types::transaction::AccessListItem as InnerAccessListItem;
@@ -34,3 +34,16 @@ impl From<AccessListItem> for InnerAccessListItem {
         (item.address, item.storage_keys)
     }
 }
+
+/// Access list created for a call, with the gas the call uses with it.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccessListWithGasUsed {
+    /// Access list
+    pub access_list: AccessList,
+    /// Gas used by the call with the access list
+    pub gas_used: U256,
+    /// Error the call with the access list failed with
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
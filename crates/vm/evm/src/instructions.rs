@@ -17,6 +17,7 @@
 //! VM Instructions list and utility functions
 
 pub use self::Instruction::*;
+use vm::Schedule;
 
 macro_rules! enum_with_from_u8 {
 	(
@@ -180,6 +181,14 @@ enum_with_from_u8! {
         GAS = 0x5a,
         #[doc = "set a potential jump destination"]
         JUMPDEST = 0x5b,
+        #[doc = "load word from transient storage"]
+        TLOAD = 0x5c,
+        #[doc = "save word to transient storage"]
+        TSTORE = 0x5d,
+        #[doc = "copy memory areas"]
+        MCOPY = 0x5e,
+        #[doc = "place the constant value 0 on stack"]
+        PUSH0 = 0x5f,
 
         #[doc = "place 1 byte item on stack"]
         PUSH1 = 0x60,
@@ -323,13 +332,6 @@ enum_with_from_u8! {
         #[doc = "Makes a log entry, 4 topics."]
         LOG4 = 0xa4,
 
-        #[doc = "Marks the entry point to a subroutine."]
-        BEGINSUB = 0x5c,
-        #[doc = "Returns from a subroutine."]
-        RETURNSUB = 0x5d,
-        #[doc = "Jumps to a defined BEGINSUB subroutine."]
-        JUMPSUB = 0x5e,
-
         #[doc = "create a new account with associated code"]
         CREATE = 0xf0,
         #[doc = "message-call into an account"]
@@ -401,8 +403,25 @@ impl Instruction {
     pub fn info(&self) -> &'static InstructionInfo {
         INSTRUCTIONS[*self as usize].as_ref().expect("A instruction is defined in Instruction enum, but it is not found in InstructionInfo struct; this indicates a logic failure in the code.")
     }
+
+    /// Returns the instruction info under the given schedule.
+    /// With subroutines enabled the opcodes of `TLOAD`, `TSTORE` and `MCOPY` are
+    /// `BEGINSUB`, `RETURNSUB` and `JUMPSUB`.
+    pub fn info_for(&self, schedule: &Schedule) -> &'static InstructionInfo {
+        if schedule.have_subs && *self >= BEGINSUB && *self <= JUMPSUB {
+            return &SUBROUTINE_INSTRUCTIONS[(*self as u8 - BEGINSUB as u8) as usize];
+        }
+        self.info()
+    }
 }
 
+/// Marks the entry point to a subroutine (EIP-2315).
+pub const BEGINSUB: Instruction = TLOAD;
+/// Returns from a subroutine (EIP-2315).
+pub const RETURNSUB: Instruction = TSTORE;
+/// Jumps to a defined BEGINSUB subroutine (EIP-2315).
+pub const JUMPSUB: Instruction = MCOPY;
+
 #[derive(PartialEq, Clone, Copy)]
 pub enum GasPriceTier {
     /// 0 Zero
@@ -532,6 +551,10 @@ lazy_static! {
         arr[MSIZE as usize] = Some(InstructionInfo::new("MSIZE", 0, 1, GasPriceTier::Base));
         arr[GAS as usize] = Some(InstructionInfo::new("GAS", 0, 1, GasPriceTier::Base));
         arr[JUMPDEST as usize] = Some(InstructionInfo::new("JUMPDEST", 0, 0, GasPriceTier::Special));
+        arr[TLOAD as usize] = Some(InstructionInfo::new("TLOAD", 1, 1, GasPriceTier::Special));
+        arr[TSTORE as usize] = Some(InstructionInfo::new("TSTORE", 2, 0, GasPriceTier::Special));
+        arr[MCOPY as usize] = Some(InstructionInfo::new("MCOPY", 3, 0, GasPriceTier::VeryLow));
+        arr[PUSH0 as usize] = Some(InstructionInfo::new("PUSH0", 0, 1, GasPriceTier::Base));
         arr[PUSH1 as usize] = Some(InstructionInfo::new("PUSH1", 0, 1, GasPriceTier::VeryLow));
         arr[PUSH2 as usize] = Some(InstructionInfo::new("PUSH2", 0, 1, GasPriceTier::VeryLow));
         arr[PUSH3 as usize] = Some(InstructionInfo::new("PUSH3", 0, 1, GasPriceTier::VeryLow));
@@ -601,9 +624,6 @@ lazy_static! {
         arr[LOG2 as usize] = Some(InstructionInfo::new("LOG2", 4, 0, GasPriceTier::Special));
         arr[LOG3 as usize] = Some(InstructionInfo::new("LOG3", 5, 0, GasPriceTier::Special));
         arr[LOG4 as usize] = Some(InstructionInfo::new("LOG4", 6, 0, GasPriceTier::Special));
-        arr[BEGINSUB as usize] = Some(InstructionInfo::new("BEGINSUB", 0, 0, GasPriceTier::Base));
-        arr[JUMPSUB as usize] = Some(InstructionInfo::new("JUMPSUB", 1, 0, GasPriceTier::High));
-        arr[RETURNSUB as usize] = Some(InstructionInfo::new("RETURNSUB", 0, 0, GasPriceTier::Low));
         arr[CREATE as usize] = Some(InstructionInfo::new("CREATE", 3, 1, GasPriceTier::Special));
         arr[CALL as usize] = Some(InstructionInfo::new("CALL", 7, 1, GasPriceTier::Special));
         arr[CALLCODE as usize] = Some(InstructionInfo::new("CALLCODE", 7, 1, GasPriceTier::Special));
@@ -615,6 +635,14 @@ lazy_static! {
         arr[REVERT as usize] = Some(InstructionInfo::new("REVERT", 2, 0, GasPriceTier::Zero));
         arr
     };
+
+    /// Instruction table of the subroutine instructions, which replace `TLOAD`, `TSTORE`
+    /// and `MCOPY` when subroutines are enabled.
+    static ref SUBROUTINE_INSTRUCTIONS: [InstructionInfo; 3] = [
+        InstructionInfo::new("BEGINSUB", 0, 0, GasPriceTier::Base),
+        InstructionInfo::new("RETURNSUB", 0, 0, GasPriceTier::Low),
+        InstructionInfo::new("JUMPSUB", 1, 0, GasPriceTier::High),
+    ];
 }
 
 /// Maximal number of topics for log instructions
@@ -658,4 +686,21 @@ mod tests {
         assert_eq!(LOG2.log_topics(), Some(2));
         assert_eq!(LOG4.log_topics(), Some(4));
     }
+
+    #[test]
+    fn test_subroutine_info() {
+        assert_eq!(Instruction::from_u8(0x5c), Some(TLOAD));
+        assert_eq!(Instruction::from_u8(0x5d), Some(TSTORE));
+        assert_eq!(Instruction::from_u8(0x5e), Some(MCOPY));
+
+        let cancun = Schedule::new_cancun();
+        assert_eq!(TLOAD.info_for(&cancun).name, "TLOAD");
+        assert_eq!(MCOPY.info_for(&cancun).name, "MCOPY");
+
+        let berlin = Schedule::new_berlin();
+        assert_eq!(BEGINSUB.info_for(&berlin).name, "BEGINSUB");
+        assert_eq!(RETURNSUB.info_for(&berlin).name, "RETURNSUB");
+        assert_eq!(JUMPSUB.info_for(&berlin).args, 1);
+        assert_eq!(ADD.info_for(&berlin).name, "ADD");
+    }
 }
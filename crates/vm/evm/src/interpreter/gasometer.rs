@@ -173,6 +173,9 @@ impl<Gas: evm::CostType> Gasometer<Gas> {
                 };
                 Request::Gas(gas.into())
             }
+            instructions::TLOAD | instructions::TSTORE if !schedule.have_subs => {
+                Request::Gas(Gas::from(vm::schedule::EIP1153_TRANSIENT_STORAGE_GAS))
+            }
             instructions::BALANCE => {
                 let address = u256_to_address(stack.peek(0));
                 Request::Gas(accessed_addresses_gas(&address, schedule.balance_gas))
@@ -232,6 +235,13 @@ impl<Gas: evm::CostType> Gasometer<Gas> {
                     Gas::from_u256(*stack.peek(2))?,
                 )
             }
+            instructions::MCOPY if !schedule.have_subs => {
+                let mem = cmp::max(
+                    mem_needed(stack.peek(0), stack.peek(2))?,
+                    mem_needed(stack.peek(1), stack.peek(2))?,
+                );
+                Request::GasMemCopy(default_gas, mem, Gas::from_u256(*stack.peek(2))?)
+            }
             instructions::EXTCODECOPY => {
                 let address = u256_to_address(stack.peek(0));
                 let gas = accessed_addresses_gas(&address, schedule.extcodecopy_base_gas);
@@ -303,7 +313,8 @@ impl<Gas: evm::CostType> Gasometer<Gas> {
                 let start = stack.peek(1);
                 let len = stack.peek(2);
 
-                let gas = Gas::from(schedule.create_gas);
+                let base = Gas::from(schedule.create_gas);
+                let gas = overflowing!(base.overflow_add(initcode_gas(schedule, len)?));
                 let mem = mem_needed(start, len)?;
 
                 Request::GasMemProvide(gas, mem, None)
@@ -316,6 +327,7 @@ impl<Gas: evm::CostType> Gasometer<Gas> {
                 let word = overflowing!(to_word_size(Gas::from_u256(*len)?));
                 let word_gas = overflowing!(Gas::from(schedule.sha3_word_gas).overflow_mul(word));
                 let gas = overflowing!(base.overflow_add(word_gas));
+                let gas = overflowing!(gas.overflow_add(initcode_gas(schedule, len)?));
                 let mem = mem_needed(start, len)?;
 
                 Request::GasMemProvide(gas, mem, None)
@@ -440,6 +452,21 @@ fn to_word_size<Gas: evm::CostType>(value: Gas) -> (Gas, bool) {
     (gas >> 5, false)
 }
 
+/// Gas for the initcode of `CREATE` and `CREATE2` as by EIP-3860.
+/// Initcode above the size limit fails the creation like running out of gas.
+#[inline]
+fn initcode_gas<Gas: evm::CostType>(schedule: &Schedule, len: &U256) -> vm::Result<Gas> {
+    if !schedule.eip3860 {
+        return Ok(Gas::from(0));
+    }
+    if *len > U256::from(vm::schedule::EIP3860_MAX_INITCODE_SIZE) {
+        return Err(vm::Error::OutOfGas);
+    }
+    let words = overflowing!(to_word_size(Gas::from_u256(*len)?));
+    let word_gas = Gas::from(vm::schedule::EIP3860_INITCODE_WORD_GAS);
+    Ok(overflowing!(word_gas.overflow_mul(words)))
+}
+
 #[inline]
 fn calculate_eip1283_eip2929_sstore_gas<Gas: evm::CostType>(
     schedule: &Schedule,
@@ -34,6 +34,8 @@ pub trait Memory {
     fn read(&self, offset: U256) -> U256;
     /// Write slice of bytes to memory. Does not resize memory!
     fn write_slice(&mut self, offset: U256, _: &[u8]);
+    /// Copy `size` bytes from `source` to `dest`, the areas may overlap. Does not resize memory!
+    fn copy_slice(&mut self, dest: U256, source: U256, size: U256);
     /// Retrieve part of the memory between offset and offset + size
     fn read_slice(&self, offset: U256, size: U256) -> &[u8];
     /// Retrieve writeable part of memory
@@ -86,6 +88,15 @@ impl Memory for Vec<u8> {
         }
     }
 
+    fn copy_slice(&mut self, dest: U256, source: U256, size: U256) {
+        if !size.is_zero() {
+            let dest = dest.low_u64() as usize;
+            let source = source.low_u64() as usize;
+            let size = size.low_u64() as usize;
+            self.copy_within(source..source + size, dest);
+        }
+    }
+
     fn write(&mut self, offset: U256, value: U256) {
         let off = offset.low_u64() as usize;
         value.to_big_endian(&mut self[off..off + 32]);
@@ -161,6 +172,28 @@ mod tests {
         assert_eq!(mem.read(U256::from(0x00)), U256::from(0xabcdef));
     }
 
+    #[test]
+    fn test_memory_copy_slice() {
+        let mem: &mut dyn Memory = &mut vec![];
+        mem.resize(16);
+        mem.write_slice(U256::from(0), "0123456789".as_bytes());
+
+        mem.copy_slice(U256::from(2), U256::from(0), U256::from(8));
+        assert_eq!(
+            mem.read_slice(U256::from(0), U256::from(10)),
+            "0101234567".as_bytes()
+        );
+
+        mem.copy_slice(U256::from(0), U256::from(4), U256::from(4));
+        assert_eq!(
+            mem.read_slice(U256::from(0), U256::from(10)),
+            "2345234567".as_bytes()
+        );
+
+        // zero size copies nothing, even at offsets out of range
+        mem.copy_slice(U256::max_value(), U256::max_value(), U256::zero());
+    }
+
     #[test]
     fn test_memory_read_slice_and_write_slice() {
         let mem: &mut dyn Memory = &mut vec![];
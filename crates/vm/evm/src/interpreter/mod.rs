@@ -387,7 +387,7 @@ impl<Cost: CostType> Interpreter<Cost> {
                     }
                 };
 
-                let info = instruction.info();
+                let info = instruction.info_for(ext.schedule());
                 self.last_stack_ret_len = info.ret;
                 if let Err(e) = self.verify_instruction(ext, instruction, info) {
                     return InterpreterResult::Done(Err(e));
@@ -414,7 +414,7 @@ impl<Cost: CostType> Interpreter<Cost> {
                         self.reader.position - 1,
                         opcode,
                         requirements.gas_cost.as_u256(),
-                        Self::mem_written(instruction, &self.stack),
+                        Self::mem_written(instruction, &self.stack, ext.schedule()),
                         Self::store_written(instruction, &self.stack),
                     );
                 }
@@ -591,8 +591,11 @@ impl<Cost: CostType> Interpreter<Cost> {
             || (instruction == CHAINID && !schedule.have_chain_id)
             || (instruction == SELFBALANCE && !schedule.have_selfbalance)
             || (instruction == BASEFEE && !schedule.eip3198)
-            || ((instruction == BEGINSUB || instruction == JUMPSUB || instruction == RETURNSUB)
+            || (instruction == PUSH0 && !schedule.eip3855)
+            || ((instruction == TLOAD || instruction == TSTORE)
+                && !schedule.eip1153
                 && !schedule.have_subs)
+            || (instruction == MCOPY && !schedule.eip5656 && !schedule.have_subs)
         {
             return Err(vm::Error::BadInstruction {
                 instruction: instruction as u8,
@@ -616,7 +619,11 @@ impl<Cost: CostType> Interpreter<Cost> {
         }
     }
 
-    fn mem_written(instruction: Instruction, stack: &dyn Stack<U256>) -> Option<(usize, usize)> {
+    fn mem_written(
+        instruction: Instruction,
+        stack: &dyn Stack<U256>,
+        schedule: &Schedule,
+    ) -> Option<(usize, usize)> {
         let read = |pos| stack.peek(pos).low_u64() as usize;
         let written = match instruction {
             instructions::MSTORE | instructions::MLOAD => Some((read(0), 32)),
@@ -625,6 +632,7 @@ impl<Cost: CostType> Interpreter<Cost> {
                 Some((read(0), read(2)))
             }
             instructions::EXTCODECOPY => Some((read(1), read(3))),
+            instructions::MCOPY if !schedule.have_subs => Some((read(0), read(2))),
             instructions::CALL | instructions::CALLCODE => Some((read(5), read(6))),
             instructions::DELEGATECALL | instructions::STATICCALL => Some((read(4), read(5))),
             _ => None,
@@ -650,6 +658,35 @@ impl<Cost: CostType> Interpreter<Cost> {
         instruction: Instruction,
         provided: Option<Cost>,
     ) -> vm::Result<InstructionResult<Cost>> {
+        if ext.schedule().have_subs {
+            match instruction {
+                instructions::BEGINSUB => {
+                    return Err(vm::Error::InvalidSubEntry);
+                }
+                instructions::JUMPSUB => {
+                    if self.return_stack.len() >= MAX_SUB_STACK_SIZE {
+                        return Err(vm::Error::OutOfSubStack {
+                            wanted: 1,
+                            limit: MAX_SUB_STACK_SIZE,
+                        });
+                    }
+                    let sub_destination = self.stack.pop_back();
+                    return Ok(InstructionResult::JumpToSubroutine(sub_destination));
+                }
+                instructions::RETURNSUB => {
+                    if let Some(pos) = self.return_stack.pop() {
+                        return Ok(InstructionResult::ReturnFromSubroutine(pos));
+                    } else {
+                        return Err(vm::Error::SubStackUnderflow {
+                            wanted: 1,
+                            on_stack: 0,
+                        });
+                    }
+                }
+                _ => {}
+            }
+        }
+
         match instruction {
             instructions::JUMP => {
                 let jump = self.stack.pop_back();
@@ -665,29 +702,6 @@ impl<Cost: CostType> Interpreter<Cost> {
             instructions::JUMPDEST => {
                 // ignore
             }
-            instructions::BEGINSUB => {
-                return Err(vm::Error::InvalidSubEntry);
-            }
-            instructions::JUMPSUB => {
-                if self.return_stack.len() >= MAX_SUB_STACK_SIZE {
-                    return Err(vm::Error::OutOfSubStack {
-                        wanted: 1,
-                        limit: MAX_SUB_STACK_SIZE,
-                    });
-                }
-                let sub_destination = self.stack.pop_back();
-                return Ok(InstructionResult::JumpToSubroutine(sub_destination));
-            }
-            instructions::RETURNSUB => {
-                if let Some(pos) = self.return_stack.pop() {
-                    return Ok(InstructionResult::ReturnFromSubroutine(pos));
-                } else {
-                    return Err(vm::Error::SubStackUnderflow {
-                        wanted: 1,
-                        on_stack: 0,
-                    });
-                }
-            }
             instructions::CREATE | instructions::CREATE2 => {
                 let endowment = self.stack.pop_back();
                 let init_off = self.stack.pop_back();
@@ -942,6 +956,9 @@ impl<Cost: CostType> Interpreter<Cost> {
                     .collect();
                 ext.log(topics, self.mem.read_slice(offset, size))?;
             }
+            instructions::PUSH0 => {
+                self.stack.push(U256::zero());
+            }
             instructions::PUSH1
             | instructions::PUSH2
             | instructions::PUSH3
@@ -997,6 +1014,12 @@ impl<Cost: CostType> Interpreter<Cost> {
             instructions::MSIZE => {
                 self.stack.push(U256::from(self.mem.size()));
             }
+            instructions::MCOPY => {
+                let dest_offset = self.stack.pop_back();
+                let source_offset = self.stack.pop_back();
+                let size = self.stack.pop_back();
+                self.mem.copy_slice(dest_offset, source_offset, size);
+            }
             instructions::SHA3 => {
                 let offset = self.stack.pop_back();
                 let size = self.stack.pop_back();
@@ -1033,6 +1056,16 @@ impl<Cost: CostType> Interpreter<Cost> {
                 ext.set_storage(key, BigEndianHash::from_uint(&val))?;
                 ext.al_insert_storage_key(self.params.address, key);
             }
+            instructions::TLOAD => {
+                let key = BigEndianHash::from_uint(&self.stack.pop_back());
+                let word = ext.transient_storage_at(&key)?.into_uint();
+                self.stack.push(word);
+            }
+            instructions::TSTORE => {
+                let key = BigEndianHash::from_uint(&self.stack.pop_back());
+                let val = self.stack.pop_back();
+                ext.set_transient_storage(key, BigEndianHash::from_uint(&val))?;
+            }
             instructions::PC => {
                 self.stack.push(U256::from(self.reader.position - 1));
             }
@@ -490,6 +490,138 @@ fn test_base_fee(factory: super::Factory) {
     );
 }
 
+evm_test! {test_push0: test_push0_int}
+fn test_push0(factory: super::Factory) {
+    let code = "60015f55".from_hex().unwrap();
+
+    let mut params = ActionParams::default();
+    params.gas = U256::from(100_000);
+    params.code = Some(Arc::new(code));
+    let mut ext = FakeExt::new_shanghai(
+        Address::from_str("0000000000000000000000000000000000000000").unwrap(),
+        Address::from_str("000000000000000000000000636F6E7472616374").unwrap(),
+        &[],
+    );
+
+    let gas_left = {
+        let vm = factory.create(params, ext.schedule(), ext.depth());
+        test_finalize(vm.exec(&mut ext).ok().unwrap()).unwrap()
+    };
+
+    assert_eq!(gas_left, U256::from(77_895));
+    assert_store(
+        &ext,
+        0,
+        "0000000000000000000000000000000000000000000000000000000000000001",
+    );
+}
+
+#[test]
+fn test_push0_before_shanghai_int() {
+    let factory = super::Factory::new(VMType::Interpreter, 1024 * 32);
+    let code = "5f".from_hex().unwrap();
+
+    let mut params = ActionParams::default();
+    params.gas = U256::from(100_000);
+    params.code = Some(Arc::new(code));
+    let mut ext = FakeExt::new_london(
+        Address::from_str("0000000000000000000000000000000000000000").unwrap(),
+        Address::from_str("000000000000000000000000636F6E7472616374").unwrap(),
+        &[],
+    );
+
+    let err = {
+        let vm = factory.create(params, ext.schedule(), ext.depth());
+        test_finalize(vm.exec(&mut ext).ok().unwrap()).unwrap_err()
+    };
+
+    match err {
+        vm::Error::BadInstruction { instruction: 0x5f } => (),
+        _ => assert!(false, "Expected bad instruction"),
+    }
+}
+
+#[test]
+fn test_initcode_size_limit_int() {
+    let factory = super::Factory::new(VMType::Interpreter, 1024 * 32);
+    // CREATE with 49153 bytes of initcode, one byte above the limit
+    let code = "6200c00160006000f0".from_hex().unwrap();
+
+    let mut params = ActionParams::default();
+    params.gas = U256::from(100_000);
+    params.code = Some(Arc::new(code));
+    let mut ext = FakeExt::new_shanghai(
+        Address::from_str("0000000000000000000000000000000000000000").unwrap(),
+        Address::from_str("000000000000000000000000636F6E7472616374").unwrap(),
+        &[],
+    );
+
+    let err = {
+        let vm = factory.create(params, ext.schedule(), ext.depth());
+        test_finalize(vm.exec(&mut ext).ok().unwrap()).unwrap_err()
+    };
+
+    match err {
+        vm::Error::OutOfGas => (),
+        _ => assert!(false, "Expected out of gas"),
+    }
+}
+
+evm_test! {test_transient_storage: test_transient_storage_int}
+fn test_transient_storage(factory: super::Factory) {
+    // TSTORE 0x2a at key 1, TLOAD key 1 and SSTORE the value at key 0
+    let code = "602a60015d60015c600055".from_hex().unwrap();
+
+    let mut params = ActionParams::default();
+    params.gas = U256::from(100_000);
+    params.code = Some(Arc::new(code));
+    let mut ext = FakeExt::new_cancun(
+        Address::from_str("0000000000000000000000000000000000000000").unwrap(),
+        Address::from_str("000000000000000000000000636F6E7472616374").unwrap(),
+        &[],
+    );
+
+    let gas_left = {
+        let vm = factory.create(params, ext.schedule(), ext.depth());
+        test_finalize(vm.exec(&mut ext).ok().unwrap()).unwrap()
+    };
+
+    assert_eq!(gas_left, U256::from(77_688));
+    assert_eq!(ext.transient_store.len(), 1);
+    assert_store(
+        &ext,
+        0,
+        "000000000000000000000000000000000000000000000000000000000000002a",
+    );
+}
+
+evm_test! {test_mcopy: test_mcopy_int}
+fn test_mcopy(factory: super::Factory) {
+    // MSTORE 0x42 at 0, MCOPY 32 bytes from 0 to 32, MLOAD 32 and SSTORE the word at key 0
+    let code = "60426000526020600060205e602051600055".from_hex().unwrap();
+
+    let mut params = ActionParams::default();
+    params.gas = U256::from(100_000);
+    params.code = Some(Arc::new(code));
+    let mut ext = FakeExt::new_cancun(
+        Address::from_str("0000000000000000000000000000000000000000").unwrap(),
+        Address::from_str("000000000000000000000000636F6E7472616374").unwrap(),
+        &[],
+    );
+
+    let gas_left = {
+        let vm = factory.create(params, ext.schedule(), ext.depth());
+        test_finalize(vm.exec(&mut ext).ok().unwrap()).unwrap()
+    };
+
+    assert_eq!(gas_left, U256::from(77_861));
+    assert_store(
+        &ext,
+        0,
+        "0000000000000000000000000000000000000000000000000000000000000042",
+    );
+}
+
 evm_test! {test_gas_limit: test_gas_limit_int}
 fn test_gas_limit(factory: super::Factory) {
     let gas_limit = U256::from(0x1234);
@@ -34,6 +34,12 @@ pub const EIP3529_MAX_REFUND_QUOTIENT: usize = 5;
 /// Reduced SSTORE refund as by EIP-3529
 pub const EIP3529_SSTORE_CLEARS_SCHEDULE: usize =
     EIP2929_SSTORE_RESET_GAS + EIP2930_ACCESS_LIST_STORAGE_KEY_COST;
+/// Gas per 32 byte word of initcode as by EIP-3860
+pub const EIP3860_INITCODE_WORD_GAS: usize = 2;
+/// Maximum size of the initcode of a contract creation as by EIP-3860
+pub const EIP3860_MAX_INITCODE_SIZE: usize = 49152;
+/// Gas per TLOAD and TSTORE as by EIP-1153
+pub const EIP1153_TRANSIENT_STORAGE_GAS: usize = EIP2929_WARM_STORAGE_READ_COST;
 
 /// Definition of the cost schedule and other parameterisations for the EVM.
 #[derive(Debug)]
@@ -173,6 +179,18 @@ pub struct Schedule {
     pub max_refund_quotient: usize,
     // Enable EIP-3541 rule
     pub eip3541: bool,
+    /// Enable PUSH0 opcode (EIP-3855)
+    pub eip3855: bool,
+    /// Limit and meter initcode (EIP-3860)
+    pub eip3860: bool,
+    /// Start transactions with a warm COINBASE (EIP-3651)
+    pub eip3651: bool,
+    /// Enable TLOAD and TSTORE opcodes for transient storage (EIP-1153)
+    pub eip1153: bool,
+    /// Enable MCOPY opcode (EIP-5656)
+    pub eip5656: bool,
+    /// SELFDESTRUCT only deletes accounts created in the same transaction (EIP-6780)
+    pub eip6780: bool,
 }
 
 /// Wasm cost table
@@ -326,6 +344,12 @@ impl Schedule {
             eip3198: false,
             max_refund_quotient: MAX_REFUND_QUOTIENT,
             eip3541: false,
+            eip3855: false,
+            eip3860: false,
+            eip3651: false,
+            eip1153: false,
+            eip5656: false,
+            eip6780: false,
         }
     }
 
@@ -400,6 +424,30 @@ impl Schedule {
         schedule
     }
 
+    /// Schedule for the Shanghai fork of the Ethereum main net.
+    pub fn new_shanghai() -> Schedule {
+        let mut schedule = Self::new_london();
+
+        schedule.eip3651 = true;
+        schedule.eip3855 = true;
+        schedule.eip3860 = true;
+
+        schedule
+    }
+
+    /// Schedule for the Shanghai fork with the EIP-1153, EIP-5656 and EIP-6780 changes of Cancun.
+    pub fn new_cancun() -> Schedule {
+        let mut schedule = Self::new_shanghai();
+        // The subroutine opcodes are reassigned to TLOAD, TSTORE and MCOPY.
+        schedule.have_subs = false;
+
+        schedule.eip1153 = true;
+        schedule.eip5656 = true;
+        schedule.eip6780 = true;
+
+        schedule
+    }
+
     fn new(efcd: bool, hdc: bool, tcg: usize) -> Schedule {
         Schedule {
             exceptional_failed_code_deposit: efcd,
@@ -469,6 +517,12 @@ impl Schedule {
             eip3198: false,
             max_refund_quotient: MAX_REFUND_QUOTIENT,
             eip3541: false,
+            eip3855: false,
+            eip3860: false,
+            eip3651: false,
+            eip1153: false,
+            eip5656: false,
+            eip6780: false,
         }
     }
 